@@ -1,13 +1,26 @@
-use std::{fs::File, io::Read, num::ParseIntError, path::Path};
+use std::{
+    fs::File,
+    hash::{Hash, Hasher},
+    io::Read,
+    num::ParseIntError,
+    path::Path,
+};
 
 use pathfinding::grid::Grid as PFGrid;
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    data::{size, square, Cell, GridPos, GridSize, GridSquare},
-    gen::room_based::GridClassification,
+    data::{
+        pos, size, square, square_radius, Cell, Direction, GridBounds, GridPos, GridRanges,
+        GridSize, GridSquare,
+    },
+    gen::{room_based::GridClassification, rooms::Room},
     logging::{error, info, trace, warn},
-    util::TriState,
+    pipe::{Pipeline, PipelineError, PipelineResult, PipelineStep},
+    util::{
+        math::{absdiff, bresenham_line, bresenham_line_thick},
+        TriState,
+    },
 };
 
 /// An iterator over all of the cells in a grid, in row-major order.
@@ -65,6 +78,73 @@ pub type MapFileParseResult = Result<(MapGrid, GridPos, GridPos), Vec<String>>;
 #[derive(Debug, Clone)]
 pub struct MapParseError(String);
 
+/// The details behind [`MapGrid::is_valid_maze`], see [`MapGrid::maze_report`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MazeReport {
+    /// The number of open (off) cells.
+    pub open_cells: usize,
+    /// The number of separate 4-connected open regions.
+    pub open_components: usize,
+    /// The number of open cells outside of the largest open region, which can never be reached from it.
+    pub unreachable_cells: usize,
+    /// The number of invalid cells.
+    pub invalid_cells: usize,
+    /// Every open cell on the border of the grid, see [`MapGrid::border_openings`].
+    pub exits: Vec<(GridPos, Direction)>,
+}
+
+impl MazeReport {
+    /// Checks whether the report describes a valid maze: there is exactly one open region (so every
+    /// open cell, including every exit, is reachable from every other) and no invalid cells.
+    #[must_use]
+    pub fn is_valid(&self) -> bool {
+        self.open_components == 1 && self.unreachable_cells == 0 && self.invalid_cells == 0
+    }
+}
+
+/// How the 4-connected open (off) regions changed between two grids, see [`MapGrid::region_diff`].
+/// Regions are matched up by whether they share any cells.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RegionDiff {
+    /// The number of open regions in the first grid.
+    pub regions_before: usize,
+    /// The number of open regions in the second grid.
+    pub regions_after: usize,
+    /// Regions in the second grid that do not overlap any region of the first.
+    pub appeared: usize,
+    /// Regions in the first grid that do not overlap any region of the second.
+    pub vanished: usize,
+    /// Regions in the second grid that overlap two or more regions of the first.
+    pub merged: usize,
+    /// Regions in the first grid that overlap two or more regions of the second.
+    pub split: usize,
+    /// The number of open cells in the second grid minus the number in the first.
+    pub area_change: isize,
+}
+
+/// Options controlling how [`MapGrid::parse_string_with_options`] interprets its input.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ParseOptions {
+    /// If true, every row of the grid data must be the same length as the first row (a single trailing
+    /// newline at the end of the input is ignored). When false (the default) the widest row determines
+    /// the grid width and shorter rows are padded at the end (on the right) with invalid cells.
+    pub strict_rectangular: bool,
+    /// If set, cells matching this character are parsed as invalid cells instead of being reported as
+    /// errors. This allows reading back grids written by [`MapGrid::to_strings_with`].
+    pub invalid: Option<char>,
+}
+
+/// Statistics about a single connected region of a [`MapGrid`], see [`MapGrid::component_stats`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ComponentStat {
+    /// The number of cells in the region.
+    pub size: usize,
+    /// The smallest square containing every cell of the region.
+    pub bounding_square: GridSquare,
+    /// The average position of the cells in the region, rounded to the nearest cell.
+    pub centroid: GridPos,
+}
+
 /// A map or grid of cells.
 #[derive(Clone, Deserialize, Serialize)]
 #[allow(clippy::module_name_repetitions)]
@@ -157,6 +237,44 @@ impl MapGrid {
         }
     }
 
+    /// Non-panicking version of [`MapGrid::new`], setting all cells to `Invalid`.
+    ///
+    /// ### Errors
+    /// Function errors if the size provided is less than 3x3.
+    pub fn try_new<Size: Into<GridSize> + std::fmt::Debug>(size: Size) -> Result<Self, String> {
+        trace!("MapGrid::try_new({:?})", size);
+        let size = size.into();
+        Self::check_size(size)?;
+
+        Ok(Self::new(size))
+    }
+
+    /// Non-panicking version of [`MapGrid::empty`], setting all cells to `False` or `off`.
+    ///
+    /// ### Errors
+    /// Function errors if the size provided is less than 3x3.
+    pub fn try_empty<Size: Into<GridSize> + std::fmt::Debug>(size: Size) -> Result<Self, String> {
+        trace!("MapGrid::try_empty({:?})", size);
+        let size = size.into();
+        Self::check_size(size)?;
+
+        Ok(Self::empty(size))
+    }
+
+    /// Checks that `size` is at least the minimum grid size of 3x3.
+    fn check_size(size: GridSize) -> Result<(), String> {
+        if size.width < 3 || size.height < 3 {
+            let msg = format!(
+                "Grid must be at least 3x3, but {}x{} was requested",
+                size.width, size.height
+            );
+            error!("{}", msg);
+            return Err(msg);
+        }
+
+        Ok(())
+    }
+
     /// Creates a new *named* grid with the given width and height, setting all cells to `False` or `off`.
     #[must_use]
     pub fn empty_named<
@@ -282,6 +400,37 @@ impl MapGrid {
         grid
     }
 
+    /// Creates a grid with 4-fold mirror symmetry. An empty grid the size of the top left quadrant
+    /// (rounded up, and at least 3x3) is passed to `inner` to be generated, and the result is then
+    /// mirrored horizontally and vertically to fill the full `size` using [`MapGrid::kaleidoscope`].
+    ///
+    /// `inner` is also given a local [`fastrand::Rng`] seeded with `seed` (see
+    /// [`crate::util::random::with_rng`]), so generators using it produce the same map for the same
+    /// seed without touching the global RNG.
+    ///
+    /// ### Panics
+    /// Function panics if the size given is less than 3x3.
+    #[must_use]
+    pub fn generate_quadrant_symmetric<Size: Into<GridSize> + std::fmt::Debug>(
+        size: Size,
+        seed: u64,
+        inner: impl FnOnce(&mut MapGrid, &mut fastrand::Rng),
+    ) -> Self {
+        trace!("MapGrid::generate_quadrant_symmetric({:?}, {})", size, seed);
+        let (width, height) = size.into().into();
+        let mut quadrant = Self::empty((((width + 1) / 2).max(3), ((height + 1) / 2).max(3)));
+        crate::util::random::with_rng(seed, |rng| inner(&mut quadrant, rng));
+
+        let mut grid = Self::empty((width, height));
+        for ((x, y), cell) in grid.iter_pos_mut() {
+            if x < quadrant.width && y < quadrant.height {
+                *cell = quadrant.cells[y][x];
+            }
+        }
+
+        grid.kaleidoscope()
+    }
+
     /// Creates a copy of the given grid. If the given grid has a name,
     /// the returned copy will be named "<Name> (Copy)"
     #[must_use]
@@ -437,6 +586,7 @@ impl MapGrid {
 
     /// Combines the data from the `first` [`MapGrid`] with the data from the
     /// `other` [`MapGrid`], prioritizing the data in `other` for any conflicts.
+    /// The result is named the same way as [`MapGrid::integrate`].
     #[must_use]
     pub fn union(first: &Self, other: &Self) -> Self {
         Self::integrate(first, other, (0, 0))
@@ -445,6 +595,9 @@ impl MapGrid {
     /// Creates a new [`MapGrid`] using the existing data from this instance,
     /// adding the data from the other instance.
     ///
+    /// The result is named `"<first> + <other>"` if both grids are named, keeps the single name if
+    /// only one of them is, and is unnamed otherwise.
+    ///
     /// #### Does *not* modify the original existing instance.
     #[must_use]
     pub fn integrate<P: Into<(usize,usize)>>(first: &Self, other: &Self, offset: P) -> Self {
@@ -461,6 +614,11 @@ impl MapGrid {
         for ((x, y), &cell) in other.iter_pos() {
             result.set_cell(x + start_x, y + start_y, cell);
         }
+        result.name = match (&first.name, &other.name) {
+            (Some(a), Some(b)) => Some(format!("{} + {}", a, b)),
+            (Some(name), None) | (None, Some(name)) => Some(name.clone()),
+            (None, None) => None,
+        };
 
         result
     }
@@ -471,13 +629,58 @@ impl MapGrid {
     ///
     /// ### Errors
     /// Function will return an error if the string does not form a valid grid.
-    #[allow(clippy::too_many_lines)]
+    ///
+    /// ##### See also: [`MapGrid::parse_string_with`], [`MapGrid::parse_string_with_options`]
     pub fn parse_string<S: AsRef<str> + std::fmt::Debug>(
         input: S,
         on: char,
         off: char,
     ) -> Result<Self, Vec<String>> {
-        trace!("MapGrid::parse_string({:?}, {}, {})", input, on, off);
+        Self::parse_string_with(input, on, off, None)
+    }
+
+    /// Same as [`MapGrid::parse_string`], but if `invalid` is given any cell matching it is parsed as
+    /// an invalid cell rather than reported as an error.
+    ///
+    /// ### Errors
+    /// Function will return an error if the string does not form a valid grid.
+    pub fn parse_string_with<S: AsRef<str> + std::fmt::Debug>(
+        input: S,
+        on: char,
+        off: char,
+        invalid: Option<char>,
+    ) -> Result<Self, Vec<String>> {
+        Self::parse_string_with_options(
+            input,
+            on,
+            off,
+            ParseOptions {
+                invalid,
+                ..ParseOptions::default()
+            },
+        )
+    }
+
+    /// Same as [`MapGrid::parse_string`], but with the given [`ParseOptions`] applied.
+    ///
+    /// ### Errors
+    /// Function will return an error if the string does not form a valid grid, or if
+    /// [`ParseOptions::strict_rectangular`] is set and any row differs in length from the first
+    /// (the error lists the offending line numbers).
+    #[allow(clippy::too_many_lines)]
+    pub fn parse_string_with_options<S: AsRef<str> + std::fmt::Debug>(
+        input: S,
+        on: char,
+        off: char,
+        options: ParseOptions,
+    ) -> Result<Self, Vec<String>> {
+        trace!(
+            "MapGrid::parse_string_with_options({:?}, {}, {}, {:?})",
+            input,
+            on,
+            off,
+            options
+        );
 
         if on == 'S' || on == 'E' {
             warn!("MapGrid::parse_string - ON character should not be S or E, these are used to designate start and end position in maze files.");
@@ -504,17 +707,21 @@ impl MapGrid {
         trace!("MapGrid::parse_string - split: {:?}", split);
 
         let mut name = None;
+        let mut header_lines = 0;
 
-        if split[0].starts_with(|c: char| c != on && c != off && c.is_alphabetic()) {
+        let is_cell = |c: char| c == on || c == off || options.invalid == Some(c);
+        if split[0].starts_with(|c: char| !is_cell(c) && c.is_alphabetic()) {
             info!("MapGrid::parse_string - Found unexpected character at start of line, assuming grid name: {:?}", split[0]);
             name = Some(split.remove(0));
+            header_lines += 1;
         }
 
         let (mut width, mut height) = (0usize, 0usize);
 
-        if split[0].starts_with(|c: char| c != on && c != off && c.is_numeric()) {
+        if split[0].starts_with(|c: char| !is_cell(c) && c.is_numeric()) {
             info!("MapGrid::parse_string - Found unexpected character at start of line, assuming grid dimensions: {:?}", split[0]);
             let line = split.remove(0);
+            header_lines += 1;
             let halves = line
                 .split_ascii_whitespace()
                 .map(std::string::ToString::to_string)
@@ -533,12 +740,40 @@ impl MapGrid {
             }
         }
 
+        if options.strict_rectangular {
+            // A single trailing newline at the end of the input is not a row
+            if split.len() > 1 && split[split.len() - 1].is_empty() {
+                split.pop();
+            }
+
+            if let Some(first) = split.first() {
+                let expected = first.chars().count();
+                let mismatched = split
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, line)| line.chars().count() != expected)
+                    .map(|(i, _)| (i + header_lines + 1).to_string())
+                    .collect::<Vec<_>>();
+                if !mismatched.is_empty() {
+                    let msg = format!(
+                        "MapGrid::parse_string - Line(s) {} differ in length from the first row ({})",
+                        mismatched.join(", "),
+                        expected
+                    );
+                    error!("{}", &msg);
+                    errors.push(msg);
+                    return Err(errors);
+                }
+            }
+        }
+
         if width == 0 {
             width = {
                 let mut max = 0;
                 for s in &split {
-                    if s.len() > max {
-                        max = s.len();
+                    let len = s.chars().count();
+                    if len > max {
+                        max = len;
                     }
                 }
 
@@ -591,6 +826,8 @@ impl MapGrid {
                         y
                     );
                     grid.set_cell_state(x, y, false);
+                } else if options.invalid == Some(ch) {
+                    grid.set_cell_invalid(x, y);
                 } else {
                     errors.push(format!("Invalid character {} at ({},{})", ch, x, y));
                     grid.set_cell_invalid(x, y);
@@ -613,6 +850,60 @@ impl MapGrid {
         }
     }
 
+    /// Same as [`MapGrid::parse_string`], but works out the on and off characters from the input
+    /// instead of taking them as arguments. The input must contain exactly two distinct cell
+    /// characters (ignoring whitespace and the `S`/`G` start and goal markers); the more frequent one
+    /// is taken to be the floor (off) and the other the wall (on), with ties going to the character
+    /// that appears first. Leading name and size header lines are skipped while counting, which
+    /// assumes neither cell character is a letter or a digit.
+    ///
+    /// Returns the parsed grid along with the detected on and off characters, in that order.
+    ///
+    /// ### Errors
+    /// Function will return an error if the input does not contain exactly two cell characters, or if
+    /// it does not form a valid grid.
+    pub fn parse_string_autodetect<S: AsRef<str> + std::fmt::Debug>(
+        input: S,
+    ) -> Result<(Self, char, char), Vec<String>> {
+        trace!("MapGrid::parse_string_autodetect({:?})", input);
+        let mut lines = input.as_ref().lines().peekable();
+        lines.next_if(|l| l.starts_with(|c: char| c.is_alphabetic() && c != 'S' && c != 'G'));
+        lines.next_if(|l| l.starts_with(|c: char| c.is_numeric()));
+
+        let mut counts: Vec<(char, usize)> = Vec::new();
+        for ch in lines.flat_map(str::chars) {
+            if ch.is_whitespace() || ch == 'S' || ch == 'G' {
+                continue;
+            }
+
+            match counts.iter_mut().find(|(c, _)| *c == ch) {
+                Some((_, n)) => *n += 1,
+                None => counts.push((ch, 1)),
+            }
+        }
+
+        if counts.len() != 2 {
+            let msg = format!(
+                "MapGrid::parse_string_autodetect - Expected exactly 2 cell characters, found {:?}",
+                counts.iter().map(|(c, _)| *c).collect::<Vec<_>>()
+            );
+            error!("{}", msg);
+            return Err(vec![msg]);
+        }
+
+        let (on, off) = if counts[1].1 > counts[0].1 {
+            (counts[0].0, counts[1].0)
+        } else {
+            (counts[1].0, counts[0].0)
+        };
+        info!(
+            "MapGrid::parse_string_autodetect - Detected on = {:?}, off = {:?}",
+            on, off
+        );
+
+        Self::parse_string(input, on, off).map(|grid| (grid, on, off))
+    }
+
     /// ## [`MapGrid::parse_file`](`crate::data::MapGrid::parse_file`)
     /// Parse a plain text file into a [`MapGrid`].
     ///
@@ -841,6 +1132,72 @@ impl MapGrid {
         self.width * self.height
     }
 
+    /// Gets a hash of the size and cell states of this grid, ignoring its name. Two grids with the same
+    /// layout always have the same checksum, so it can be used to tell whether a grid has changed.
+    /// The value is only stable within a single build of the library, it should not be persisted.
+    #[must_use]
+    pub fn checksum(&self) -> u64 {
+        trace!("MapGrid::checksum()");
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        (self.width, self.height).hash(&mut hasher);
+        for cell in self.iter() {
+            cell.hash(&mut hasher);
+        }
+
+        hasher.finish()
+    }
+
+    /// Gets the fraction of cells (from 0.0 to 1.0) whose state is the same in this grid and `other`,
+    /// which makes a useful fuzzy comparison for the output of random generators. Names are ignored.
+    ///
+    /// ### Errors
+    /// Function will return an error if the two grids are not the same size.
+    #[allow(clippy::cast_precision_loss)]
+    pub fn similarity(&self, other: &Self) -> Result<f64, String> {
+        trace!("MapGrid::similarity()");
+        if self.size() != other.size() {
+            let msg = format!(
+                "MapGrid::similarity - Grid sizes differ: {:?} vs {:?}",
+                self.size(),
+                other.size()
+            );
+            warn!("{}", msg);
+            return Err(msg);
+        }
+
+        let matching = self
+            .iter()
+            .zip(other.iter())
+            .filter(|(a, b)| a.state() == b.state())
+            .count();
+
+        Ok(matching as f64 / self.cell_count() as f64)
+    }
+
+    /// Gets the number of cells inside of `square` whose state matches `state`. The square is clamped
+    /// to the bounds of the grid, so any part of it lying outside is simply not counted.
+    #[must_use]
+    pub fn count_state_in_square(&self, square: &GridSquare, state: TriState) -> usize {
+        trace!("MapGrid::count_state_in_square({:?}, {:?})", square, state);
+        let (xs, ys) = square.ranges_usize();
+        let (xs, ys) = (
+            xs.start..xs.end.min(self.width),
+            ys.start..ys.end.min(self.height),
+        );
+        self.cells
+            .get(ys)
+            .unwrap_or_default()
+            .iter()
+            .map(|row| {
+                row.get(xs.clone())
+                    .unwrap_or_default()
+                    .iter()
+                    .filter(|cell| cell.state() == state)
+                    .count()
+            })
+            .sum()
+    }
+
     /// Gets the number of cells in the grid whose state is on.
     #[must_use]
     pub fn on_cells_count(&self) -> usize {
@@ -869,6 +1226,23 @@ impl MapGrid {
         self.iter().filter(|&&c| c.is_invalid()).count()
     }
 
+    /// Counts the (**on**, **off**, **invalid**) cells in the grid in a single pass. Prefer this over
+    /// calling the individual counting functions when more than one count is needed.
+    #[must_use]
+    pub fn count_all(&self) -> (usize, usize, usize) {
+        trace!("MapGrid::count_all()");
+        let (mut on, mut off, mut invalid) = (0usize, 0usize, 0usize);
+        for cell in self.iter() {
+            match cell.state() {
+                TriState::True => on += 1,
+                TriState::False => off += 1,
+                TriState::Invalid => invalid += 1,
+            }
+        }
+
+        (on, off, invalid)
+    }
+
     /// Calculates the percentage of (**on**, **off**, **invalid**) cells in the grid.
     #[allow(clippy::cast_precision_loss)]
     #[must_use]
@@ -880,17 +1254,7 @@ impl MapGrid {
             return (0.0, 0.0, 0.0);
         }
 
-        let mut on = 0usize;
-        let mut off = 0usize;
-
-        for cell in self.iter() {
-            if cell.is_on() {
-                on += 1;
-            } else if cell.is_off() {
-                off += 1;
-            }
-        }
-
+        let (on, off, _) = self.count_all();
         let on_ratio = (on as f64) / (total as f64);
         let off_ratio = (off as f64) / (total as f64);
         let invalid_ratio = 1.0 - on_ratio - off_ratio;
@@ -986,6 +1350,29 @@ impl MapGrid {
         self.set_cell(x, y, Cell::invalid());
     }
 
+    /// Sets each cell in `cells` to its paired value, returning how many were in bounds and applied.
+    ///
+    /// Out of bounds entries are skipped (and logged) rather than treated as errors, which makes this
+    /// convenient for rasterizing computed point sets such as [`crate::util::math::bresenham_line`] output.
+    pub fn set_cells<I: IntoIterator<Item = (GridPos, Cell)>>(&mut self, cells: I) -> usize {
+        trace!("MapGrid::set_cells(...)");
+        let mut applied = 0;
+        for (pos, cell) in cells {
+            if pos.x >= self.width || pos.y >= self.height {
+                warn!(
+                    "MapGrid::set_cells - skipping out of bounds position ({},{}) on grid of size ({},{})",
+                    pos.x, pos.y, self.width, self.height
+                );
+                continue;
+            }
+
+            self.cells[pos.y][pos.x] = cell;
+            applied += 1;
+        }
+
+        applied
+    }
+
     /// Sets all cells in the [`MapGrid`] to the given `state`.
     pub fn set_all_cells(&mut self, state: bool) {
         trace!("MapGrid::set_all_cells({})", state);
@@ -1001,6 +1388,20 @@ impl MapGrid {
         }
     }
 
+    /// Sets every cell whose state is currently `from` to `to`, returning how many cells were changed.
+    pub fn replace_state(&mut self, from: TriState, to: TriState) -> usize {
+        trace!("MapGrid::replace_state({:?}, {:?})", from, to);
+        let mut replaced = 0;
+        for cell in self.iter_mut() {
+            if cell.state() == from {
+                cell.set_state(to);
+                replaced += 1;
+            }
+        }
+
+        replaced
+    }
+
     /// Set all cells in the first and last rows and columns to the given state.
     pub fn set_outer_cells(&mut self, state: bool) {
         trace!("MapGrid::set_outer_cells({})", state);
@@ -1013,6 +1414,104 @@ impl MapGrid {
         }
     }
 
+    /// Sets all cells within the outermost `thickness` rings of the grid to the given state. A `thickness`
+    /// of 1 is the same as [`MapGrid::set_outer_cells`]. If `thickness` covers at least half of the
+    /// smaller dimension the whole grid is filled and a warning is logged.
+    pub fn set_border(&mut self, thickness: usize, state: bool) {
+        trace!("MapGrid::set_border({}, {})", thickness, state);
+        let (width, height) = (self.width, self.height);
+        if thickness * 2 >= width.min(height) {
+            warn!(
+                "MapGrid::set_border - thickness {} fills the entire {}x{} grid",
+                thickness, width, height
+            );
+        }
+
+        for ((x, y), cell) in self.iter_pos_mut() {
+            let ring = x.min(width - 1 - x).min(y).min(height - 1 - y);
+            if ring < thickness {
+                cell.set_state(state.into());
+            }
+        }
+    }
+
+    /// Sets every cell inside the ellipse centered on `center` with the horizontal radius `rx` and
+    /// vertical radius `ry` to the given state, using the standard `(dx/rx)^2 + (dy/ry)^2 <= 1` test.
+    /// Cells outside of the grid are ignored.
+    pub fn fill_ellipse(&mut self, center: GridPos, rx: usize, ry: usize, state: bool) {
+        trace!(
+            "MapGrid::fill_ellipse({:?}, {}, {}, {})",
+            center,
+            rx,
+            ry,
+            state
+        );
+        for (x, y) in self.ellipse_cells(center, rx, ry, false) {
+            self.cells[y][x].set_state(state.into());
+        }
+    }
+
+    /// Sets the outline of the ellipse described in [`MapGrid::fill_ellipse`] to the given state. The
+    /// outline is every cell inside the ellipse that has an orthogonal neighbor outside of it.
+    pub fn draw_ellipse(&mut self, center: GridPos, rx: usize, ry: usize, state: bool) {
+        trace!(
+            "MapGrid::draw_ellipse({:?}, {}, {}, {})",
+            center,
+            rx,
+            ry,
+            state
+        );
+        for (x, y) in self.ellipse_cells(center, rx, ry, true) {
+            self.cells[y][x].set_state(state.into());
+        }
+    }
+
+    /// Gets the in-bounds cells of an ellipse, either all of them or only those on the outline.
+    #[allow(clippy::cast_possible_wrap, clippy::cast_precision_loss)]
+    fn ellipse_cells(
+        &self,
+        center: GridPos,
+        rx: usize,
+        ry: usize,
+        outline: bool,
+    ) -> Vec<(usize, usize)> {
+        let axis = |d: isize, r: usize| {
+            if r == 0 {
+                if d == 0 {
+                    0.0
+                } else {
+                    f64::INFINITY
+                }
+            } else {
+                (d as f64 / r as f64).powi(2)
+            }
+        };
+        let inside = |dx: isize, dy: isize| axis(dx, rx) + axis(dy, ry) <= 1.0;
+
+        let mut cells = Vec::new();
+        let (cx, cy) = (center.x as isize, center.y as isize);
+        for y in center.y.saturating_sub(ry)..(center.y + ry + 1).min(self.height) {
+            for x in center.x.saturating_sub(rx)..(center.x + rx + 1).min(self.width) {
+                let (dx, dy) = (x as isize - cx, y as isize - cy);
+                if !inside(dx, dy) {
+                    continue;
+                }
+                if outline
+                    && inside(dx - 1, dy)
+                    && inside(dx + 1, dy)
+                    && inside(dx, dy - 1)
+                    && inside(dx, dy + 1)
+                {
+                    continue;
+                }
+
+                cells.push((x, y));
+            }
+        }
+
+        cells
+    }
+
     /// Toggles the cell at the given x and y, turning True to False, False to True, and Invalid to Invalid.
     pub fn toggle_cell(&mut self, x: usize, y: usize) {
         trace!("MapGrid::toggle_cell({}, {})", x, y);
@@ -1021,7 +1520,10 @@ impl MapGrid {
         }
     }
 
-    /// Gets the coordinates of the neighbors to the given cell, truncating edges.
+    /// Gets the coordinates of the neighbors to the given cell, truncating edges. Positions are
+    /// returned in row-major order (the same order as [`MapGrid::neighbor_positions_wrapping`]), so a
+    /// cell in the middle of the grid has 8 neighbors, a cell on an edge has 5, and a corner cell has 3.
+    /// A position outside of the grid has no neighbors.
     #[must_use]
     pub fn neighbor_positions<P: Into<(usize, usize)>>(
         &self,
@@ -1029,1168 +1531,5088 @@ impl MapGrid {
     ) -> Vec<(usize, usize)> {
         let pos = target_pos.into();
         trace!("MapGrid::neighbor_positions(pos = {:?})", pos);
-        let xs: Vec<usize> = if pos.0 == 0 {
-            vec![0, 1]
-        } else if pos.0 == self.width - 1 {
-            vec![self.width - 2, self.width - 1]
-        } else {
-            vec![pos.0 - 1, pos.0, pos.0 + 1]
-        };
+        if pos.0 >= self.width || pos.1 >= self.height {
+            warn!(
+                "MapGrid::neighbor_positions - {:?} is outside of the grid",
+                pos
+            );
+            return Vec::new();
+        }
 
-        let ys: Vec<usize> = if pos.1 == 0 {
-            vec![0, 1]
-        } else if pos.1 == self.height - 1 {
-            vec![self.height - 2, self.height - 1]
-        } else {
-            vec![pos.1 - 1, pos.1, pos.1 + 1]
-        };
+        let xs = pos.0.saturating_sub(1)..=(pos.0 + 1).min(self.width - 1);
+        let ys = pos.1.saturating_sub(1)..=(pos.1 + 1).min(self.height - 1);
 
-        let mut positions = Vec::new();
-        for x in xs {
-            for y in &ys {
-                if (x, *y) == pos {
+        let mut positions = Vec::with_capacity(8);
+        for y in ys {
+            for x in xs.clone() {
+                if (x, y) == pos {
                     continue;
                 }
 
-                positions.push((x, *y));
+                positions.push((x, y));
             }
         }
 
         positions
     }
 
-    /// Gets the coordinates of the neighbors to the given cell, wrapping on edges.
+    /// Gets the coordinates of the neighbors to the given cell, wrapping on edges. Positions are
+    /// returned in row-major order relative to the cell (the row above first, then the cell's own row,
+    /// then the row below), and each position appears only once, so any cell of a grid that is at least
+    /// 3x3 has exactly 8 distinct neighbors. A position outside of the grid has no neighbors.
     #[must_use]
     pub fn neighbor_positions_wrapping<P: Into<(usize, usize)>>(
         &self,
         target_pos: P,
     ) -> Vec<(usize, usize)> {
         let pos = target_pos.into();
-        trace!("MapGrid::get_neighbor_positions({:?})", pos);
-        info!(
-            "Getting neighbors to ({:?}) in grid of size ({},{})",
-            pos, self.width, self.height
-        );
-        let (x, y) = pos;
-        let mut positions = Vec::new();
-        let xs: [usize; 3] = if x == 0 {
-            [self.width - 1, 0, 1]
-        } else {
-            [(x - 1) % self.width, x % self.width, (x + 1) % self.width]
-        };
-        let ys: [usize; 3] = if y == 0 {
-            [self.height - 1, 0, 1]
-        } else {
-            [
-                (y - 1) % self.height,
-                y % self.height,
-                (y + 1) % self.height,
-            ]
-        };
+        trace!("MapGrid::neighbor_positions_wrapping({:?})", pos);
+        if pos.0 >= self.width || pos.1 >= self.height {
+            warn!(
+                "MapGrid::neighbor_positions_wrapping - {:?} is outside of the grid",
+                pos
+            );
+            return Vec::new();
+        }
 
-        info!("xs = {:?}", xs);
-        info!("ys = {:?}", ys);
+        let (x, y) = pos;
+        let xs = [(x + self.width - 1) % self.width, x, (x + 1) % self.width];
+        let ys = [
+            (y + self.height - 1) % self.height,
+            y,
+            (y + 1) % self.height,
+        ];
+
+        let mut positions = Vec::with_capacity(8);
         for yy in ys {
             for xx in xs {
-                if xx == x && yy == y {
+                if (xx, yy) == pos || positions.contains(&(xx, yy)) {
                     continue;
                 }
                 positions.push((xx, yy));
             }
         }
-        info!("positions = {:?}", positions);
 
         positions
     }
 
-    /// Gets all neighbors of the given position whose state matches `state`. If `wrap_edges` is true,
-    /// neighbors will be considered by wrapping first and last rows and columns.
+    /// Gets the positions of all cells at exactly `distance` (Chebyshev distance) from `center`,
+    /// forming a square "ring" around it. Positions outside of the grid are skipped, and a
+    /// `distance` of 0 returns only the `center`.
+    #[allow(clippy::cast_possible_wrap, clippy::cast_sign_loss)]
     #[must_use]
-    pub fn neighbors_with_state<P: Into<(usize, usize)>>(
-        &self,
-        target_pos: P,
-        state: bool,
-        wrap_edges: bool,
-    ) -> Vec<(usize, usize)> {
-        let pos = target_pos.into();
-        trace!("MapGrid::neighbors_with_state({:?}, {})", pos, state);
-        let mut neighbors = Vec::new();
-        let range = if wrap_edges {
-            self.neighbor_positions_wrapping(pos)
-        } else {
-            self.neighbor_positions(pos)
-        };
+    pub fn ring_positions(&self, center: GridPos, distance: usize) -> Vec<GridPos> {
+        trace!("MapGrid::ring_positions({:?}, {})", center, distance);
+        if distance == 0 {
+            return if center.x < self.width && center.y < self.height {
+                vec![center]
+            } else {
+                Vec::new()
+            };
+        }
 
-        for (x, y) in range {
-            if matches!(self.cell((x, y)), Some(cell) if cell.state() == state.into()) {
-                neighbors.push((x, y));
+        let (cx, cy, d) = (center.x as isize, center.y as isize, distance as isize);
+        let mut positions = Vec::with_capacity(distance * 8);
+        for y in (cy - d)..=(cy + d) {
+            for x in (cx - d)..=(cx + d) {
+                if (x - cx).abs() != d && (y - cy).abs() != d {
+                    continue;
+                }
+                if x < 0 || y < 0 || x as usize >= self.width || y as usize >= self.height {
+                    continue;
+                }
+
+                positions.push(GridPos::new(x as usize, y as usize));
             }
         }
-        neighbors
+
+        positions
     }
 
-    /// Gets the number of neighboring cells whose state is True. This does not include the cell at the given x and y.
+    /// Finds the open (off) cell closest to `from` by searching outwards ring by ring (see
+    /// [`MapGrid::ring_positions`]). If `from` is itself open it is returned. Within the first ring
+    /// containing an open cell, the one with the smallest straight-line distance wins. Returns `None`
+    /// if the grid contains no open cells.
     #[must_use]
-    pub fn active_neighbor_count(&self, pos: (usize, usize), wrapped: bool) -> usize {
-        trace!(
-            "MapGrid::active_neighbor_count(pos = {:?}, wrapped = {})",
-            pos,
-            wrapped
-        );
+    pub fn nearest_open(&self, from: GridPos) -> Option<GridPos> {
+        trace!("MapGrid::nearest_open({:?})", from);
+        self.nearest_matching(from, |p| self.cells[p.y][p.x].is_off())
+    }
 
-        if wrapped {
-            self.neighbors_with_state(pos, true, true).len()
-        } else {
-            self.neighbors_with_state(pos, true, false).len()
+    /// Carves a straight, 4-connected corridor (turning cells off) from `from` to the nearest open cell
+    /// that is not in the same 4-connected open region as `from`, returning the cells that were carved.
+    /// Returns `None` if `from` is out of bounds or there is no other open region to connect to.
+    ///
+    /// ##### See also: [`MapGrid::nearest_open`], [`MapGrid::label_regions`]
+    pub fn carve_to_nearest_open(&mut self, from: GridPos) -> Option<Vec<GridPos>> {
+        trace!("MapGrid::carve_to_nearest_open({:?})", from);
+        if from.x >= self.width || from.y >= self.height {
+            return None;
         }
-    }
 
-    /// Gets the number of neighboring cells in the range (pos.x - x)..=(pos.x + x) x (pos.y - y)..=(pos.y + y)
-    /// whose state is `on` or `active`.
-    #[must_use]
-    pub fn active_neighbors_n(&self, x: usize, y: usize, n: usize) -> usize {
-        trace!("MapGrid::active_neighbors_n({}, {}, {})", x, y, n);
-        if n == 0 {
-            0
-        } else if n == 1 {
-            self.active_neighbor_count((x, y), true)
-        } else if (n * 2) + 1 > self.width || (n * 2) + 1 > self.height {
-            warn!("Invalid neighbor extents");
-            0
-        } else {
-            // #...# if called with (2, 2, 2)
-            // ..... center is (2,2)
-            // ..#.. top left is (0 (2 - 2),0 (2 - 2))
-            // ..... bot right is (4 (2 + 2),4 (2 + 2))
-            // #...# size is (5,5) (which means I might need to make the square((0 (2-2),0 (2-2)), 5 (2+2+1), 5 (2+2+1))?
-            self.create_subgrid(&square(
-                &(x.saturating_sub(n), y.saturating_sub(n)),
-                x + n + 1,
-                y + n + 1,
-            ))
-            .on_cells_count()
-                - if self
-                    .cell((x, y))
-                    .expect("Unable to get cell for active_neighbors_n (else condition)")
-                    .is_on()
-                {
-                    1
-                } else {
-                    0
+        let (labels, _) = self.label_regions(false);
+        let own = labels[from.y][from.x];
+        let target = self.nearest_matching(from, |p| {
+            let label = labels[p.y][p.x];
+            label >= 0 && label != own
+        })?;
+
+        let mut carved = Vec::new();
+        let mut previous: Option<(usize, usize)> = None;
+        for (x, y) in bresenham_line(from, target) {
+            // Diagonal steps would only touch at the corners, so carve the orthogonal cell between them.
+            let corner = previous
+                .filter(|&(px, py)| px != x && py != y)
+                .map(|(_, py)| (x, py));
+            for (cx, cy) in corner.into_iter().chain(std::iter::once((x, y))) {
+                if !self.cells[cy][cx].is_off() {
+                    self.cells[cy][cx].set_state(TriState::False);
+                    carved.push(GridPos::new(cx, cy));
                 }
+            }
+
+            previous = Some((x, y));
         }
+
+        Some(carved)
     }
 
-    /// Reverses this entire [`MapGrid`] by calling [`crate::data::TriCell::toggle()`] on each cell in the grid.
-    pub fn reverse_in_place(&mut self) {
-        trace!("MapGrid::reverse_in_place()");
-        for cell in self.iter_mut() {
-            cell.toggle();
+    /// Carves an organic, cave-like tunnel (turning cells off) from `from` to `to` using a random walk
+    /// biased towards `to`, returning every position the walk visited in order (including both ends,
+    /// and possibly repeating positions). Each step has a `wander` chance (clamped to `0.0..=0.9`, so
+    /// the walk always reaches `to`) of going in a random direction, and otherwise moves one cell
+    /// closer to `to`, so a `wander` of 0 gives a direct path. Random steps never go onto the outer
+    /// rows and columns of the grid. The walk only depends on `seed`, the global RNG is not used.
+    /// Returns an empty list if `from` or `to` is outside of the grid.
+    pub fn carve_random_walk(
+        &mut self,
+        from: GridPos,
+        to: GridPos,
+        wander: f64,
+        seed: u64,
+    ) -> Vec<GridPos> {
+        trace!(
+            "MapGrid::carve_random_walk({:?}, {:?}, {}, {})",
+            from,
+            to,
+            wander,
+            seed
+        );
+        if !from.is_within(self.size()) || !to.is_within(self.size()) {
+            warn!(
+                "MapGrid::carve_random_walk - {:?} -> {:?} is outside of the grid",
+                from, to
+            );
+            return Vec::new();
         }
-    }
 
-    /// Returns an iterator over all of the cells in this [`MapGrid`].
-    pub fn iter(&self) -> impl Iterator<Item = &Cell> {
-        self.cells.iter().flat_map(|row| row.iter())
+        let wander = wander.clamp(0.0, 0.9);
+        let (right, bottom) = (self.width - 1, self.height - 1);
+        crate::util::random::with_rng(seed, |rng| {
+            let mut current = from;
+            let mut walk = vec![current];
+            self.cells[current.y][current.x].set_state(TriState::False);
+            while current != to {
+                let towards = [
+                    (current.x < to.x).then(|| GridPos::new(current.x + 1, current.y)),
+                    (current.x > to.x).then(|| GridPos::new(current.x - 1, current.y)),
+                    (current.y < to.y).then(|| GridPos::new(current.x, current.y + 1)),
+                    (current.y > to.y).then(|| GridPos::new(current.x, current.y - 1)),
+                ]
+                .into_iter()
+                .flatten()
+                .collect::<Vec<_>>();
+
+                current = if rng.f64() < wander {
+                    let random = [
+                        (current.x > 1).then(|| GridPos::new(current.x - 1, current.y)),
+                        (current.x + 1 < right).then(|| GridPos::new(current.x + 1, current.y)),
+                        (current.y > 1).then(|| GridPos::new(current.x, current.y - 1)),
+                        (current.y + 1 < bottom).then(|| GridPos::new(current.x, current.y + 1)),
+                    ]
+                    .into_iter()
+                    .flatten()
+                    .collect::<Vec<_>>();
+                    if random.is_empty() {
+                        towards[rng.usize(..towards.len())]
+                    } else {
+                        random[rng.usize(..random.len())]
+                    }
+                } else {
+                    towards[rng.usize(..towards.len())]
+                };
+
+                self.cells[current.y][current.x].set_state(TriState::False);
+                walk.push(current);
+            }
+
+            walk
+        })
     }
 
-    /// Returns an iterator over all of the cells along with their position in this [`MapGrid`].
-    pub fn iter_pos(&self) -> impl Iterator<Item = ((usize, usize), &Cell)> {
-        self.cells
-            .iter()
-            .enumerate()
-            .flat_map(|(y, row)| row.iter().enumerate().map(move |(x, cell)| ((x, y), cell)))
+    /// Finds the position closest to `from` (see [`MapGrid::nearest_open`]) that satisfies `pred`.
+    fn nearest_matching<F: Fn(&GridPos) -> bool>(&self, from: GridPos, pred: F) -> Option<GridPos> {
+        let limit = from.x.max(self.width).max(from.y.max(self.height));
+        for distance in 0..=limit {
+            let found = self
+                .ring_positions(from, distance)
+                .into_iter()
+                .filter(|p| pred(p))
+                .min_by_key(|p| {
+                    let (dx, dy) = (absdiff(p.x, from.x), absdiff(p.y, from.y));
+                    dx * dx + dy * dy
+                });
+            if found.is_some() {
+                return found;
+            }
+        }
+
+        None
     }
 
-    /// Returns a mutable iterator over all of the cells in this [`MapGrid`].
-    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Cell> {
-        self.cells.iter_mut().flat_map(|row| row.iter_mut())
+    /// Gets all neighbors of the given position whose state matches `state`. If `wrap_edges` is true,
+    /// neighbors will be considered by wrapping first and last rows and columns.
+    #[must_use]
+    pub fn neighbors_with_state<P: Into<(usize, usize)>>(
+        &self,
+        target_pos: P,
+        state: bool,
+        wrap_edges: bool,
+    ) -> Vec<(usize, usize)> {
+        let pos = target_pos.into();
+        trace!("MapGrid::neighbors_with_state({:?}, {})", pos, state);
+        let mut neighbors = Vec::new();
+        let range = if wrap_edges {
+            self.neighbor_positions_wrapping(pos)
+        } else {
+            self.neighbor_positions(pos)
+        };
+
+        for (x, y) in range {
+            if matches!(self.cell((x, y)), Some(cell) if cell.state() == state.into()) {
+                neighbors.push((x, y));
+            }
+        }
+        neighbors
     }
 
-    /// Returns a mutable iterator over all of the cells along with their position in this [`MapGrid`].
-    pub fn iter_pos_mut(&mut self) -> impl Iterator<Item = ((usize, usize), &mut Cell)> {
-        self.cells.iter_mut().enumerate().flat_map(|(y, row)| {
-            row.iter_mut()
-                .enumerate()
-                .map(move |(x, cell)| ((x, y), cell))
-        })
+    /// Same as [`MapGrid::neighbors_with_state`], but only the four orthogonal neighbors are considered.
+    /// Pathfinding over these can never slip diagonally between two walls that touch at a corner, which
+    /// is what corridor style mazes expect.
+    #[must_use]
+    pub fn neighbors_with_state_ortho<P: Into<(usize, usize)>>(
+        &self,
+        target_pos: P,
+        state: bool,
+        wrap_edges: bool,
+    ) -> Vec<(usize, usize)> {
+        let pos = target_pos.into();
+        trace!("MapGrid::neighbors_with_state_ortho({:?}, {})", pos, state);
+        let (x, y) = pos;
+        self.neighbors_with_state(pos, state, wrap_edges)
+            .into_iter()
+            .filter(|&(nx, ny)| nx == x || ny == y)
+            .collect()
     }
 
-    /// Creates a new grid from the given [`section`](`crate::data::types::GridSquare`) of the current grid.
-    ///
-    /// TODO: Fix this to either handle overflow (by wrapping) or fail more gracefully.
-    ///
-    /// ### Panics
-    /// Function panics if the size of `section` is less than 3x3.
+    /// Gets the states of the eight neighbors of `pos`, in `N, NE, E, SE, S, SW, W, NW` order. If
+    /// `wrap_edges` is true neighbors past the edges wrap around to the other side of the grid,
+    /// otherwise they are reported as [`TriState::Invalid`].
+    #[allow(clippy::cast_possible_wrap, clippy::cast_sign_loss)]
     #[must_use]
-    pub fn create_subgrid(&self, section: &GridSquare) -> Self {
-        if section.height() < 3 || section.width() < 3 {
-            error!("Invalid GridSquare size: {:?}", section);
-            panic!("Invalid GridSquare size");
+    pub fn neighbor_states(&self, pos: GridPos, wrap_edges: bool) -> [TriState; 8] {
+        const OFFSETS: [(isize, isize); 8] = [
+            (0, -1),
+            (1, -1),
+            (1, 0),
+            (1, 1),
+            (0, 1),
+            (-1, 1),
+            (-1, 0),
+            (-1, -1),
+        ];
+        trace!("MapGrid::neighbor_states({:?}, {})", pos, wrap_edges);
+
+        let mut states = [TriState::Invalid; 8];
+        for (state, (dx, dy)) in states.iter_mut().zip(OFFSETS) {
+            let (x, y) = (pos.x as isize + dx, pos.y as isize + dy);
+            let cell = if wrap_edges {
+                self.cell_wrapped(x, y)
+            } else if x < 0 || y < 0 {
+                None
+            } else {
+                self.cell((x as usize, y as usize))
+            };
+
+            if let Some(cell) = cell {
+                *state = cell.state();
+            }
         }
 
-        if section.max.x > self.width || section.max.y > self.height {
-            error!(
-                "Section is too big for current grid: Grid Size = {:?} Section = {:?}",
-                self.size(),
-                section
-            );
-            panic!("Invalid GridSquare size");
+        states
+    }
+
+    /// Gets the number of neighboring cells whose state is True. This does not include the cell at the given x and y.
+    #[must_use]
+    pub fn active_neighbor_count(&self, pos: (usize, usize), wrapped: bool) -> usize {
+        trace!(
+            "MapGrid::active_neighbor_count(pos = {:?}, wrapped = {})",
+            pos,
+            wrapped
+        );
+
+        if wrapped {
+            self.neighbors_with_state(pos, true, true).len()
+        } else {
+            self.neighbors_with_state(pos, true, false).len()
         }
+    }
 
-        MapGrid::sub_grid(self, section)
+    /// Gets the number of cells within `n` cells of (`x`, `y`), i.e. in the range
+    /// `(x - n)..=(x + n)` x `(y - n)..=(y + n)`, whose state is `on` or `active`. The cell at
+    /// (`x`, `y`) itself is never counted, and the range is truncated at the edges of the grid.
+    #[must_use]
+    pub fn active_neighbors_n(&self, x: usize, y: usize, n: usize) -> usize {
+        trace!("MapGrid::active_neighbors_n({}, {}, {})", x, y, n);
+        let count = self.count_state_in_square(&square_radius(&(x, y), n), TriState::True);
+        if matches!(self.cell((x, y)), Some(cell) if cell.is_on()) {
+            count - 1
+        } else {
+            count
+        }
     }
 
-    /// Resize all rows in the grid to the given size, using [`crate::data::Cell::invalid()`]
-    /// as the default value for each added cell. Rows cannot be resized to be less than
-    /// 3. If grid currently already has `new_row_size` rows, function will early out.
-    ///
-    /// #### This changes the SIZE OF EACH ROW aka the width of the [`MapGrid`], NOT the ROW COUNT (which would be the height).
-    /// ##### This is the same as calling [`MapGrid::resize_rows_with(new_row_size, Cell::invalid())`].
+    /// Gets every cell connected to `start` that shares its state, including `start` itself, in the
+    /// order they are reached. Cells are connected through their four orthogonal neighbors, or through
+    /// all eight if `diagonal` is true. Returns an empty list if `start` is outside of the grid or is
+    /// `Invalid`, since invalid cells never form a region.
     ///
-    /// ### Panics
-    /// Function panics if the resulting size of the grid is less than 3x3, which should not happen
-    /// because the function first checks if the result is going to be less than 3.
-    pub fn resize_rows(&mut self, new_row_size: usize) {
-        trace!("MapGrid::resize_rows({})", new_row_size);
-        self.resize_rows_with(new_row_size, Cell::invalid());
+    /// ##### See also: [`MapGrid::flood_fill`]
+    #[must_use]
+    pub fn flood_region(&self, start: (usize, usize), diagonal: bool) -> Vec<(usize, usize)> {
+        trace!("MapGrid::flood_region({:?}, {})", start, diagonal);
+        let target = match self.cell(start) {
+            Some(cell) if cell.is_valid() => cell.state(),
+            _ => return Vec::new(),
+        };
+
+        let mut seen = vec![vec![false; self.width]; self.height];
+        let mut region = Vec::new();
+        let mut queue = std::collections::VecDeque::new();
+        seen[start.1][start.0] = true;
+        queue.push_back(start);
+        while let Some((x, y)) = queue.pop_front() {
+            region.push((x, y));
+            for (nx, ny) in self.neighbor_positions((x, y)) {
+                if (!diagonal && nx != x && ny != y)
+                    || seen[ny][nx]
+                    || self.cells[ny][nx].state() != target
+                {
+                    continue;
+                }
+
+                seen[ny][nx] = true;
+                queue.push_back((nx, ny));
+            }
+        }
+
+        region
     }
 
-    /// Resize all rows in the grid to the given size, using `cell_value` as the
-    /// default value for each added cell. Rows cannot be resized to be less than
-    /// 3. If grid currently already has `new_row_size` rows, function will early out.
-    ///
-    /// #### This changes the SIZE OF EACH ROW aka the width of the [`MapGrid`], NOT the ROW COUNT (which would be the height).
-    ///
-    /// ### Panics
-    /// Function panics if the resulting size of the grid is less than 3x3, which should not happen
-    /// because the function first checks if the result is going to be less than 3.
-    pub fn resize_rows_with(&mut self, new_row_size: usize, cell_value: Cell) {
+    /// Sets every cell in the region connected to `start` (see [`MapGrid::flood_region`]) to
+    /// `new_state`, returning how many cells were changed. Nothing happens if `start` is outside of the
+    /// grid, is `Invalid`, or already has `new_state`.
+    pub fn flood_fill(
+        &mut self,
+        start: (usize, usize),
+        new_state: TriState,
+        diagonal: bool,
+    ) -> usize {
         trace!(
-            "MapGrid::resize_rows_with({}, {:?})",
-            new_row_size,
-            cell_value
+            "MapGrid::flood_fill({:?}, {:?}, {})",
+            start,
+            new_state,
+            diagonal
         );
-        let safe_size = if new_row_size < 3 {
-            error!("MapGrid::resize_rows_with - cannot resize row length to less than 3");
-            3
-        } else {
-            new_row_size
-        };
-
-        if safe_size == self.cols() {
-            info!("MapGrid::resize_rows_with - new size same as current size, bailing on resize");
-            return;
+        match self.cell(start) {
+            Some(cell) if cell.is_valid() && cell.state() != new_state => {}
+            _ => return 0,
         }
 
-        for row in &mut self.cells {
-            row.resize(safe_size, cell_value);
+        let region = self.flood_region(start, diagonal);
+        for &(x, y) in &region {
+            self.cells[y][x].set_state(new_state);
         }
 
-        assert!(
-            self.cells[0].len() == safe_size,
-            "Actual row length (self.cells[0].len() = {}) does not equal safe_size ({})",
-            self.cells[0].len(),
-            safe_size
-        );
-        self.width = safe_size;
+        region.len()
     }
 
-    /// Resize all columns in the grid to the given size, using [`crate::data::Cell::invalid()`]
-    /// as the default value for each added cell. Column count cannot be than 3.
-    /// If grid currently already has `new_column_size` columns, function will early out.
-    ///
-    /// #### This changes the SIZE OF EACH COLUMN aka the height of the [`MapGrid`], NOT the COLUMN COUNT (which would be the width).
-    /// ##### This is the same as calling [`MapGrid::resize_cols_with(new_column_size, Cell::invalid())`].
+    /// Labels each 4-connected region of cells whose state matches `state`.
     ///
-    /// ### Panics
-    /// Function panics if the resulting size of the grid is less than 3x3, which should not happen
-    /// because the function first checks if the result is going to be less than 3.
-    pub fn resize_cols(&mut self, new_column_size: usize) {
-        trace!("MapGrid::resize_cols({})", new_column_size);
-        self.resize_cols_with(new_column_size, Cell::invalid());
+    /// Returns a grid (indexed `[y][x]`) where each matching cell holds the 0-based id of its region
+    /// and every other cell holds -1, along with the total number of regions found. Regions are
+    /// numbered in the order they are first encountered in row-major order.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+    #[must_use]
+    pub fn label_regions(&self, state: bool) -> (Vec<Vec<i32>>, usize) {
+        trace!("MapGrid::label_regions({})", state);
+        let target: TriState = state.into();
+        let mut labels = vec![vec![-1i32; self.width]; self.height];
+        let mut count = 0usize;
+        let mut queue = std::collections::VecDeque::new();
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if labels[y][x] != -1 || self.cells[y][x].state() != target {
+                    continue;
+                }
+
+                let label = count as i32;
+                labels[y][x] = label;
+                queue.push_back((x, y));
+                while let Some((cx, cy)) = queue.pop_front() {
+                    let mut adjacent = Vec::with_capacity(4);
+                    if cx > 0 {
+                        adjacent.push((cx - 1, cy));
+                    }
+                    if cx + 1 < self.width {
+                        adjacent.push((cx + 1, cy));
+                    }
+                    if cy > 0 {
+                        adjacent.push((cx, cy - 1));
+                    }
+                    if cy + 1 < self.height {
+                        adjacent.push((cx, cy + 1));
+                    }
+
+                    for (nx, ny) in adjacent {
+                        if labels[ny][nx] == -1 && self.cells[ny][nx].state() == target {
+                            labels[ny][nx] = label;
+                            queue.push_back((nx, ny));
+                        }
+                    }
+                }
+
+                count += 1;
+            }
+        }
+
+        (labels, count)
     }
 
-    /// Resize all columns in the grid to the given size, using `cell_value` as the
-    /// default value for each added cell. Column count cannot be less than 3.
-    /// If grid currently already has `new_column_size` columns, function will early out.
-    ///
-    /// #### This changes the SIZE OF EACH COLUMN aka the height of the [`MapGrid`], NOT the COLUMN COUNT (which would be the width).
+    /// Gets every group of connected cells whose state matches `state`, largest first. Cells are
+    /// connected through their four orthogonal neighbors, or through all eight if `diagonal` is true.
+    /// Groups of the same size keep the order they were found in (row-major order of their first cell),
+    /// and `Invalid` cells never join a group.
     ///
-    /// ### Panics
-    /// Function panics if the resulting size of the grid is less than 3x3, which should not happen
-    /// because the function first checks if the result is going to be less than 3.
-    pub fn resize_cols_with(&mut self, new_column_size: usize, cell_value: Cell) {
+    /// ##### See also: [`MapGrid::largest_component`], [`MapGrid::label_regions`]
+    #[must_use]
+    pub fn connected_components(&self, state: bool, diagonal: bool) -> Vec<Vec<(usize, usize)>> {
+        trace!("MapGrid::connected_components({}, {})", state, diagonal);
+        let target: TriState = state.into();
+        let mut seen = vec![vec![false; self.width]; self.height];
+        let mut components = Vec::new();
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if seen[y][x] || self.cells[y][x].state() != target {
+                    continue;
+                }
+
+                let component = self.flood_region((x, y), diagonal);
+                for &(cx, cy) in &component {
+                    seen[cy][cx] = true;
+                }
+
+                components.push(component);
+            }
+        }
+
+        components.sort_by_key(|c| std::cmp::Reverse(c.len()));
+        components
+    }
+
+    /// Gets the largest group of connected cells whose state matches `state`, or `None` if there are
+    /// no such cells. See [`MapGrid::connected_components`].
+    #[must_use]
+    pub fn largest_component(&self, state: bool, diagonal: bool) -> Option<Vec<(usize, usize)>> {
+        trace!("MapGrid::largest_component({}, {})", state, diagonal);
+        self.connected_components(state, diagonal)
+            .into_iter()
+            .next()
+    }
+
+    /// Flips every group of connected `state` cells (see [`MapGrid::connected_components`]) that has
+    /// fewer than `min_size` cells to the opposite state, returning how many cells were changed. Groups
+    /// touching the edge of the grid are removed like any other, and a `min_size` of 0 changes nothing.
+    pub fn remove_small_regions(&mut self, state: bool, min_size: usize, diagonal: bool) -> usize {
         trace!(
-            "MapGrid::resize_cols_with({}, {:?})",
-            new_column_size,
-            cell_value
+            "MapGrid::remove_small_regions({}, {}, {})",
+            state,
+            min_size,
+            diagonal
         );
-        let safe_size = if new_column_size < 3 {
-            error!("MapGrid::resize_cols_with - cannot resize column count to less than 3");
-            3
-        } else {
-            new_column_size
-        };
+        let mut removed = 0;
+        for component in self.connected_components(state, diagonal) {
+            if component.len() >= min_size {
+                continue;
+            }
 
-        if safe_size == self.rows() {
-            info!("MapGrid::resize_cols_with - new size same as current size, bailing on resize");
-            return;
+            for &(x, y) in &component {
+                self.cells[y][x].set_state((!state).into());
+            }
+            removed += component.len();
         }
 
-        let row_size = self.cols();
-        self.cells.resize(safe_size, vec![cell_value; row_size]);
-        assert!(
-            self.cells.len() == safe_size,
-            "Actual col length (self.cells.len() = {}) does not equal safe_size ({})",
-            self.cells.len(),
-            safe_size
-        );
-        self.height = safe_size;
+        removed
     }
 
-    /// Convenience function which calls:
-    /// ```
-    /// # use dungen::data::MapGrid;
-    /// # let mut grid = MapGrid::new((5, 5));
-    /// # assert!(grid.cell_count() == 25);
-    /// # let size = (10,10);
-    /// grid.resize_rows(size.0);
-    /// grid.resize_cols(size.1);
-    /// # assert!(grid.cell_count() == 100);
-    /// ```
-    ///
-    /// ### Panics
-    /// - Function panics if the resulting size of the grid is less than 3x3, which should not happen
-    /// because the function first checks if the result is going to be less than 3.
-    /// - Function panics if the actual resulting size of the grid does not match the expected end size
-    /// (which means something probably went horribly wrong or was horribly coded)
-    pub fn resize<P: Into<(usize, usize)>>(&mut self, size: P) {
-        let (width, height) = size.into();
-        if self.width != width {
-            self.resize_rows(width);
-        }
-        if self.height != height {
-            self.resize_cols(height);
+    /// Gets the size, bounding square, and centroid of each 4-connected region of cells whose state
+    /// matches `state`. Stats are ordered by region id, as returned by [`MapGrid::label_regions`].
+    #[must_use]
+    pub fn component_stats(&self, state: bool) -> Vec<ComponentStat> {
+        #[derive(Clone, Copy)]
+        struct Totals {
+            size: usize,
+            min: (usize, usize),
+            max: (usize, usize),
+            sum: (usize, usize),
         }
 
-        let new_current: (usize, usize) = self.size().into();
-        if new_current.0 != width.max(3) || new_current.1 != height.max(3) {
-            error!(
-                "MapGrid::resize - grid not set to the expected size. Actual = {:?} Expected = {:?}",
-                self.size(),
-                (width, height)
-            );
-            panic!("MapGrid::resize - failed to resize to requested size");
+        trace!("MapGrid::component_stats({})", state);
+        let (labels, count) = self.label_regions(state);
+        let empty = Totals {
+            size: 0,
+            min: (usize::MAX, usize::MAX),
+            max: (0, 0),
+            sum: (0, 0),
+        };
+        let mut totals = vec![empty; count];
+        for (y, row) in labels.iter().enumerate() {
+            for (x, label) in row.iter().enumerate() {
+                if let Ok(label) = usize::try_from(*label) {
+                    let entry = &mut totals[label];
+                    entry.size += 1;
+                    entry.min = (entry.min.0.min(x), entry.min.1.min(y));
+                    entry.max = (entry.max.0.max(x), entry.max.1.max(y));
+                    entry.sum = (entry.sum.0 + x, entry.sum.1 + y);
+                }
+            }
         }
+
+        totals
+            .into_iter()
+            .map(|t| ComponentStat {
+                size: t.size,
+                bounding_square: square(&t.min, t.max.0 - t.min.0 + 1, t.max.1 - t.min.1 + 1),
+                centroid: GridPos::new(
+                    (t.sum.0 + t.size / 2) / t.size,
+                    (t.sum.1 + t.size / 2) / t.size,
+                ),
+            })
+            .collect()
     }
 
-    /// Modifies this [`MapGrid`] by adding the contents of `other` to it
-    /// at position (0,0).
-    pub fn union_in_place(&mut self, other: &Self) {
-        self.integrate_in_place(other, (0, 0).into());
+    /// Gets the diameter of the largest 4-connected open (off) region: the two cells whose shortest path
+    /// between each other is the longest, along with that path's length. Returns `None` if there are no
+    /// open cells.
+    ///
+    /// This uses the double breadth-first search trick, which is exact for perfect mazes (where the open
+    /// cells form a tree) and a close lower bound for maps containing loops.
+    #[must_use]
+    pub fn diameter(&self) -> Option<(GridPos, GridPos, usize)> {
+        trace!("MapGrid::diameter()");
+        let (labels, count) = self.label_regions(false);
+        if count == 0 {
+            return None;
+        }
+
+        let mut sizes = vec![0usize; count];
+        for label in labels
+            .iter()
+            .flatten()
+            .filter_map(|&l| usize::try_from(l).ok())
+        {
+            sizes[label] += 1;
+        }
+        let largest = (0..count).max_by_key(|&l| sizes[l])?;
+        let start = self
+            .iter_pos()
+            .map(|(p, _)| p)
+            .find(|&(x, y)| usize::try_from(labels[y][x]).ok() == Some(largest))?;
+
+        let farthest = |from: (usize, usize)| {
+            let distances = self.distance_field(from.into());
+            let mut best = (from, 0usize);
+            for ((x, y), _) in self.iter_pos() {
+                if let Some(distance) = distances[y][x] {
+                    if distance > best.1 {
+                        best = ((x, y), distance);
+                    }
+                }
+            }
+
+            best
+        };
+
+        let (first, _) = farthest(start);
+        let (second, distance) = farthest(first);
+
+        Some((first.into(), second.into(), distance))
     }
 
-    /// Integrates the given [`MapGrid`] into this one at the given position. Newer data
-    /// (from `other`) will take precedence over the currently existing data. This
-    /// [`MapGrid`] will be resized if necessary.
-    pub fn integrate_in_place(&mut self, other: &Self, offset: GridPos) {
-        let offset_size = (other.width + offset.x, other.height + offset.y);
-        if other.width + offset.x > self.width || other.height + offset.y > self.height {
-            self.resize((
-                offset_size.0.max(self.width),
-                offset_size.1.max(self.height),
-            ));
+    /// Gets the number of 4-connected steps from `start` to every open (off) cell, indexed `[y][x]`.
+    /// Cells that are not open, or that cannot be reached from `start`, are [`None`]. If `start`
+    /// itself is not open every entry is [`None`].
+    #[must_use]
+    pub fn distance_field(&self, start: GridPos) -> Vec<Vec<Option<usize>>> {
+        trace!("MapGrid::distance_field({:?})", start);
+        let mut distances = vec![vec![None; self.width]; self.height];
+        if !matches!(self.cell(start), Some(cell) if cell.is_off()) {
+            return distances;
         }
 
-        for ((x, y), &cell) in other.iter_pos() {
-            self.set_cell(x + offset.x, y + offset.y, cell);
+        let mut queue = std::collections::VecDeque::new();
+        distances[start.y][start.x] = Some(0);
+        queue.push_back((start.x, start.y, 0usize));
+        while let Some((x, y, distance)) = queue.pop_front() {
+            let adjacent = [
+                (x.wrapping_sub(1), y),
+                (x + 1, y),
+                (x, y.wrapping_sub(1)),
+                (x, y + 1),
+            ];
+            for (nx, ny) in adjacent {
+                if nx < self.width
+                    && ny < self.height
+                    && distances[ny][nx].is_none()
+                    && self.cells[ny][nx].is_off()
+                {
+                    distances[ny][nx] = Some(distance + 1);
+                    queue.push_back((nx, ny, distance + 1));
+                }
+            }
         }
+
+        distances
     }
 
-    /// Converts this [`MapGrid`] into an instance of [`pathfinding::grid::Grid`].
+    /// Checks whether `goal` can be reached from `start` by 4-connected steps over open (off) cells,
+    /// treating every position in `blocked` as a wall. This allows checking temporary obstacles without
+    /// modifying the grid. Returns false if `start` or `goal` is not open or is blocked.
     #[must_use]
-    pub fn to_pf_grid(&self) -> PFGrid {
-        let mut pf_grid = PFGrid::new(self.width, self.height);
-        pf_grid.enable_diagonal_mode();
+    pub fn reachable_avoiding(&self, start: GridPos, goal: GridPos, blocked: &[GridPos]) -> bool {
+        trace!(
+            "MapGrid::reachable_avoiding({:?}, {:?}, {} blocked)",
+            start,
+            goal,
+            blocked.len()
+        );
+        let mut seen = vec![vec![false; self.width]; self.height];
+        for p in blocked {
+            if p.x < self.width && p.y < self.height {
+                seen[p.y][p.x] = true;
+            }
+        }
 
-        for ((x, y), cell) in self.iter_pos() {
-            if cell.is_on() {
-                pf_grid.add_vertex((x, y));
+        let is_open = |p: GridPos| matches!(self.cell(p), Some(cell) if cell.is_off());
+        if !is_open(start) || !is_open(goal) || seen[start.y][start.x] || seen[goal.y][goal.x] {
+            return false;
+        }
+
+        let mut queue = std::collections::VecDeque::new();
+        seen[start.y][start.x] = true;
+        queue.push_back((start.x, start.y));
+        while let Some((x, y)) = queue.pop_front() {
+            if (x, y) == (goal.x, goal.y) {
+                return true;
+            }
+
+            for (nx, ny) in self.neighbors_with_state_ortho((x, y), false, false) {
+                if !seen[ny][nx] {
+                    seen[ny][nx] = true;
+                    queue.push_back((nx, ny));
+                }
             }
         }
 
-        pf_grid
+        false
     }
 
-    /// Converts the grid to a [Vec] of [String]s, with each cell represented by the given
-    /// character.
+    /// Floods outwards from every position in `sources` at once, labeling each open (off) cell with the
+    /// index of the source nearest to it by 4-connected steps. Ties go to the source with the lowest
+    /// index. Walls, invalid cells, and cells unreachable from every source are [`None`], and sources
+    /// that are not open cells are ignored. The result is indexed `[y][x]`.
     #[must_use]
-    pub fn to_strings_with(&self, on: char, off: char) -> Vec<String> {
-        trace!("MapGrid::to_strings_with({}, {})", on, off);
+    pub fn nearest_source(&self, sources: &[GridPos]) -> Vec<Vec<Option<usize>>> {
+        trace!("MapGrid::nearest_source({:?})", sources);
+        let mut owners = vec![vec![None; self.width]; self.height];
+        let mut queue = std::collections::VecDeque::new();
+        for (i, source) in sources.iter().enumerate() {
+            if matches!(self.cell(*source), Some(cell) if cell.is_off())
+                && owners[source.y][source.x].is_none()
+            {
+                owners[source.y][source.x] = Some(i);
+                queue.push_back((source.x, source.y, i));
+            }
+        }
 
-        let invalid: char = {
-            if INVALID_MARKERS[0] != on && INVALID_MARKERS[0] != off {
-                INVALID_MARKERS[0]
-            } else if INVALID_MARKERS[1] != on && INVALID_MARKERS[1] != off {
-                INVALID_MARKERS[1]
-            } else {
-                INVALID_MARKERS[2]
+        // Sources are queued in index order, so each distance band is processed in index order as well
+        // and the lowest index reaches any tied cell first.
+        while let Some((x, y, owner)) = queue.pop_front() {
+            let adjacent = [
+                (x.wrapping_sub(1), y),
+                (x + 1, y),
+                (x, y.wrapping_sub(1)),
+                (x, y + 1),
+            ];
+            for (nx, ny) in adjacent {
+                if nx < self.width
+                    && ny < self.height
+                    && owners[ny][nx].is_none()
+                    && self.cells[ny][nx].is_off()
+                {
+                    owners[ny][nx] = Some(owner);
+                    queue.push_back((nx, ny, owner));
+                }
             }
-        };
-        info!(
-            "MapGrid::to_strings_with - Using '{}' as invalid character",
-            invalid
-        );
+        }
 
-        let mut strings = Vec::with_capacity(self.height);
+        owners
+    }
 
-        for row in &self.cells {
-            let mut string = String::with_capacity(row.len());
-            for cell in row {
-                string.push(if cell.is_on() {
-                    on
-                } else if cell.is_off() {
-                    off
-                } else {
-                    invalid
-                });
+    /// Buckets every open cell reachable from `start` into `tiers` bands of equal distance width,
+    /// ordered from nearest to farthest. Cells within a tier are in row-major order. Useful for
+    /// placing content by difficulty, e.g. easy encounters in the first tier and bosses in the last.
+    ///
+    /// Returns `tiers` empty lists if `start` is not open.
+    ///
+    /// ##### See also: [`MapGrid::distance_field`]
+    #[must_use]
+    pub fn difficulty_zones(&self, start: GridPos, tiers: usize) -> Vec<Vec<GridPos>> {
+        trace!("MapGrid::difficulty_zones({:?}, {})", start, tiers);
+        let mut zones = vec![Vec::new(); tiers];
+        if tiers == 0 {
+            return zones;
+        }
+
+        let distances = self.distance_field(start);
+        let max = distances
+            .iter()
+            .flatten()
+            .flatten()
+            .max()
+            .copied()
+            .unwrap_or(0);
+        for ((x, y), _) in self.iter_pos() {
+            if let Some(distance) = distances[y][x] {
+                zones[distance * tiers / (max + 1)].push(pos((x, y)));
             }
-            strings.push(string);
         }
 
-        strings
+        zones
     }
 
-    /// Converts the grid to a [String] with each cell represented by the given on and off
-    /// characters, with each row separated by the given separator.
+    /// Gathers the connectivity and border details of this grid used to decide whether it is a valid
+    /// maze, see [`MazeReport`].
     #[must_use]
-    pub fn to_string_with(&self, on: char, off: char, div: char) -> String {
-        trace!("MapGrid::to_string_with({}, {}, {})", on, off, div);
-        self.to_strings_with(on, off).join(&div.to_string())
+    pub fn maze_report(&self) -> MazeReport {
+        trace!("MapGrid::maze_report()");
+        let components = self.component_stats(false);
+        let (_, open_cells, invalid_cells) = self.count_all();
+        let largest = components.iter().map(|c| c.size).max().unwrap_or(0);
+
+        MazeReport {
+            open_cells,
+            open_components: components.len(),
+            unreachable_cells: open_cells - largest,
+            invalid_cells,
+            exits: self.border_openings(),
+        }
     }
 
-    /// Gets a [Vec] of [String]s representing the grid, using the default on and off
-    /// characters (`'#'` and `'.'` respectively).
+    /// Checks whether this grid is a valid maze: all of its open cells form a single connected region,
+    /// meaning every open cell (and every exit on the border) can be reached, and there are no invalid
+    /// cells. Use [`MapGrid::maze_report`] to find out why a grid fails.
     #[must_use]
-    pub fn to_strings(&self) -> Vec<String> {
-        trace!("MapGrid::to_strings()");
-        self.to_strings_with('#', '.')
+    pub fn is_valid_maze(&self) -> bool {
+        trace!("MapGrid::is_valid_maze()");
+        self.maze_report().is_valid()
     }
 
-    /// Gets a string representation of the grid with the default on and off characters
-    /// (`'#'` and `'.'` respectively).
+    /// Counts the 4-connected open (off) regions that never touch the outer edge of the grid. These
+    /// are rooms with no way out to the border, e.g. potential secret rooms.
     #[must_use]
-    pub fn as_string(&self) -> String {
-        self.to_strings().join("\n")
+    pub fn enclosed_region_count(&self) -> usize {
+        trace!("MapGrid::enclosed_region_count()");
+        let (labels, count) = self.label_regions(false);
+        let mut touches_border = vec![false; count];
+        for (y, row) in labels.iter().enumerate() {
+            for (x, label) in row.iter().enumerate() {
+                let on_border = x == 0 || y == 0 || x + 1 == self.width || y + 1 == self.height;
+                if let (true, Ok(label)) = (on_border, usize::try_from(*label)) {
+                    touches_border[label] = true;
+                }
+            }
+        }
+
+        touches_border.iter().filter(|touches| !**touches).count()
     }
-}
 
-/// Serialization and Deserialization implementations.
-impl MapGrid {
-    /// Parse the given [`input`] [`serde_json::Value`] into a [`MapGrid`].
-    ///
-    /// ### Errors
-    /// Function errors if [`serde_json::from_value`] fails.
-    ///
-    /// ##### See also: [`serde_json::from_value`]
-    pub fn from_json<J: Into<serde_json::Value>>(input: J) -> Result<Self, serde_json::Error> {
-        serde_json::from_value(input.into())
+    /// Compares the open (off) regions of this grid with those of `other`, e.g. the same map before and
+    /// after a [cellular automata](`crate::gen::cell_auto::CellularAutomata`) pass, reporting which
+    /// regions appeared, vanished, merged or split. Only cells lying within both grids are compared
+    /// when their sizes differ.
+    #[allow(clippy::cast_possible_wrap)]
+    #[must_use]
+    pub fn region_diff(&self, other: &Self) -> RegionDiff {
+        trace!("MapGrid::region_diff(other)");
+        let (before, regions_before) = self.label_regions(false);
+        let (after, regions_after) = other.label_regions(false);
+        let mut overlaps = std::collections::HashSet::new();
+        for (row_before, row_after) in before.iter().zip(&after) {
+            for (&a, &b) in row_before.iter().zip(row_after) {
+                if let (Ok(a), Ok(b)) = (usize::try_from(a), usize::try_from(b)) {
+                    overlaps.insert((a, b));
+                }
+            }
+        }
+
+        let mut matches_before = vec![0usize; regions_before];
+        let mut matches_after = vec![0usize; regions_after];
+        for &(a, b) in &overlaps {
+            matches_before[a] += 1;
+            matches_after[b] += 1;
+        }
+
+        RegionDiff {
+            regions_before,
+            regions_after,
+            appeared: matches_after.iter().filter(|&&n| n == 0).count(),
+            vanished: matches_before.iter().filter(|&&n| n == 0).count(),
+            merged: matches_after.iter().filter(|&&n| n > 1).count(),
+            split: matches_before.iter().filter(|&&n| n > 1).count(),
+            area_change: other.off_cells_count() as isize - self.off_cells_count() as isize,
+        }
     }
 
-    /// Parse the given [`input`] string into a [`MapGrid`].
-    ///
-    /// ### Errors
-    /// Function errors if [`serde_json::from_str`] fails.
-    ///
-    /// ##### See also: [`serde_json::from_str`]
-    pub fn from_json_str<S: AsRef<str>>(input: S) -> Result<Self, serde_json::Error> {
-        serde_json::from_str(input.as_ref())
+    /// Gets the perimeter of the region made up of the cells in `component`, counted as the number of
+    /// cell edges that border a cell outside of `component` or the edge of the grid. Positions that lie
+    /// outside of the grid are skipped and duplicate positions are only counted once. Comparing this to
+    /// [`MapGrid::region_area`] tells compact, blobby regions apart from long winding ones.
+    #[must_use]
+    pub fn region_perimeter(&self, component: &[GridPos]) -> usize {
+        trace!("MapGrid::region_perimeter({} cells)", component.len());
+        let cells = component
+            .iter()
+            .filter(|p| p.is_within(self.size()))
+            .map(|p| (p.x, p.y))
+            .collect::<std::collections::HashSet<_>>();
+        cells
+            .iter()
+            .map(|&(x, y)| {
+                [
+                    x.checked_sub(1).map(|nx| (nx, y)),
+                    Some((x + 1, y)),
+                    y.checked_sub(1).map(|ny| (x, ny)),
+                    Some((x, y + 1)),
+                ]
+                .into_iter()
+                .filter(|side| !matches!(side, Some(n) if cells.contains(n)))
+                .count()
+            })
+            .sum()
     }
 
-    /// Parse the given [`input`] bytes into a [`MapGrid`].
-    ///
-    /// ### Errors
-    /// Function errors if [`serde_json::from_slice`] fails.
+    /// Gets the area of the region made up of the cells in `component`, which is the number of those
+    /// positions that lie within the grid.
     ///
-    /// ##### See also: [`serde_json::from_slice`]
-    pub fn from_json_bytes<B: AsRef<[u8]>>(input: B) -> Result<Self, serde_json::Error> {
-        serde_json::from_slice(input.as_ref())
+    /// ##### See also: [`MapGrid::region_perimeter`]
+    #[must_use]
+    pub fn region_area(&self, component: &[GridPos]) -> usize {
+        trace!("MapGrid::region_area({} cells)", component.len());
+        component
+            .iter()
+            .filter(|p| p.is_within(self.size()))
+            .count()
     }
 
-    /// Parse the given [`reader`] into a [`MapGrid`].
-    ///
-    /// ### Errors
-    /// Function errors if [`serde_json::from_reader`] fails.
+    /// Checks whether the 4-connected cells whose state matches `state` contain at least one loop.
     ///
-    /// ##### See also: [`serde_json::from_reader`]
-    pub fn from_json_reader<R: Read>(reader: R) -> Result<Self, serde_json::Error> {
-        serde_json::from_reader(reader)
+    /// A perfect maze (one path between any two cells) has no cycle, while a braided maze does. Note
+    /// that any 2x2 block of matching cells counts as a loop.
+    #[must_use]
+    pub fn has_cycle(&self, state: bool) -> bool {
+        trace!("MapGrid::has_cycle({})", state);
+        self.cycle_count(state) > 0
     }
 
-    /// Open the [`path`](`std::convert::AsRef<std::path::Path>`) and parses the resulting
-    /// reader into a [`MapGrid`] using [`MapGrid::from_json_reader`].
-    ///
-    /// ### Errors
-    /// Function errors if [`serde_json::from_reader`] fails.
-    ///
-    /// ##### See also: [`serde_json::from_reader`]
-    pub fn from_json_file<P: AsRef<std::path::Path>>(path: P) -> serde_json::Result<Self> {
-        match File::open(path) {
-            Ok(file) => Self::from_json_reader(file),
-            Err(e) => Err(serde_json::Error::io(e)),
+    /// Gets the number of independent loops formed by the 4-connected cells whose state matches
+    /// `state`, using Euler's formula (`edges - nodes + components`).
+    #[must_use]
+    pub fn cycle_count(&self, state: bool) -> usize {
+        fn find(parent: &mut [usize], mut i: usize) -> usize {
+            while parent[i] != i {
+                parent[i] = parent[parent[i]];
+                i = parent[i];
+            }
+            i
         }
-    }
 
-    /// Serialize this [`MapGrid`] into a [`Json Value`](`serde_json::Value`).
-    ///
-    /// ### Errors
-    /// Function errors if [`serde_json::to_value`] fails.
-    ///
-    /// ##### See also: [`serde_json::to_value`]
-    pub fn to_json(&self) -> Result<serde_json::Value, serde_json::Error> {
-        serde_json::to_value(self)
+        trace!("MapGrid::cycle_count({})", state);
+        let target: TriState = state.into();
+        let index = |x: usize, y: usize| y * self.width + x;
+        let mut parent: Vec<usize> = (0..self.width * self.height).collect();
+
+        let (mut nodes, mut edges, mut merges) = (0usize, 0usize, 0usize);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if self.cells[y][x].state() != target {
+                    continue;
+                }
+
+                nodes += 1;
+                let mut adjacent = Vec::with_capacity(2);
+                if x + 1 < self.width && self.cells[y][x + 1].state() == target {
+                    adjacent.push(index(x + 1, y));
+                }
+                if y + 1 < self.height && self.cells[y + 1][x].state() == target {
+                    adjacent.push(index(x, y + 1));
+                }
+
+                for other in adjacent {
+                    edges += 1;
+                    let (a, b) = (find(&mut parent, index(x, y)), find(&mut parent, other));
+                    if a != b {
+                        parent[a] = b;
+                        merges += 1;
+                    }
+                }
+            }
+        }
+
+        let components = nodes - merges;
+        edges + components - nodes
     }
 
-    /// Serialize this [`MapGrid`] into a [`Byte Array`](`std::collections::Vec<u8>`).
-    ///
-    /// ### Errors
-    /// Function errors if [`serde_json::to_vec`] fails.
+    /// Gets every cell within `thickness` of the line segments connecting each consecutive pair of
+    /// positions in `path`, clamped to this grid. The result is sorted and contains no duplicates.
     ///
-    /// ##### See also: [`serde_json::to_vec`]
-    pub fn to_json_bytes(&self) -> Result<Vec<u8>, serde_json::Error> {
-        serde_json::to_vec(self)
+    /// ##### See also: [`crate::util::math::bresenham_line_thick`]
+    #[must_use]
+    pub fn rasterize_path(&self, path: &[GridPos], thickness: usize) -> Vec<GridPos> {
+        trace!("MapGrid::rasterize_path(.., {})", thickness);
+        let mut cells: Vec<(usize, usize)> = match path {
+            [] => Vec::new(),
+            [single] => bresenham_line_thick(*single, *single, thickness),
+            _ => path
+                .windows(2)
+                .flat_map(|segment| bresenham_line_thick(segment[0], segment[1], thickness))
+                .collect(),
+        };
+        cells.retain(|&(x, y)| x < self.width && y < self.height);
+        cells.sort_unstable();
+        cells.dedup();
+
+        cells.into_iter().map(GridPos::from).collect()
     }
 
-    /// Serialize this [`MapGrid`] into a [`String`] containing the json. The [`pretty`]
-    /// argument determines whether it is converted with pretty indentation for display.
-    ///
-    /// ### Errors
-    /// Function errors if [`serde_json::to_string`] or [`serde_json::to_string_pretty`] fails.
-    ///
-    /// ##### See also: [`serde_json::to_string`] [`serde_json::to_string_pretty`]
-    pub fn to_json_string(&self, pretty: bool) -> Result<String, serde_json::Error> {
-        if pretty {
-            serde_json::to_string_pretty(self)
-        } else {
-            serde_json::to_string(self)
+    /// Counts how many times the direction of movement changes along `path`. Directions are compared
+    /// by the sign of each step, so a straight line (including a diagonal one) has no turns, and
+    /// repeated positions are skipped.
+    #[must_use]
+    pub fn count_turns(path: &[GridPos]) -> usize {
+        trace!("MapGrid::count_turns({:?})", path);
+        let mut turns = 0;
+        let mut last = None;
+        for step in path.windows(2) {
+            let direction = (step[1].x.cmp(&step[0].x), step[1].y.cmp(&step[0].y));
+            if direction == (std::cmp::Ordering::Equal, std::cmp::Ordering::Equal) {
+                continue;
+            }
+
+            if matches!(last, Some(prev) if prev != direction) {
+                turns += 1;
+            }
+            last = Some(direction);
         }
+
+        turns
     }
 
-    /// Deserialize the given `byte_ref` (which must implement [`std::convert::AsRef<[u8]>`] + [`?Sized`])
-    /// containing msgpack data into a new [`MapGrid`]. This is performed in zero-copy manner whenever it
-    /// is possible, borrowing the data from the reader itself. For example, strings and byte-arrays won’t
-    /// be not copied.
-    ///
-    /// ### Errors
-    /// Function errors if [`rmp_serde::from_read_ref`] fails.
-    ///  
-    /// ##### See also: [`rmp_serde::from_read_ref`].
-    pub fn from_msgpack_ref<R: AsRef<[u8]> + ?Sized>(
-        byte_ref: &R,
-    ) -> Result<Self, rmp_serde::decode::Error> {
-        rmp_serde::from_read_ref(byte_ref)
+    /// Counts the cells whose state matches `state` along the [`bresenham_line`] from `from` to `to`,
+    /// including both endpoints. Positions on the line that fall outside of the grid are not counted.
+    #[must_use]
+    pub fn count_state_on_line(&self, from: GridPos, to: GridPos, state: bool) -> usize {
+        trace!(
+            "MapGrid::count_state_on_line({:?}, {:?}, {})",
+            from,
+            to,
+            state
+        );
+        bresenham_line(from, to)
+            .into_iter()
+            .filter(|&p| matches!(self.cell(p), Some(cell) if cell.state() == state.into()))
+            .count()
     }
 
-    /// Deserialize the given [`reader`](std::io::Read) containing msgpack data into a [`MapGrid`].
-    ///
-    /// ### Errors
-    /// Function errors if [`rmp_serde::from_read`] fails.
-    ///
-    /// ##### See also: [`rmp_serde::from_read`].
-    pub fn from_msgpack_reader<R: Read>(reader: R) -> Result<Self, rmp_serde::decode::Error> {
-        rmp_serde::from_read(reader)
+    /// Steps from `from` one cell at a time in `dir`, for at most `max_dist` cells, and returns the
+    /// position of the first on (wall) cell hit. The cell at `from` itself is never checked. Returns
+    /// [`None`] if no wall is hit before the distance runs out or the ray leaves the grid.
+    #[must_use]
+    pub fn ray_cast(&self, from: GridPos, dir: Direction, max_dist: usize) -> Option<GridPos> {
+        trace!("MapGrid::ray_cast({:?}, {:?}, {})", from, dir, max_dist);
+        let mut current: (usize, usize) = from.into();
+        for _ in 0..max_dist {
+            let (x, y) = current;
+            current = match dir {
+                Direction::North => (x, y.checked_sub(1)?),
+                Direction::East => (x + 1, y),
+                Direction::South => (x, y + 1),
+                Direction::West => (x.checked_sub(1)?, y),
+            };
+            if self.cell(current)?.is_on() {
+                return Some(pos(current));
+            }
+        }
+
+        None
     }
 
-    /// Serialize this [`MapGrid`] into a [`Vec<u8>`] of msgpack data.
-    ///
-    /// ### Errors
-    /// Function errors if [`rmp_serde::to_vec`] fails.
+    /// Gets every off cell on the perimeter of this grid, along with the edge of the grid it lies on.
+    /// Entries are ordered clockwise starting from the top left corner (north, east, south, then west),
+    /// and corner cells are reported once for each of their two edges.
+    #[must_use]
+    pub fn border_openings(&self) -> Vec<(GridPos, Direction)> {
+        trace!("MapGrid::border_openings()");
+        let (right, bottom) = (self.width - 1, self.height - 1);
+        let edges = (0..self.width)
+            .map(|x| ((x, 0), Direction::North))
+            .chain((0..self.height).map(|y| ((right, y), Direction::East)))
+            .chain(
+                (0..self.width)
+                    .rev()
+                    .map(|x| ((x, bottom), Direction::South)),
+            )
+            .chain((0..self.height).rev().map(|y| ((0, y), Direction::West)));
+
+        edges
+            .filter(|&((x, y), _)| self.cells[y][x].is_off())
+            .map(|(p, dir)| (GridPos::from(p), dir))
+            .collect()
+    }
+
+    /// Groups the off cells on the border of this grid into runs of neighboring cells along each edge,
+    /// which are natural places to connect this map to another one. Edges are reported in the order
+    /// north, east, south, then west, and each run is ordered by increasing x (north and south) or y
+    /// (east and west). Corner cells are part of a run on both of their edges.
     ///
-    /// ##### See also: [`rmp_serde::to_vec`]
-    pub fn to_msgpack(&self) -> Result<Vec<u8>, rmp_serde::encode::Error> {
-        rmp_serde::to_vec(self)
+    /// ##### See also: [`MapGrid::border_openings`]
+    #[must_use]
+    pub fn edge_open_runs(&self) -> Vec<(Direction, Vec<GridPos>)> {
+        trace!("MapGrid::edge_open_runs()");
+        let (right, bottom) = (self.width - 1, self.height - 1);
+        let edges: [(Direction, Vec<(usize, usize)>); 4] = [
+            (Direction::North, (0..self.width).map(|x| (x, 0)).collect()),
+            (
+                Direction::East,
+                (0..self.height).map(|y| (right, y)).collect(),
+            ),
+            (
+                Direction::South,
+                (0..self.width).map(|x| (x, bottom)).collect(),
+            ),
+            (Direction::West, (0..self.height).map(|y| (0, y)).collect()),
+        ];
+
+        let mut runs = Vec::new();
+        for (dir, cells) in edges {
+            let mut run = Vec::new();
+            for (x, y) in cells {
+                if self.cells[y][x].is_off() {
+                    run.push(pos((x, y)));
+                } else if !run.is_empty() {
+                    runs.push((dir, std::mem::take(&mut run)));
+                }
+            }
+            if !run.is_empty() {
+                runs.push((dir, run));
+            }
+        }
+
+        runs
     }
-}
 
-impl From<PFGrid> for MapGrid {
-    fn from(pfg: PFGrid) -> Self {
-        let mut grid = MapGrid::empty((pfg.width, pfg.height));
-        for (x, y) in pfg.iter() {
-            grid.set_cell_state(x, y, true);
+    /// Opens up to `count` exits on the border of this grid (excluding corners), each connected to the
+    /// open interior by carving straight inwards from the border until an existing open cell is reached.
+    /// Border cells needing the shortest tunnel are preferred, ties are broken randomly using `seed`,
+    /// and no two new exits are placed next to each other. Returns the position of every exit created,
+    /// which is fewer than `count` (with a warning) if there are not enough usable border cells.
+    pub fn punch_exits(&mut self, count: usize, seed: u64) -> Vec<GridPos> {
+        trace!("MapGrid::punch_exits({}, {})", count, seed);
+        let (right, bottom) = (self.width - 1, self.height - 1);
+        let inward = |(x, y): (usize, usize), dir: Direction| match dir {
+            Direction::North => (x, y + 1),
+            Direction::East => (x - 1, y),
+            Direction::South => (x, y - 1),
+            Direction::West => (x + 1, y),
+        };
+        let on_border = |(x, y): (usize, usize)| x == 0 || y == 0 || x == right || y == bottom;
+
+        let mut candidates = Vec::new();
+        let edges = (1..right)
+            .flat_map(|x| [((x, 0), Direction::North), ((x, bottom), Direction::South)])
+            .chain(
+                (1..bottom)
+                    .flat_map(|y| [((0, y), Direction::West), ((right, y), Direction::East)]),
+            );
+        for (start, dir) in edges {
+            if self.cells[start.1][start.0].is_off() {
+                continue;
+            }
+
+            let mut tunnel = vec![start];
+            let mut current = inward(start, dir);
+            while !on_border(current) {
+                if self.cells[current.1][current.0].is_off() {
+                    candidates.push(tunnel);
+                    break;
+                }
+
+                tunnel.push(current);
+                current = inward(current, dir);
+            }
         }
 
-        grid
+        crate::util::random::with_rng(seed, |rng| rng.shuffle(&mut candidates));
+        candidates.sort_by_key(Vec::len);
+
+        let mut exits: Vec<(usize, usize)> = Vec::with_capacity(count);
+        for tunnel in candidates {
+            if exits.len() >= count {
+                break;
+            }
+
+            let (x, y) = tunnel[0];
+            if exits
+                .iter()
+                .any(|&(ex, ey)| absdiff(ex, x) <= 1 && absdiff(ey, y) <= 1)
+            {
+                continue;
+            }
+
+            for (tx, ty) in tunnel {
+                self.cells[ty][tx].set_state(TriState::False);
+            }
+            exits.push((x, y));
+        }
+
+        if exits.len() < count {
+            warn!(
+                "MapGrid::punch_exits - Only able to place {} of {} exits",
+                exits.len(),
+                count
+            );
+        }
+
+        exits.into_iter().map(GridPos::from).collect()
     }
-}
 
-impl From<&PFGrid> for MapGrid {
-    fn from(pfg: &PFGrid) -> Self {
-        let mut grid = MapGrid::empty((pfg.width, pfg.height));
-        for (x, y) in pfg.iter() {
-            grid.set_cell_state(x, y, true);
+    /// Carves an open room of the given `size` as close to `near` as possible (measured from the
+    /// room's center), placed so that it and the ring of cells around it are entirely walls. The
+    /// room is therefore sealed off from all existing open space and never touches the grid border,
+    /// see [`MapGrid::enclosed_region_count`]. Ties between equally close spots are broken randomly
+    /// using `seed`. Returns the carved square, or [`None`] if there is no solid area big enough.
+    pub fn add_secret_room(
+        &mut self,
+        near: GridPos,
+        size: GridSize,
+        seed: u64,
+    ) -> Option<GridSquare> {
+        trace!("MapGrid::add_secret_room({:?}, {:?}, {})", near, size, seed);
+        let (width, height) = (size.width, size.height);
+        if width == 0 || height == 0 || width + 2 > self.width || height + 2 > self.height {
+            warn!(
+                "MapGrid::add_secret_room - A {}x{} room cannot fit in a {}x{} grid",
+                width, height, self.width, self.height
+            );
+            return None;
         }
 
-        grid
+        let solid = (width + 2) * (height + 2);
+        let mut candidates = Vec::new();
+        for y in 1..self.height - height {
+            for x in 1..self.width - width {
+                let walls = square(&(x - 1, y - 1), width + 2, height + 2);
+                if self.count_state_in_square(&walls, TriState::True) == solid {
+                    candidates.push((x, y));
+                }
+            }
+        }
+
+        crate::util::random::with_rng(seed, |rng| rng.shuffle(&mut candidates));
+        candidates.sort_by_key(|&(x, y)| {
+            absdiff(x + width / 2, near.x) + absdiff(y + height / 2, near.y)
+        });
+
+        let room = square(candidates.first()?, width, height);
+        let (x_range, y_range) = room.ranges_usize();
+        for y in y_range {
+            for x in x_range.clone() {
+                self.cells[y][x].set_state(TriState::False);
+            }
+        }
+
+        Some(room)
     }
-}
 
-impl PartialEq for MapGrid {
-    /// Checks whether `other` is equal to this [`MapGrid`].
-    ///
-    /// This does check ***EACH CELL*** in the [`MapGrid`], but it has early outs
-    /// if the dimensions or name of the grids are not equal.
-    fn eq(&self, other: &MapGrid) -> bool {
-        trace!("MapGrid::eq()");
-        if self.width != other.width || self.height != other.height {
-            return false;
+    /// Carves each of the `rooms` into this grid by turning their cells off, then removes the single
+    /// wall separating any two rooms that are within 1 cell of each other (along the span where they
+    /// face each other), so that neighboring rooms form one open space.
+    pub fn carve_rooms_merged(&mut self, rooms: &[Room]) {
+        trace!("MapGrid::carve_rooms_merged({} rooms)", rooms.len());
+        let mut carve = |xs: std::ops::Range<usize>, ys: std::ops::Range<usize>| {
+            for y in ys.start..ys.end.min(self.height) {
+                for x in xs.start..xs.end.min(self.width) {
+                    self.cells[y][x].set_state(TriState::False);
+                }
+            }
+        };
+
+        for room in rooms {
+            let sq = room.square();
+            carve(sq.x_range(), sq.y_range());
         }
 
-        if self.name != other.name {
-            return false;
+        for (i, first) in rooms.iter().enumerate() {
+            for second in &rooms[i + 1..] {
+                let (a, b) = (first.square(), second.square());
+                let x_overlap = a.min.x.max(b.min.x)..a.max.x.min(b.max.x);
+                let y_overlap = a.min.y.max(b.min.y)..a.max.y.min(b.max.y);
+                if !y_overlap.is_empty() {
+                    let gap = a.max.x.min(b.max.x)..a.min.x.max(b.min.x);
+                    if gap.len() == 1 {
+                        carve(gap, y_overlap.clone());
+                    }
+                }
+                if !x_overlap.is_empty() {
+                    let gap = a.max.y.min(b.max.y)..a.min.y.max(b.min.y);
+                    if gap.len() == 1 {
+                        carve(x_overlap, gap);
+                    }
+                }
+            }
         }
+    }
 
-        for (row, other_row) in self.cells.iter().zip(other.cells.iter()) {
-            for (cell, other_cell) in row.iter().zip(other_row.iter()) {
-                if cell != other_cell {
-                    return false;
+    /// Reverses this entire [`MapGrid`] by calling [`crate::data::TriCell::toggle()`] on each cell in the grid.
+    pub fn reverse_in_place(&mut self) {
+        trace!("MapGrid::reverse_in_place()");
+        for cell in self.iter_mut() {
+            cell.toggle();
+        }
+    }
+
+    /// Toggles every cell inside of `square`, clamped to the bounds of the grid. Invalid cells
+    /// remain invalid.
+    ///
+    /// ##### See also: [`MapGrid::reverse_in_place`]
+    pub fn invert_region(&mut self, square: &GridSquare) {
+        trace!("MapGrid::invert_region({:?})", square);
+        let (xs, ys) = square.ranges_usize();
+        for y in ys.start..ys.end.min(self.height) {
+            for x in xs.start..xs.end.min(self.width) {
+                self.cells[y][x].toggle();
+            }
+        }
+    }
+
+    /// Sets every cell inside of `square`, clamped to the bounds of the grid, to `cell`. Unlike the
+    /// state based setters this can also make cells invalid. Returns the number of cells that actually
+    /// changed.
+    ///
+    /// ##### See also: [`MapGrid::invert_region`]
+    pub fn set_square(&mut self, square: &GridSquare, cell: Cell) -> usize {
+        trace!("MapGrid::set_square({:?}, {:?})", square, cell);
+        let (xs, ys) = square.ranges_usize();
+        let mut changed = 0;
+        for y in ys.start..ys.end.min(self.height) {
+            for x in xs.start..xs.end.min(self.width) {
+                if self.cells[y][x] != cell {
+                    self.cells[y][x] = cell;
+                    changed += 1;
                 }
             }
         }
 
-        true
+        changed
     }
-}
 
-impl std::fmt::Debug for MapGrid {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "MapGrid {{ name: {:?}, width: {}, height: {} }}",
-            self.name, self.width, self.height
-        )?;
-        writeln!(f)?;
-        let mut i = 0;
-        write!(f, " ")?;
-        while i < self.width {
-            write!(f, "{}", i % 10)?;
-            i += 1;
+    /// Returns an iterator over all of the cells in this [`MapGrid`].
+    pub fn iter(&self) -> impl Iterator<Item = &Cell> {
+        self.cells.iter().flat_map(|row| row.iter())
+    }
+
+    /// Returns an iterator over all of the cells along with their position in this [`MapGrid`].
+    pub fn iter_pos(&self) -> impl Iterator<Item = ((usize, usize), &Cell)> {
+        self.cells
+            .iter()
+            .enumerate()
+            .flat_map(|(y, row)| row.iter().enumerate().map(move |(x, cell)| ((x, y), cell)))
+    }
+
+    /// Returns an iterator over the [`TriState`] of every cell in this [`MapGrid`], in row-major order.
+    pub fn iter_states(&self) -> impl Iterator<Item = TriState> + '_ {
+        self.iter().map(|cell| cell.state())
+    }
+
+    /// Returns an iterator over the position and [`TriState`] of every cell in this [`MapGrid`].
+    pub fn iter_pos_states(&self) -> impl Iterator<Item = (GridPos, TriState)> + '_ {
+        self.iter_pos()
+            .map(|(pos, cell)| (GridPos::from(pos), cell.state()))
+    }
+
+    /// Returns an iterator over the positions of every on cell in this [`MapGrid`], in row-major order.
+    ///
+    /// ##### See also: [`MapGrid::iter_off`]
+    pub fn iter_on(&self) -> impl Iterator<Item = GridPos> + '_ {
+        self.iter_pos()
+            .filter(|(_, cell)| cell.is_on())
+            .map(|(pos, _)| GridPos::from(pos))
+    }
+
+    /// Returns an iterator over the positions of every off cell in this [`MapGrid`], in row-major order.
+    ///
+    /// ##### See also: [`MapGrid::iter_on`]
+    pub fn iter_off(&self) -> impl Iterator<Item = GridPos> + '_ {
+        self.iter_pos()
+            .filter(|(_, cell)| cell.is_off())
+            .map(|(pos, _)| GridPos::from(pos))
+    }
+
+    /// Finds the first cell, in row-major (reading) order, for which `pred` returns true. Returns
+    /// the position and a reference to the matching cell, or [`None`] if no cell matches.
+    ///
+    /// ##### See also: [`MapGrid::find_all`]
+    pub fn find_cell<F: Fn(GridPos, &Cell) -> bool>(&self, pred: F) -> Option<(GridPos, &Cell)> {
+        trace!("MapGrid::find_cell(pred)");
+        self.iter_pos()
+            .map(|(pos, cell)| (GridPos::from(pos), cell))
+            .find(|&(pos, cell)| pred(pos, cell))
+    }
+
+    /// Finds every cell for which `pred` returns true, in row-major order.
+    ///
+    /// ##### See also: [`MapGrid::find_cell`]
+    pub fn find_all<F: Fn(GridPos, &Cell) -> bool>(&self, pred: F) -> Vec<(GridPos, &Cell)> {
+        trace!("MapGrid::find_all(pred)");
+        self.iter_pos()
+            .map(|(pos, cell)| (GridPos::from(pos), cell))
+            .filter(|&(pos, cell)| pred(pos, cell))
+            .collect()
+    }
+
+    /// Returns a mutable iterator over all of the cells in this [`MapGrid`].
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Cell> {
+        self.cells.iter_mut().flat_map(|row| row.iter_mut())
+    }
+
+    /// Returns a mutable iterator over all of the cells along with their position in this [`MapGrid`].
+    pub fn iter_pos_mut(&mut self) -> impl Iterator<Item = ((usize, usize), &mut Cell)> {
+        self.cells.iter_mut().enumerate().flat_map(|(y, row)| {
+            row.iter_mut()
+                .enumerate()
+                .map(move |(x, cell)| ((x, y), cell))
+        })
+    }
+
+    /// Creates a new grid from the given [`section`](`crate::data::types::GridSquare`) of the current grid.
+    ///
+    /// TODO: Fix this to either handle overflow (by wrapping) or fail more gracefully.
+    ///
+    /// ### Panics
+    /// Function panics if the size of `section` is less than 3x3.
+    #[must_use]
+    pub fn create_subgrid(&self, section: &GridSquare) -> Self {
+        if section.height() < 3 || section.width() < 3 {
+            error!("Invalid GridSquare size: {:?}", section);
+            panic!("Invalid GridSquare size");
         }
-        writeln!(f)?;
-        let grid = self.to_strings();
-        for (y, grid) in grid.iter().enumerate() {
-            writeln!(f, "{}{}", y % 10, grid)?;
+
+        if section.max.x > self.width || section.max.y > self.height {
+            error!(
+                "Section is too big for current grid: Grid Size = {:?} Section = {:?}",
+                self.size(),
+                section
+            );
+            panic!("Invalid GridSquare size");
         }
 
-        Ok(())
+        MapGrid::sub_grid(self, section)
     }
-}
 
-impl std::fmt::Display for MapGrid {
-    /// Displays a fancy [`MapGrid`] over multiple lines.
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let title_line = if self.has_name() {
-            format!(
-                "{} ({}x{})",
-                self.name.as_ref().unwrap(),
-                self.width,
-                self.height
-            )
-        } else {
-            format!("MapGrid ({}x{})", self.width, self.height)
-        };
-        write!(
-            f,
-            "|  {}\n|{}\n",
-            title_line,
-            "-".repeat(title_line.len() + 4)
-        )?;
-        for line in &self.to_strings() {
-            writeln!(f, "|{}", line)?;
+    /// Gets the smallest square containing every cell whose state is not `state`, or [`None`] if every
+    /// cell matches `state`. Invalid cells never match, so they are always inside the square.
+    #[must_use]
+    pub fn bounding_box(&self, state: bool) -> Option<GridSquare> {
+        trace!("MapGrid::bounding_box({})", state);
+        let target: TriState = state.into();
+        let mut bounds: Option<((usize, usize), (usize, usize))> = None;
+        for ((x, y), cell) in self.iter_pos() {
+            if cell.state() == target {
+                continue;
+            }
+
+            bounds = Some(match bounds {
+                None => ((x, y), (x, y)),
+                Some((min, max)) => ((min.0.min(x), min.1.min(y)), (max.0.max(x), max.1.max(y))),
+            });
         }
 
-        Ok(())
+        bounds.map(|(min, max)| square(&min, max.0 - min.0 + 1, max.1 - min.1 + 1))
     }
-}
 
-#[cfg(test)]
-#[allow(clippy::cognitive_complexity, clippy::too_many_lines, unused)]
-mod tests {
-    use super::*;
+    /// Creates a copy of this grid with every outer row and column made up entirely of `state` cells
+    /// removed, leaving the [`bounding box`](`MapGrid::bounding_box`) of the remaining content. The
+    /// result keeps this grid's name. Since a [`MapGrid`] can not be smaller than 3x3, the box is
+    /// widened to 3 cells (keeping as much `state` padding as needed) and a warning is logged if the
+    /// content is narrower or shorter than that.
+    ///
+    /// ##### See also: [`MapGrid::trim`]
+    #[must_use]
+    pub fn cropped(&self, state: bool) -> Self {
+        trace!("MapGrid::cropped({})", state);
+        let (x, y, width, height) = match self.bounding_box(state) {
+            Some(b) => (b.min.x, b.min.y, b.width(), b.height()),
+            None => (0, 0, 0, 0),
+        };
+        let (x, width) = if width < 3 {
+            warn!(
+                "MapGrid::cropped - content is only {} columns wide, keeping 3",
+                width
+            );
+            (x.min(self.width - 3), 3)
+        } else {
+            (x, width)
+        };
+        let (y, height) = if height < 3 {
+            warn!(
+                "MapGrid::cropped - content is only {} rows tall, keeping 3",
+                height
+            );
+            (y.min(self.height - 3), 3)
+        } else {
+            (y, height)
+        };
 
-    use assert_float_eq::{
-        afe_abs, afe_absolute_error_msg, afe_is_absolute_eq, afe_is_relative_eq,
-        afe_relative_error_msg, assert_float_absolute_eq, assert_float_relative_eq,
-    };
+        let mut grid = Self::sub_grid(self, &square(&(x, y), width, height));
+        grid.name.clone_from(&self.name);
+        grid
+    }
 
-    use crate::assert_unordered_match;
-    use crate::data::pos;
-    use crate::util::testing::crate_before_test;
+    /// Shrinks this grid in place by removing every outer row and column made up entirely of `state`
+    /// cells. See [`MapGrid::cropped`] for how the 3x3 minimum size is handled.
+    pub fn trim(&mut self, state: bool) {
+        trace!("MapGrid::trim({})", state);
+        *self = self.cropped(state);
+    }
 
-    fn init() {
-        let _ = env_logger::builder().is_test(true).try_init();
-        crate::util::random::init_rng_seeded(0);
+    /// Splits this grid into chunks of `chunk_w`x`chunk_h` cells, in row-major order, yielding the square
+    /// each chunk covers along with a copy of its cells (see [`MapGrid::sub_grid`]). Chunks along the right
+    /// and bottom edges are smaller when the grid size is not a multiple of the chunk size. Since a
+    /// [`MapGrid`] can not be smaller than 3x3, any chunk narrower or shorter than that is padded with
+    /// invalid cells, the yielded square always reflects the real area covered.
+    ///
+    /// ### Panics
+    /// Function panics if `chunk_w` or `chunk_h` is 0.
+    pub fn chunks(
+        &self,
+        chunk_w: usize,
+        chunk_h: usize,
+    ) -> impl Iterator<Item = (GridSquare, MapGrid)> + '_ {
+        trace!("MapGrid::chunks({}, {})", chunk_w, chunk_h);
+        assert!(
+            chunk_w > 0 && chunk_h > 0,
+            "Chunk size must be greater than 0"
+        );
+        (0..self.height).step_by(chunk_h).flat_map(move |y| {
+            (0..self.width).step_by(chunk_w).map(move |x| {
+                let (w, h) = (chunk_w.min(self.width - x), chunk_h.min(self.height - y));
+                let section = square(&(x, y), w, h);
+                let chunk = if w >= 3 && h >= 3 {
+                    Self::sub_grid(self, &section)
+                } else {
+                    let mut padded = Self::new((w.max(3), h.max(3)));
+                    padded.name = self.name.as_ref().map(|n| format!("SubGrid of {}", n));
+                    for cy in 0..h {
+                        for cx in 0..w {
+                            padded.cells[cy][cx] = self.cells[y + cy][x + cx];
+                        }
+                    }
+
+                    padded
+                };
+
+                (section, chunk)
+            })
+        })
+    }
+
+    /// Creates a new grid from the given `square` of this grid (see [`MapGrid::create_subgrid`]), along
+    /// with a closure that maps positions in the new grid back to their positions in this grid.
+    ///
+    /// ### Panics
+    /// Function panics if the size of `square` is less than 3x3 or it does not fit in this grid.
+    pub fn clone_region(&self, square: &GridSquare) -> (Self, impl Fn(GridPos) -> GridPos) {
+        trace!("MapGrid::clone_region({:?})", square);
+        let offset = square.min.to_vector();
+        let region = self.create_subgrid(square);
+        (region, move |pos: GridPos| pos + offset)
+    }
+
+    /// Creates a new, smaller [`MapGrid`] where each cell represents a `factor`x`factor` block of this
+    /// grid, taking the majority state of the valid cells in that block (ties count as off). Blocks
+    /// containing only invalid cells stay invalid. The result is never smaller than 3x3, any cells
+    /// that fall outside of this grid because of that are left off.
+    ///
+    /// ### Panics
+    /// Function panics if `factor` is 0.
+    #[must_use]
+    pub fn downscale(&self, factor: usize) -> Self {
+        trace!("MapGrid::downscale({})", factor);
+        assert!(factor > 0, "Downscale factor must be greater than 0");
+        let width = ((self.width + factor - 1) / factor).max(3);
+        let height = ((self.height + factor - 1) / factor).max(3);
+        let mut grid = Self::empty((width, height));
+        for ((x, y), cell) in grid.iter_pos_mut() {
+            let (mut on, mut off) = (0usize, 0usize);
+            for sy in (y * factor)..((y + 1) * factor).min(self.height) {
+                for sx in (x * factor)..((x + 1) * factor).min(self.width) {
+                    match self.cells[sy][sx].state() {
+                        TriState::True => on += 1,
+                        TriState::False => off += 1,
+                        TriState::Invalid => {}
+                    }
+                }
+            }
+
+            let in_bounds = x * factor < self.width && y * factor < self.height;
+            if in_bounds && on == 0 && off == 0 {
+                cell.set_state(TriState::Invalid);
+            } else {
+                cell.set_state((on > off).into());
+            }
+        }
+
+        grid
+    }
+
+    /// Shrinks this grid with [`MapGrid::downscale`] so that it fits within `max` without changing its
+    /// aspect ratio, using the smallest integer factor that fits (i.e. the largest possible result).
+    /// Grids that already fit are returned unchanged. Like [`MapGrid::downscale`] the result is never
+    /// smaller than 3x3, so a `max` smaller than that cannot be honored.
+    #[must_use]
+    pub fn fit_to(&self, max: GridSize) -> Self {
+        trace!("MapGrid::fit_to({:?})", max);
+        let factor_for = |actual: usize, limit: usize| (actual + limit - 1) / limit;
+        let factor = factor_for(self.width, max.width.max(1))
+            .max(factor_for(self.height, max.height.max(1)))
+            .max(1);
+        if factor == 1 {
+            return self.clone();
+        }
+
+        self.downscale(factor)
+    }
+
+    /// Creates a new, larger [`MapGrid`] where each cell of this grid is replicated into a
+    /// `factor`x`factor` block (nearest-neighbor scaling).
+    ///
+    /// ### Panics
+    /// Function panics if `factor` is 0.
+    #[must_use]
+    pub fn upscale(&self, factor: usize) -> Self {
+        trace!("MapGrid::upscale({})", factor);
+        assert!(factor > 0, "Upscale factor must be greater than 0");
+        let mut grid = Self::new((self.width * factor, self.height * factor));
+        for ((x, y), cell) in grid.iter_pos_mut() {
+            *cell = self.cells[y / factor][x / factor];
+        }
+
+        grid
+    }
+
+    /// Creates a new [`MapGrid`] with 4-fold mirror symmetry by taking the top left quadrant of this
+    /// grid (rounded up) and mirroring it horizontally and vertically into the other three. The
+    /// result is twice the quadrant size, minus one for odd dimensions where the center row or
+    /// column is shared, so it always has the same size as this grid.
+    ///
+    /// ##### See also: [`MapGrid::generate_quadrant_symmetric`]
+    #[must_use]
+    pub fn kaleidoscope(&self) -> Self {
+        trace!("MapGrid::kaleidoscope()");
+        let (width, height) = (self.width, self.height);
+        let mut grid = Self::empty((width, height));
+        for ((x, y), cell) in grid.iter_pos_mut() {
+            *cell = self.cells[y.min(height - 1 - y)][x.min(width - 1 - x)];
+        }
+
+        grid
+    }
+
+    /// Creates a new [`MapGrid`] rotated clockwise by `90 * (times % 4)` degrees. Width and height are
+    /// swapped for odd rotations, and every cell (including `Invalid` ones) is carried over exactly. If
+    /// this grid has a name, the rotated grid will be named "<Name> (Rotated)" (the suffix is not
+    /// repeated when rotating an already rotated grid). Zero effective rotations return an exact copy.
+    ///
+    /// ##### See also: [`MapGrid::flip_horizontal`], [`MapGrid::flip_vertical`]
+    #[must_use]
+    pub fn rotate90(&self, times: u8) -> Self {
+        trace!("MapGrid::rotate90({})", times);
+        let turns = times % 4;
+        let mut grid = self.clone();
+        for _ in 0..turns {
+            let height = grid.height;
+            let mut rotated = Self::empty((grid.height, grid.width));
+            for ((x, y), cell) in rotated.iter_pos_mut() {
+                *cell = grid.cells[height - 1 - x][y];
+            }
+            grid = rotated;
+        }
+
+        if turns > 0 {
+            grid.name = self.name_ref().as_ref().map(|n| {
+                if n.ends_with(" (Rotated)") {
+                    n.clone()
+                } else {
+                    format!("{} (Rotated)", n)
+                }
+            });
+        }
+
+        grid
+    }
+
+    /// Creates a new [`MapGrid`] mirrored left to right. If this grid has a name, the flipped grid will
+    /// be named "<Name> (Flipped)", without repeating the suffix if this grid was already flipped.
+    #[must_use]
+    pub fn flip_horizontal(&self) -> Self {
+        trace!("MapGrid::flip_horizontal()");
+        let mut grid = self.clone();
+        for row in &mut grid.cells {
+            row.reverse();
+        }
+
+        grid.name = self.name_ref().as_ref().map(|n| {
+            if n.ends_with(" (Flipped)") {
+                n.clone()
+            } else {
+                format!("{} (Flipped)", n)
+            }
+        });
+        grid
+    }
+
+    /// Creates a new [`MapGrid`] mirrored top to bottom. If this grid has a name, the flipped grid will
+    /// be named "<Name> (Flipped)", without repeating the suffix if this grid was already flipped.
+    #[must_use]
+    pub fn flip_vertical(&self) -> Self {
+        trace!("MapGrid::flip_vertical()");
+        let mut grid = self.clone();
+        grid.cells.reverse();
+
+        grid.name = self.name_ref().as_ref().map(|n| {
+            if n.ends_with(" (Flipped)") {
+                n.clone()
+            } else {
+                format!("{} (Flipped)", n)
+            }
+        });
+        grid
+    }
+
+    /// Creates a new [`MapGrid`] where every valid cell with at least one orthogonal neighbor matching
+    /// `state` is set to `state` (morphological dilation). Invalid cells are left untouched.
+    ///
+    /// ##### See also: [`MapGrid::erode`], [`MapGrid::closing`]
+    #[must_use]
+    pub fn dilate(&self, state: bool) -> Self {
+        trace!("MapGrid::dilate({})", state);
+        let mut grid = self.clone();
+        for ((x, y), cell) in grid.iter_pos_mut() {
+            let touching = self.neighbors_with_state_ortho((x, y), state, false);
+            if cell.is_valid() && !touching.is_empty() {
+                cell.set_state(state.into());
+            }
+        }
+
+        grid
+    }
+
+    /// Creates a new [`MapGrid`] where cells matching `state` only keep it if none of their orthogonal
+    /// neighbors has the opposite state (morphological erosion). Neighbors outside of the grid and
+    /// invalid neighbors are ignored, so shapes touching the edge of the grid are not eaten away there.
+    ///
+    /// ##### See also: [`MapGrid::dilate`], [`MapGrid::opening`]
+    #[must_use]
+    pub fn erode(&self, state: bool) -> Self {
+        trace!("MapGrid::erode({})", state);
+        let mut grid = self.clone();
+        for ((x, y), cell) in grid.iter_pos_mut() {
+            let touching = self.neighbors_with_state_ortho((x, y), !state, false);
+            if cell.state() == state.into() && !touching.is_empty() {
+                cell.set_state((!state).into());
+            }
+        }
+
+        grid
+    }
+
+    /// Erodes and then dilates the cells matching `state`, removing specks and thin spurs that are
+    /// narrower than 3 cells while mostly keeping the shape of larger areas.
+    #[must_use]
+    pub fn opening(&self, state: bool) -> Self {
+        trace!("MapGrid::opening({})", state);
+        self.erode(state).dilate(state)
+    }
+
+    /// Dilates and then erodes the cells matching `state`, filling single cell gaps and holes while
+    /// mostly keeping the shape of larger areas.
+    #[must_use]
+    pub fn closing(&self, state: bool) -> Self {
+        trace!("MapGrid::closing({})", state);
+        self.dilate(state).erode(state)
+    }
+
+    /// Creates a new [`MapGrid`] with its open areas grown into the surrounding walls for `iterations`
+    /// passes, each of which [`dilates`](`MapGrid::dilate`) the off cells by one cell. The outermost
+    /// rows and columns are never changed, so a sealed border stays sealed.
+    #[must_use]
+    pub fn widen_corridors(&self, iterations: usize) -> Self {
+        trace!("MapGrid::widen_corridors({})", iterations);
+        let mut grid = self.clone();
+        for _ in 0..iterations {
+            grid = grid.dilate(false);
+            for ((x, y), cell) in grid.iter_pos_mut() {
+                if x == 0 || y == 0 || x == self.width - 1 || y == self.height - 1 {
+                    *cell = self.cells[y][x];
+                }
+            }
+        }
+
+        grid
+    }
+
+    /// Creates a new [`MapGrid`] where only the floor cells bordering a wall are on: a cell is on if it
+    /// is off in this grid and at least one of its eight neighbors is on. Every other cell is off. This
+    /// traces the inside edge of every room, e.g. for rendering.
+    #[must_use]
+    pub fn outline(&self) -> Self {
+        trace!("MapGrid::outline()");
+        let mut grid = Self::empty(self.size());
+        if let Some(name) = self.name_ref() {
+            grid.set_name(format!("Outline of {}", name));
+        }
+
+        for ((x, y), cell) in grid.iter_pos_mut() {
+            let edge = self.cells[y][x].is_off()
+                && !self.neighbors_with_state((x, y), true, false).is_empty();
+            cell.set_state(edge.into());
+        }
+
+        grid
+    }
+
+    /// Creates a new [`MapGrid`] where the open (off) areas of this grid are thinned down to 1 cell wide
+    /// center lines using the Zhang-Suen thinning algorithm, turning every removed cell on. The result
+    /// keeps the connectivity of the open areas, which makes it a good guide for routing corridors
+    /// through wide caves. Note that diagonal parts of the skeleton may only touch at their corners.
+    /// Cells outside of the grid and invalid cells are treated as walls.
+    #[must_use]
+    pub fn skeletonize(&self) -> Self {
+        // P2 through P9 of the algorithm: clockwise starting from the cell above, offset by +1
+        const RING: [(usize, usize); 8] = [
+            (1, 0),
+            (2, 0),
+            (2, 1),
+            (2, 2),
+            (1, 2),
+            (0, 2),
+            (0, 1),
+            (0, 0),
+        ];
+
+        trace!("MapGrid::skeletonize()");
+        let mut grid = self.clone();
+        let is_open = |grid: &Self, x: usize, y: usize, (dx, dy): (usize, usize)| {
+            let (nx, ny) = (x + dx, y + dy);
+            (1..=grid.width).contains(&nx)
+                && (1..=grid.height).contains(&ny)
+                && grid.cells[ny - 1][nx - 1].is_off()
+        };
+
+        loop {
+            let mut changed = false;
+            for step in 0..2 {
+                let mut remove = Vec::new();
+                for (y, row) in grid.cells.iter().enumerate() {
+                    for (x, cell) in row.iter().enumerate() {
+                        if !cell.is_off() {
+                            continue;
+                        }
+
+                        let p = RING.map(|offset| is_open(&grid, x, y, offset));
+                        let neighbors = p.iter().filter(|&&open| open).count();
+                        let transitions = (0..8).filter(|&i| !p[i] && p[(i + 1) % 8]).count();
+                        let (p2, p4, p6, p8) = (p[0], p[2], p[4], p[6]);
+                        let directional = if step == 0 {
+                            !(p4 && p6 && (p2 || p8))
+                        } else {
+                            !(p2 && p8 && (p4 || p6))
+                        };
+
+                        if (2..=6).contains(&neighbors) && transitions == 1 && directional {
+                            remove.push((x, y));
+                        }
+                    }
+                }
+
+                changed |= !remove.is_empty();
+                for (x, y) in remove {
+                    grid.cells[y][x].set_state(TriState::True);
+                }
+            }
+
+            if !changed {
+                break;
+            }
+        }
+
+        grid
+    }
+
+    /// Runs the given pipeline `steps` in order, starting from this grid. This is a shortcut around
+    /// building a [`Pipeline`] by hand for the common "generate then refine" flow.
+    ///
+    /// ### Errors
+    /// - Returns [`PipelineError::NoSteps`] if `steps` is empty.
+    /// - Returns any [`PipelineError`] raised by one of the steps.
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn transform(self, steps: Vec<Box<dyn PipelineStep>>) -> PipelineResult {
+        trace!("MapGrid::transform({} steps)", steps.len());
+        if steps.is_empty() {
+            return Err(PipelineError::NoSteps);
+        }
+
+        let mut pipeline = Pipeline::new();
+        for step in steps {
+            pipeline.add_boxed_step(step);
+        }
+
+        pipeline.run(&self)
+    }
+
+    /// Resize all rows in the grid to the given size, using [`crate::data::Cell::invalid()`]
+    /// as the default value for each added cell. Rows cannot be resized to be less than
+    /// 3. If grid currently already has `new_row_size` rows, function will early out.
+    ///
+    /// #### This changes the SIZE OF EACH ROW aka the width of the [`MapGrid`], NOT the ROW COUNT (which would be the height).
+    /// ##### This is the same as calling [`MapGrid::resize_rows_with(new_row_size, Cell::invalid())`].
+    ///
+    /// ### Panics
+    /// Function panics if the resulting size of the grid is less than 3x3, which should not happen
+    /// because the function first checks if the result is going to be less than 3.
+    pub fn resize_rows(&mut self, new_row_size: usize) {
+        trace!("MapGrid::resize_rows({})", new_row_size);
+        self.resize_rows_with(new_row_size, Cell::invalid());
+    }
+
+    /// Resize all rows in the grid to the given size, using `cell_value` as the
+    /// default value for each added cell. Rows cannot be resized to be less than
+    /// 3. If grid currently already has `new_row_size` rows, function will early out.
+    ///
+    /// #### This changes the SIZE OF EACH ROW aka the width of the [`MapGrid`], NOT the ROW COUNT (which would be the height).
+    ///
+    /// ### Panics
+    /// Function panics if the resulting size of the grid is less than 3x3, which should not happen
+    /// because the function first checks if the result is going to be less than 3.
+    pub fn resize_rows_with(&mut self, new_row_size: usize, cell_value: Cell) {
+        trace!(
+            "MapGrid::resize_rows_with({}, {:?})",
+            new_row_size,
+            cell_value
+        );
+        let safe_size = if new_row_size < 3 {
+            error!("MapGrid::resize_rows_with - cannot resize row length to less than 3");
+            3
+        } else {
+            new_row_size
+        };
+
+        if safe_size == self.cols() {
+            info!("MapGrid::resize_rows_with - new size same as current size, bailing on resize");
+            return;
+        }
+
+        for row in &mut self.cells {
+            row.resize(safe_size, cell_value);
+        }
+
+        assert!(
+            self.cells[0].len() == safe_size,
+            "Actual row length (self.cells[0].len() = {}) does not equal safe_size ({})",
+            self.cells[0].len(),
+            safe_size
+        );
+        self.width = safe_size;
+    }
+
+    /// Resize all columns in the grid to the given size, using [`crate::data::Cell::invalid()`]
+    /// as the default value for each added cell. Column count cannot be than 3.
+    /// If grid currently already has `new_column_size` columns, function will early out.
+    ///
+    /// #### This changes the SIZE OF EACH COLUMN aka the height of the [`MapGrid`], NOT the COLUMN COUNT (which would be the width).
+    /// ##### This is the same as calling [`MapGrid::resize_cols_with(new_column_size, Cell::invalid())`].
+    ///
+    /// ### Panics
+    /// Function panics if the resulting size of the grid is less than 3x3, which should not happen
+    /// because the function first checks if the result is going to be less than 3.
+    pub fn resize_cols(&mut self, new_column_size: usize) {
+        trace!("MapGrid::resize_cols({})", new_column_size);
+        self.resize_cols_with(new_column_size, Cell::invalid());
+    }
+
+    /// Resize all columns in the grid to the given size, using `cell_value` as the
+    /// default value for each added cell. Column count cannot be less than 3.
+    /// If grid currently already has `new_column_size` columns, function will early out.
+    ///
+    /// #### This changes the SIZE OF EACH COLUMN aka the height of the [`MapGrid`], NOT the COLUMN COUNT (which would be the width).
+    ///
+    /// ### Panics
+    /// Function panics if the resulting size of the grid is less than 3x3, which should not happen
+    /// because the function first checks if the result is going to be less than 3.
+    pub fn resize_cols_with(&mut self, new_column_size: usize, cell_value: Cell) {
+        trace!(
+            "MapGrid::resize_cols_with({}, {:?})",
+            new_column_size,
+            cell_value
+        );
+        let safe_size = if new_column_size < 3 {
+            error!("MapGrid::resize_cols_with - cannot resize column count to less than 3");
+            3
+        } else {
+            new_column_size
+        };
+
+        if safe_size == self.rows() {
+            info!("MapGrid::resize_cols_with - new size same as current size, bailing on resize");
+            return;
+        }
+
+        let row_size = self.cols();
+        self.cells.resize(safe_size, vec![cell_value; row_size]);
+        assert!(
+            self.cells.len() == safe_size,
+            "Actual col length (self.cells.len() = {}) does not equal safe_size ({})",
+            self.cells.len(),
+            safe_size
+        );
+        self.height = safe_size;
+    }
+
+    /// Convenience function which calls:
+    /// ```
+    /// # use dungen::data::MapGrid;
+    /// # let mut grid = MapGrid::new((5, 5));
+    /// # assert!(grid.cell_count() == 25);
+    /// # let size = (10,10);
+    /// grid.resize_rows(size.0);
+    /// grid.resize_cols(size.1);
+    /// # assert!(grid.cell_count() == 100);
+    /// ```
+    ///
+    /// ### Panics
+    /// - Function panics if the resulting size of the grid is less than 3x3, which should not happen
+    /// because the function first checks if the result is going to be less than 3.
+    /// - Function panics if the actual resulting size of the grid does not match the expected end size
+    /// (which means something probably went horribly wrong or was horribly coded)
+    pub fn resize<P: Into<(usize, usize)>>(&mut self, size: P) {
+        let (width, height) = size.into();
+        if self.width != width {
+            self.resize_rows(width);
+        }
+        if self.height != height {
+            self.resize_cols(height);
+        }
+
+        let new_current: (usize, usize) = self.size().into();
+        if new_current.0 != width.max(3) || new_current.1 != height.max(3) {
+            error!(
+                "MapGrid::resize - grid not set to the expected size. Actual = {:?} Expected = {:?}",
+                self.size(),
+                (width, height)
+            );
+            panic!("MapGrid::resize - failed to resize to requested size");
+        }
+    }
+
+    /// Modifies this [`MapGrid`] by adding the contents of `other` to it
+    /// at position (0,0).
+    pub fn union_in_place(&mut self, other: &Self) {
+        self.integrate_in_place(other, (0, 0).into());
+    }
+
+    /// Integrates the given [`MapGrid`] into this one at the given position. Newer data
+    /// (from `other`) will take precedence over the currently existing data. This
+    /// [`MapGrid`] will be resized if necessary.
+    pub fn integrate_in_place(&mut self, other: &Self, offset: GridPos) {
+        let offset_size = (other.width + offset.x, other.height + offset.y);
+        if other.width + offset.x > self.width || other.height + offset.y > self.height {
+            self.resize((
+                offset_size.0.max(self.width),
+                offset_size.1.max(self.height),
+            ));
+        }
+
+        for ((x, y), &cell) in other.iter_pos() {
+            self.set_cell(x + offset.x, y + offset.y, cell);
+        }
+    }
+
+    /// Copies `prefab` onto this grid with its top left corner at `anchor`. Unlike
+    /// [`MapGrid::integrate_in_place`] this grid is never resized, any part of `prefab` falling outside
+    /// of it is clipped. If `skip_invalid` is true, invalid cells in `prefab` are treated as transparent
+    /// and leave the cells underneath them untouched. Returns the number of cells written.
+    pub fn stamp(&mut self, prefab: &Self, anchor: GridPos, skip_invalid: bool) -> usize {
+        trace!(
+            "MapGrid::stamp({:?}, {:?}, {})",
+            prefab.size(),
+            anchor,
+            skip_invalid
+        );
+        let mut written = 0;
+        for ((x, y), &cell) in prefab.iter_pos() {
+            let (Some(tx), Some(ty)) = (anchor.x.checked_add(x), anchor.y.checked_add(y)) else {
+                continue;
+            };
+            if tx >= self.width || ty >= self.height || (skip_invalid && cell.is_invalid()) {
+                continue;
+            }
+
+            self.cells[ty][tx] = cell;
+            written += 1;
+        }
+
+        written
+    }
+
+    /// Converts this [`MapGrid`] into an instance of [`pathfinding::grid::Grid`].
+    #[must_use]
+    pub fn to_pf_grid(&self) -> PFGrid {
+        let mut pf_grid = PFGrid::new(self.width, self.height);
+        pf_grid.enable_diagonal_mode();
+
+        for ((x, y), cell) in self.iter_pos() {
+            if cell.is_on() {
+                pf_grid.add_vertex((x, y));
+            }
+        }
+
+        pf_grid
+    }
+
+    /// Converts the grid to a [Vec] of [String]s, with each cell represented by the given
+    /// character.
+    #[must_use]
+    pub fn to_strings_with(&self, on: char, off: char) -> Vec<String> {
+        trace!("MapGrid::to_strings_with({}, {})", on, off);
+
+        let invalid: char = {
+            if INVALID_MARKERS[0] != on && INVALID_MARKERS[0] != off {
+                INVALID_MARKERS[0]
+            } else if INVALID_MARKERS[1] != on && INVALID_MARKERS[1] != off {
+                INVALID_MARKERS[1]
+            } else {
+                INVALID_MARKERS[2]
+            }
+        };
+        info!(
+            "MapGrid::to_strings_with - Using '{}' as invalid character",
+            invalid
+        );
+
+        let mut strings = Vec::with_capacity(self.height);
+
+        for row in &self.cells {
+            let mut string = String::with_capacity(row.len());
+            for cell in row {
+                string.push(if cell.is_on() {
+                    on
+                } else if cell.is_off() {
+                    off
+                } else {
+                    invalid
+                });
+            }
+            strings.push(string);
+        }
+
+        strings
+    }
+
+    /// Converts the grid to a [String] with each cell represented by the given on and off
+    /// characters, with each row separated by the given separator.
+    #[must_use]
+    pub fn to_string_with(&self, on: char, off: char, div: char) -> String {
+        trace!("MapGrid::to_string_with({}, {}, {})", on, off, div);
+        self.to_strings_with(on, off).join(&div.to_string())
+    }
+
+    /// Gets a [Vec] of [String]s representing the grid, using the default on and off
+    /// characters (`'#'` and `'.'` respectively).
+    #[must_use]
+    pub fn to_strings(&self) -> Vec<String> {
+        trace!("MapGrid::to_strings()");
+        self.to_strings_with('#', '.')
+    }
+
+    /// Renders the grid the same way as [`MapGrid::to_strings`], but with the character at each
+    /// position in `overlay` substituted in place of the cell's normal character. Useful for
+    /// drawing paths (e.g. `'*'` or direction arrows) over a maze. Overlay positions that are out
+    /// of bounds are ignored.
+    #[must_use]
+    pub fn render_with_overlay(&self, overlay: &[(GridPos, char)]) -> Vec<String> {
+        trace!("MapGrid::render_with_overlay({:?})", overlay);
+        let mut rows: Vec<Vec<char>> = self
+            .to_strings()
+            .iter()
+            .map(|row| row.chars().collect())
+            .collect();
+        for &(p, ch) in overlay {
+            if p.x < self.width && p.y < self.height {
+                rows[p.y][p.x] = ch;
+            } else {
+                trace!(
+                    "MapGrid::render_with_overlay - Ignoring out of bounds overlay at {:?}",
+                    p
+                );
+            }
+        }
+
+        rows.into_iter()
+            .map(|row| row.into_iter().collect())
+            .collect()
+    }
+
+    /// Same as [`MapGrid::to_strings`], but framed with a coordinate ruler: the first line holds each
+    /// column index (mod 10) and every row is prefixed with its row index (mod 10). This is the form
+    /// used by the [`std::fmt::Debug`] implementation.
+    #[must_use]
+    pub fn to_strings_bordered(&self) -> Vec<String> {
+        trace!("MapGrid::to_strings_bordered()");
+        let mut lines = Vec::with_capacity(self.height + 1);
+        lines.push(format!(
+            " {}",
+            (0..self.width)
+                .map(|x| (x % 10).to_string())
+                .collect::<String>()
+        ));
+        for (y, row) in self.to_strings().into_iter().enumerate() {
+            lines.push(format!("{}{}", y % 10, row));
+        }
+
+        lines
+    }
+
+    /// Gets a string representation of the grid with the default on and off characters
+    /// (`'#'` and `'.'` respectively).
+    #[must_use]
+    pub fn as_string(&self) -> String {
+        self.to_strings().join("\n")
+    }
+
+    /// Saves this grid to `path` in the `<Name>\n<Width> <Height>\n<Map>` format read by
+    /// [`MapGrid::parse_map_file`], marking `start` with `S` and `goal` with `G` when given. Unnamed
+    /// grids are saved with an empty name. Invalid cells are written as `X`, which is read back as open.
+    ///
+    /// ### Errors
+    /// Function errors if the file cannot be written.
+    ///
+    /// ##### See also: [`MapGrid::to_map_file`]
+    pub fn save_map_file<P: AsRef<Path> + std::fmt::Debug>(
+        &self,
+        path: P,
+        start: Option<GridPos>,
+        goal: Option<GridPos>,
+    ) -> std::io::Result<()> {
+        trace!(
+            "MapGrid::save_map_file({:?}, {:?}, {:?})",
+            path,
+            start,
+            goal
+        );
+        std::fs::write(path, self.map_file_contents(start, goal))
+    }
+
+    /// Alias for [`MapGrid::save_map_file`] with both markers given, so that parsing the file gives
+    /// back this grid along with `start` and `goal`. A warning is logged if `start` or `goal` is on a
+    /// wall, since the marker replaces it and the cell will be read back as open.
+    ///
+    /// ### Errors
+    /// Function errors if the file cannot be written.
+    pub fn to_map_file<P: AsRef<Path> + std::fmt::Debug>(
+        &self,
+        path: P,
+        start: GridPos,
+        goal: GridPos,
+    ) -> std::io::Result<()> {
+        trace!("MapGrid::to_map_file({:?}, {:?}, {:?})", path, start, goal);
+        for (marker, p) in [("Start", start), ("Goal", goal)] {
+            if matches!(self.cell(p), Some(cell) if cell.is_on()) {
+                warn!(
+                    "MapGrid::to_map_file - {} position {:?} is a wall, it will be read back as open",
+                    marker, p
+                );
+            }
+        }
+
+        self.save_map_file(path, Some(start), Some(goal))
+    }
+
+    fn map_file_contents(&self, start: Option<GridPos>, goal: Option<GridPos>) -> String {
+        let name = self.name_ref().as_deref().unwrap_or_default();
+        let mut rows: Vec<Vec<char>> = self
+            .to_strings_with('#', '.')
+            .iter()
+            .map(|row| row.chars().collect())
+            .collect();
+        for (marker, ch) in [(start, 'S'), (goal, 'G')] {
+            match marker {
+                Some(p) if p.x < self.width && p.y < self.height => rows[p.y][p.x] = ch,
+                Some(p) => warn!(
+                    "MapGrid::map_file_contents - Marker '{}' at {:?} is outside of the grid",
+                    ch, p
+                ),
+                None => {}
+            }
+        }
+
+        let mut contents = format!("{}\n{} {}\n", name, self.width, self.height);
+        contents.push_str(
+            &rows
+                .into_iter()
+                .map(|row| row.into_iter().collect::<String>())
+                .collect::<Vec<_>>()
+                .join("\n"),
+        );
+
+        contents
+    }
+}
+
+/// Serialization and Deserialization implementations.
+impl MapGrid {
+    /// Parse the given [`input`] [`serde_json::Value`] into a [`MapGrid`].
+    ///
+    /// ### Errors
+    /// Function errors if [`serde_json::from_value`] fails.
+    ///
+    /// ##### See also: [`serde_json::from_value`]
+    pub fn from_json<J: Into<serde_json::Value>>(input: J) -> Result<Self, serde_json::Error> {
+        serde_json::from_value(input.into())
+    }
+
+    /// Parse the given [`input`] string into a [`MapGrid`].
+    ///
+    /// ### Errors
+    /// Function errors if [`serde_json::from_str`] fails.
+    ///
+    /// ##### See also: [`serde_json::from_str`]
+    pub fn from_json_str<S: AsRef<str>>(input: S) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(input.as_ref())
+    }
+
+    /// Parse the given [`input`] bytes into a [`MapGrid`].
+    ///
+    /// ### Errors
+    /// Function errors if [`serde_json::from_slice`] fails.
+    ///
+    /// ##### See also: [`serde_json::from_slice`]
+    pub fn from_json_bytes<B: AsRef<[u8]>>(input: B) -> Result<Self, serde_json::Error> {
+        serde_json::from_slice(input.as_ref())
+    }
+
+    /// Parse the given [`reader`] into a [`MapGrid`].
+    ///
+    /// ### Errors
+    /// Function errors if [`serde_json::from_reader`] fails.
+    ///
+    /// ##### See also: [`serde_json::from_reader`]
+    pub fn from_json_reader<R: Read>(reader: R) -> Result<Self, serde_json::Error> {
+        serde_json::from_reader(reader)
+    }
+
+    /// Open the [`path`](`std::convert::AsRef<std::path::Path>`) and parses the resulting
+    /// reader into a [`MapGrid`] using [`MapGrid::from_json_reader`].
+    ///
+    /// ### Errors
+    /// Function errors if [`serde_json::from_reader`] fails.
+    ///
+    /// ##### See also: [`serde_json::from_reader`]
+    pub fn from_json_file<P: AsRef<std::path::Path>>(path: P) -> serde_json::Result<Self> {
+        match File::open(path) {
+            Ok(file) => Self::from_json_reader(file),
+            Err(e) => Err(serde_json::Error::io(e)),
+        }
+    }
+
+    /// Serialize this [`MapGrid`] into a [`Json Value`](`serde_json::Value`).
+    ///
+    /// ### Errors
+    /// Function errors if [`serde_json::to_value`] fails.
+    ///
+    /// ##### See also: [`serde_json::to_value`]
+    pub fn to_json(&self) -> Result<serde_json::Value, serde_json::Error> {
+        serde_json::to_value(self)
+    }
+
+    /// Serialize this [`MapGrid`] into a [`Byte Array`](`std::collections::Vec<u8>`).
+    ///
+    /// ### Errors
+    /// Function errors if [`serde_json::to_vec`] fails.
+    ///
+    /// ##### See also: [`serde_json::to_vec`]
+    pub fn to_json_bytes(&self) -> Result<Vec<u8>, serde_json::Error> {
+        serde_json::to_vec(self)
+    }
+
+    /// Serialize this [`MapGrid`] into a [`String`] containing the json. The [`pretty`]
+    /// argument determines whether it is converted with pretty indentation for display.
+    ///
+    /// ### Errors
+    /// Function errors if [`serde_json::to_string`] or [`serde_json::to_string_pretty`] fails.
+    ///
+    /// ##### See also: [`serde_json::to_string`] [`serde_json::to_string_pretty`]
+    pub fn to_json_string(&self, pretty: bool) -> Result<String, serde_json::Error> {
+        if pretty {
+            serde_json::to_string_pretty(self)
+        } else {
+            serde_json::to_string(self)
+        }
+    }
+
+    /// Deserialize the given `byte_ref` (which must implement [`std::convert::AsRef<[u8]>`] + [`?Sized`])
+    /// containing msgpack data into a new [`MapGrid`]. This is performed in zero-copy manner whenever it
+    /// is possible, borrowing the data from the reader itself. For example, strings and byte-arrays won’t
+    /// be not copied.
+    ///
+    /// ### Errors
+    /// Function errors if [`rmp_serde::from_read_ref`] fails.
+    ///  
+    /// ##### See also: [`rmp_serde::from_read_ref`].
+    pub fn from_msgpack_ref<R: AsRef<[u8]> + ?Sized>(
+        byte_ref: &R,
+    ) -> Result<Self, rmp_serde::decode::Error> {
+        rmp_serde::from_read_ref(byte_ref)
+    }
+
+    /// Deserialize the given [`reader`](std::io::Read) containing msgpack data into a [`MapGrid`].
+    ///
+    /// ### Errors
+    /// Function errors if [`rmp_serde::from_read`] fails.
+    ///
+    /// ##### See also: [`rmp_serde::from_read`].
+    pub fn from_msgpack_reader<R: Read>(reader: R) -> Result<Self, rmp_serde::decode::Error> {
+        rmp_serde::from_read(reader)
+    }
+
+    /// Serialize this [`MapGrid`] into a [`Vec<u8>`] of msgpack data.
+    ///
+    /// ### Errors
+    /// Function errors if [`rmp_serde::to_vec`] fails.
+    ///
+    /// ##### See also: [`rmp_serde::to_vec`]
+    pub fn to_msgpack(&self) -> Result<Vec<u8>, rmp_serde::encode::Error> {
+        rmp_serde::to_vec(self)
+    }
+
+    /// Exports this [`MapGrid`] as a minimal [Tiled](https://www.mapeditor.org/) JSON map containing a
+    /// single tile layer. On cells use `wall_tile` and off cells use `floor_tile`. Invalid cells have no
+    /// Tiled equivalent, so they are exported as `floor_tile` and a warning is logged.
+    #[must_use]
+    pub fn to_tiled_json(&self, wall_tile: u32, floor_tile: u32) -> String {
+        trace!("MapGrid::to_tiled_json({}, {})", wall_tile, floor_tile);
+        let invalid = self.invalid_cells_count();
+        if invalid > 0 {
+            warn!(
+                "MapGrid::to_tiled_json - exporting {} invalid cells as floor tiles",
+                invalid
+            );
+        }
+
+        let data: Vec<u32> = self
+            .iter()
+            .map(|cell| if cell.is_on() { wall_tile } else { floor_tile })
+            .collect();
+
+        serde_json::json!({
+            "type": "map",
+            "version": "1.8",
+            "orientation": "orthogonal",
+            "renderorder": "right-down",
+            "infinite": false,
+            "width": self.width,
+            "height": self.height,
+            "tilewidth": 16,
+            "tileheight": 16,
+            "nextlayerid": 2,
+            "nextobjectid": 1,
+            "tilesets": [],
+            "layers": [{
+                "id": 1,
+                "name": self.name.clone().unwrap_or_else(|| "Tile Layer 1".to_string()),
+                "type": "tilelayer",
+                "encoding": "csv",
+                "width": self.width,
+                "height": self.height,
+                "x": 0,
+                "y": 0,
+                "opacity": 1,
+                "visible": true,
+                "data": data,
+            }],
+        })
+        .to_string()
+    }
+
+    /// Imports the first tile layer of a [Tiled](https://www.mapeditor.org/) JSON map, turning on every
+    /// cell whose tile id is in `wall_tiles` and turning off all others. Maps with more than one tile
+    /// layer log a warning and only the first is used.
+    ///
+    /// ### Errors
+    /// - Function errors if `input` is not valid json or does not contain a tile layer.
+    /// - Function errors if the tile layer is not CSV encoded (i.e. its `data` is not an array of tile ids).
+    /// - Function errors if the layer is smaller than 3x3 or its `data` length does not match its size.
+    pub fn from_tiled_json(input: &str, wall_tiles: &[u32]) -> Result<Self, String> {
+        trace!("MapGrid::from_tiled_json(..., {:?})", wall_tiles);
+        let value: serde_json::Value = serde_json::from_str(input)
+            .map_err(|e| format!("Unable to parse Tiled json: {}", e))?;
+        let layers: Vec<&serde_json::Value> = value["layers"]
+            .as_array()
+            .ok_or_else(|| "Tiled map does not contain a layers array".to_string())?
+            .iter()
+            .filter(|layer| layer["type"] == "tilelayer")
+            .collect();
+        if layers.len() > 1 {
+            warn!(
+                "MapGrid::from_tiled_json - map contains {} tile layers, only the first will be used",
+                layers.len()
+            );
+        }
+        let layer = layers
+            .first()
+            .ok_or_else(|| "Tiled map does not contain any tile layers".to_string())?;
+
+        let dimension = |key: &str| {
+            layer[key]
+                .as_u64()
+                .and_then(|n| usize::try_from(n).ok())
+                .ok_or_else(|| format!("Tile layer is missing a valid {}", key))
+        };
+        let (width, height) = (dimension("width")?, dimension("height")?);
+        if width < 3 || height < 3 {
+            return Err(format!(
+                "Tile layer must be at least 3x3, found {}x{}",
+                width, height
+            ));
+        }
+
+        let data = layer["data"].as_array().ok_or_else(|| {
+            "Tile layer data must be CSV encoded (an array of tile ids)".to_string()
+        })?;
+        if width.checked_mul(height) != Some(data.len()) {
+            return Err(format!(
+                "Tile layer data contains {} tiles but its size is {}x{}",
+                data.len(),
+                width,
+                height
+            ));
+        }
+
+        let mut grid = Self::empty((width, height));
+        for (i, tile) in data.iter().enumerate() {
+            let id = tile
+                .as_u64()
+                .and_then(|n| u32::try_from(n).ok())
+                .ok_or_else(|| format!("Invalid tile id {} at index {}", tile, i))?;
+            grid.cells[i / width][i % width] = Cell::new(wall_tiles.contains(&id).into());
+        }
+
+        Ok(grid)
+    }
+
+    /// Converts this grid into a plain matrix of booleans, indexed `[y][x]`, where `true` is on.
+    /// Invalid cells are converted to `false`, so they do not survive a round trip.
+    ///
+    /// ##### See also: [`MapGrid::from_bool_matrix`]
+    #[must_use]
+    pub fn to_bool_matrix(&self) -> Vec<Vec<bool>> {
+        trace!("MapGrid::to_bool_matrix()");
+        self.cells
+            .iter()
+            .map(|row| row.iter().map(|cell| cell.is_on()).collect())
+            .collect()
+    }
+
+    /// Creates a grid from a matrix of booleans, indexed `[y][x]`, where `true` is on.
+    ///
+    /// ### Errors
+    /// - Function errors if the matrix is smaller than 3x3.
+    /// - Function errors if any row differs in length from the first.
+    ///
+    /// ##### See also: [`MapGrid::to_bool_matrix`]
+    pub fn from_bool_matrix(m: &[Vec<bool>]) -> Result<Self, String> {
+        trace!("MapGrid::from_bool_matrix({:?})", m);
+        let (width, height) = (m.first().map_or(0, Vec::len), m.len());
+        if width < 3 || height < 3 {
+            return Err(format!(
+                "Matrix must be at least 3x3, found {}x{}",
+                width, height
+            ));
+        }
+
+        if let Some(y) = m.iter().position(|row| row.len() != width) {
+            return Err(format!(
+                "Matrix row {} has {} columns but the first row has {}",
+                y,
+                m[y].len(),
+                width
+            ));
+        }
+
+        let mut grid = Self::empty((width, height));
+        for (y, row) in m.iter().enumerate() {
+            for (x, &on) in row.iter().enumerate() {
+                grid.cells[y][x] = Cell::new(on.into());
+            }
+        }
+
+        Ok(grid)
+    }
+
+    /// Encodes this grid into a compact, single line, URL safe base64 string that can be decoded with
+    /// [`MapGrid::from_base64`]. The encoded data is a header holding the width, height and name length
+    /// (each as a little endian `u32`), followed by the name and then one bit per cell in row-major
+    /// order, with on cells set. Invalid cells are stored as off, and an empty name is decoded as no name.
+    ///
+    /// ### Panics
+    /// Function panics if the width, height, or name length do not fit in a `u32`.
+    #[must_use]
+    pub fn to_base64(&self) -> String {
+        trace!("MapGrid::to_base64()");
+        let name = self.name_ref().as_deref().unwrap_or_default().as_bytes();
+        let mut bytes = Vec::with_capacity(12 + name.len() + (self.cell_count() + 7) / 8);
+        for value in [self.width, self.height, name.len()] {
+            let value = u32::try_from(value).expect("Grid is too large to encode");
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        bytes.extend_from_slice(name);
+
+        let mut bits = Vec::with_capacity((self.cell_count() + 7) / 8);
+        for (i, cell) in self.iter().enumerate() {
+            if i % 8 == 0 {
+                bits.push(0u8);
+            }
+            if cell.is_on() {
+                bits[i / 8] |= 0x80 >> (i % 8);
+            }
+        }
+        bytes.extend(bits);
+
+        base64::encode_config(bytes, base64::URL_SAFE_NO_PAD)
+    }
+
+    /// Decodes a grid from a string created by [`MapGrid::to_base64`].
+    ///
+    /// ### Errors
+    /// Function errors if `input` is not valid base64, if the data is truncated or has trailing bytes,
+    /// if the name is not valid UTF-8, or if the encoded size is smaller than 3x3.
+    pub fn from_base64<S: AsRef<str>>(input: S) -> Result<Self, String> {
+        trace!("MapGrid::from_base64({})", input.as_ref());
+        let bytes = base64::decode_config(input.as_ref().trim(), base64::URL_SAFE_NO_PAD)
+            .map_err(|e| format!("Invalid base64 - {}", e))?;
+        if bytes.len() < 12 {
+            return Err(format!(
+                "Encoded grid is too short to hold its header ({} bytes)",
+                bytes.len()
+            ));
+        }
+
+        let mut header = bytes[..12]
+            .chunks_exact(4)
+            .map(|chunk| u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]) as usize);
+        let (width, height, name_len) = (
+            header.next().unwrap_or_default(),
+            header.next().unwrap_or_default(),
+            header.next().unwrap_or_default(),
+        );
+        if width < 3 || height < 3 {
+            return Err(format!(
+                "Grid must be at least 3x3, but {}x{} was encoded",
+                width, height
+            ));
+        }
+
+        let bits_start = 12 + name_len;
+        let expected = bits_start + (width * height + 7) / 8;
+        if bytes.len() != expected {
+            return Err(format!(
+                "Encoded {}x{} grid should be {} bytes but is {}",
+                width,
+                height,
+                expected,
+                bytes.len()
+            ));
+        }
+
+        let name = std::str::from_utf8(&bytes[12..bits_start])
+            .map_err(|e| format!("Encoded name is not valid UTF-8 - {}", e))?;
+        let mut grid = Self::empty((width, height));
+        if !name.is_empty() {
+            grid.set_name(name);
+        }
+        let bits = &bytes[bits_start..];
+        for (i, cell) in grid.iter_mut().enumerate() {
+            cell.set_state((bits[i / 8] & (0x80 >> (i % 8)) != 0).into());
+        }
+
+        Ok(grid)
+    }
+}
+
+impl From<PFGrid> for MapGrid {
+    fn from(pfg: PFGrid) -> Self {
+        let mut grid = MapGrid::empty((pfg.width, pfg.height));
+        for (x, y) in pfg.iter() {
+            grid.set_cell_state(x, y, true);
+        }
+
+        grid
+    }
+}
+
+impl From<&PFGrid> for MapGrid {
+    fn from(pfg: &PFGrid) -> Self {
+        let mut grid = MapGrid::empty((pfg.width, pfg.height));
+        for (x, y) in pfg.iter() {
+            grid.set_cell_state(x, y, true);
+        }
+
+        grid
+    }
+}
+
+impl PartialEq for MapGrid {
+    /// Checks whether `other` is equal to this [`MapGrid`].
+    ///
+    /// This does check ***EACH CELL*** in the [`MapGrid`], but it has early outs
+    /// if the dimensions or name of the grids are not equal.
+    fn eq(&self, other: &MapGrid) -> bool {
+        trace!("MapGrid::eq()");
+        if self.width != other.width || self.height != other.height {
+            return false;
+        }
+
+        if self.name != other.name {
+            return false;
+        }
+
+        for (row, other_row) in self.cells.iter().zip(other.cells.iter()) {
+            for (cell, other_cell) in row.iter().zip(other_row.iter()) {
+                if cell != other_cell {
+                    return false;
+                }
+            }
+        }
+
+        true
+    }
+}
+
+impl std::fmt::Debug for MapGrid {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "MapGrid {{ name: {:?}, width: {}, height: {} }}",
+            self.name, self.width, self.height
+        )?;
+        writeln!(f)?;
+        for line in self.to_strings_bordered() {
+            writeln!(f, "{}", line)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for MapGrid {
+    /// Displays a fancy [`MapGrid`] over multiple lines.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let title_line = if self.has_name() {
+            format!(
+                "{} ({}x{})",
+                self.name.as_ref().unwrap(),
+                self.width,
+                self.height
+            )
+        } else {
+            format!("MapGrid ({}x{})", self.width, self.height)
+        };
+        write!(
+            f,
+            "|  {}\n|{}\n",
+            title_line,
+            "-".repeat(title_line.len() + 4)
+        )?;
+        for line in &self.to_strings() {
+            writeln!(f, "|{}", line)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::cognitive_complexity, clippy::too_many_lines, unused)]
+mod tests {
+    use super::*;
+
+    use assert_float_eq::{
+        afe_abs, afe_absolute_error_msg, afe_is_absolute_eq, afe_is_relative_eq,
+        afe_relative_error_msg, assert_float_absolute_eq, assert_float_relative_eq,
+    };
+
+    use crate::assert_unordered_match;
+    use crate::data::{pos, GridPositions, PremadeGridStrings, PremadeGrids};
+    use crate::util::testing::crate_before_test;
+
+    fn init() {
+        let _ = env_logger::builder().is_test(true).try_init();
+        crate::util::random::init_rng_seeded(0);
+    }
+
+    #[test]
+    fn construction_works() {
+        crate_before_test();
+
+        let mut grid = MapGrid::new(size(10, 10));
+        assert_eq!(grid.width, 10);
+        assert_eq!(grid.height, 10);
+        assert_eq!(grid.rows(), 10);
+        assert_eq!(grid.cols(), 10);
+        assert_eq!(grid.cells.len(), 10);
+        assert_eq!(grid.cells[0].len(), 10);
+        assert_eq!(grid.invalid_cells_count(), 100);
+        assert_eq!(grid.on_cells_count(), 0);
+        assert_eq!(grid.off_cells_count(), 0);
+
+        grid.set_cell(0, 0, Cell::on());
+        grid.set_cell(1, 0, Cell::off());
+        assert_eq!(grid.invalid_cells_count(), 98);
+        assert_eq!(grid.on_cells_count(), 1);
+        assert_eq!(grid.off_cells_count(), 1);
+
+        let mut grid = MapGrid::empty((5, 5));
+        assert_eq!(grid.width, 5);
+        assert_eq!(grid.height, 5);
+        assert_eq!(grid.rows(), 5);
+        assert_eq!(grid.cols(), 5);
+        assert_eq!(grid.cells.len(), 5);
+        assert_eq!(grid.cells[0].len(), 5);
+        assert_eq!(grid.invalid_cells_count(), 0);
+        assert_eq!(grid.on_cells_count(), 0);
+        assert_eq!(grid.off_cells_count(), 25);
+
+        grid.set_cell_state(0, 0, true);
+        assert_eq!(grid.invalid_cells_count(), 0);
+        assert_eq!(grid.on_cells_count(), 1);
+        assert_eq!(grid.off_cells_count(), 24);
+
+        grid.set_cell_invalid(0, 1);
+        assert_eq!(grid.invalid_cells_count(), 1);
+        assert_eq!(grid.on_cells_count(), 1);
+        assert_eq!(grid.off_cells_count(), 23);
+    }
+
+    #[test]
+    fn random_fill_works() {
+        init();
+
+        let grid = MapGrid::random_fill_percent((10, 10), 0.5);
+        assert_eq!(grid.on_cells_count(), 50);
+        assert_eq!(grid.off_cells_count(), 50);
+
+        let grid = MapGrid::random_fill_number((10, 10), 50);
+        assert_eq!(grid.on_cells_count(), 50);
+        assert_eq!(grid.off_cells_count(), 50);
+    }
+
+    #[test]
+    fn set_all_cells() {
+        init();
+
+        let mut grid = MapGrid::new(size(5, 5));
+        assert_eq!(grid.invalid_cells_count(), 25);
+        assert_eq!(grid.on_cells_count(), 0);
+        assert_eq!(grid.off_cells_count(), 0);
+
+        grid.set_all_cells(true);
+        assert_eq!(grid.invalid_cells_count(), 0);
+        assert_eq!(grid.on_cells_count(), 25);
+        assert_eq!(grid.off_cells_count(), 0);
+
+        grid.set_all_cells(false);
+        assert_eq!(grid.invalid_cells_count(), 0);
+        assert_eq!(grid.on_cells_count(), 0);
+        assert_eq!(grid.off_cells_count(), 25);
+    }
+
+    #[test]
+    fn set_cells_bulk() {
+        init();
+
+        let mut grid = MapGrid::empty(size(5, 5));
+        let applied = grid.set_cells((0..7).map(|i| (pos((i, i)), Cell::on())));
+        assert_eq!(applied, 5);
+        assert_eq!(grid.on_cells_count(), 5);
+        for i in 0..5 {
+            assert!(grid.cell((i, i)).unwrap().is_on());
+        }
+    }
+
+    #[test]
+    fn state_iterators() {
+        init();
+
+        let grid = MapGrid::random((6, 4));
+        let expected: Vec<TriState> = grid.iter().map(|c| c.state()).collect();
+        assert_eq!(grid.iter_states().collect::<Vec<_>>(), expected);
+
+        for ((pos, state), ((x, y), cell)) in grid.iter_pos_states().zip(grid.iter_pos()) {
+            assert_eq!(pos, (x, y).into());
+            assert_eq!(state, cell.state());
+        }
+    }
+
+    #[test]
+    fn replace_state() {
+        init();
+
+        let mut grid = MapGrid::new(size(5, 4));
+        grid.set_cell_state(1, 1, true);
+        assert_eq!(grid.replace_state(TriState::Invalid, TriState::False), 19);
+        assert_eq!(grid.invalid_cells_count(), 0);
+        assert_eq!(grid.off_cells_count(), 19);
+        assert_eq!(grid.on_cells_count(), 1);
+        assert_eq!(grid.replace_state(TriState::Invalid, TriState::True), 0);
+    }
+
+    #[test]
+    fn quadrant_symmetric() {
+        init();
+
+        let fill = |quad: &mut MapGrid, rng: &mut fastrand::Rng| {
+            for cell in quad.iter_mut() {
+                *cell = if rng.f64() < 0.45 {
+                    Cell::on()
+                } else {
+                    Cell::off()
+                };
+            }
+        };
+
+        for (width, height) in [(20, 12), (15, 9), (3, 3)] {
+            let grid = MapGrid::generate_quadrant_symmetric((width, height), 7, fill);
+            assert_eq!(grid.size(), (width, height).into());
+            for ((x, y), cell) in grid.iter_pos() {
+                let rotated = grid.cell((width - 1 - x, height - 1 - y)).unwrap();
+                assert_eq!(cell, rotated);
+                assert_eq!(cell, grid.cell((width - 1 - x, y)).unwrap());
+            }
+        }
+
+        crate::util::random::init_rng_seeded(99);
+        let expected = fastrand::u64(..);
+        crate::util::random::init_rng_seeded(99);
+        let first = MapGrid::generate_quadrant_symmetric((16, 16), 3, fill);
+        assert_eq!(fastrand::u64(..), expected);
+        let second = MapGrid::generate_quadrant_symmetric((16, 16), 3, fill);
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn morphology() {
+        init();
+
+        // A block of walls with a single open hole in the middle
+        let grid = MapGrid::parse_string(
+            ".........\n.........\n..#####..\n..##.##..\n..#####..\n.........\n.........",
+            '#',
+            '.',
+        )
+        .expect("Unable to parse grid!");
+        let dilated = grid.dilate(true);
+        assert_eq!(dilated.on_cells_count(), 35 - 4);
+        assert!(dilated.cell((4, 3)).unwrap().is_on());
+
+        let closed = grid.closing(true);
+        assert_eq!(closed.on_cells_count(), 15);
+        assert!(closed.cell((4, 3)).unwrap().is_on());
+        assert!(closed.cell((1, 3)).unwrap().is_off());
+
+        let mut specks = MapGrid::create_copy(&closed);
+        specks.set_cell_state(7, 6, true);
+        specks.set_cell_state(0, 0, true);
+        let opened = specks.opening(true);
+        assert!(opened.cell((7, 6)).unwrap().is_off());
+        assert!(opened.cell((0, 0)).unwrap().is_off());
+        assert_eq!(opened, closed.opening(true));
+        assert_eq!(closed.erode(true).on_cells_count(), 3);
+    }
+
+    #[test]
+    fn stamp() {
+        init();
+
+        // A pillar with a transparent hole in the middle
+        let mut prefab = MapGrid::empty((3, 3));
+        prefab.set_all_cells(true);
+        prefab.set_cell_invalid(1, 1);
+
+        let mut grid = MapGrid::empty((8, 6));
+        grid.set_cell_state(3, 2, true);
+        assert_eq!(grid.stamp(&prefab, pos((2, 1)), true), 8);
+        assert_eq!(grid.on_cells_count(), 9);
+        assert!(grid.cell((3, 2)).unwrap().is_on());
+        assert_eq!(grid.invalid_cells_count(), 0);
+
+        let mut grid = MapGrid::empty((8, 6));
+        grid.set_cell_state(3, 2, true);
+        assert_eq!(grid.stamp(&prefab, pos((2, 1)), false), 9);
+        assert!(grid.cell((3, 2)).unwrap().is_invalid());
+
+        // Stamping off the edge clips instead of resizing (the hole lands on (7, 5))
+        assert_eq!(grid.stamp(&prefab, pos((6, 4)), true), 3);
+        assert_eq!(grid.size(), size(8, 6));
+        assert_eq!(grid.stamp(&prefab, pos((8, 0)), true), 0);
+        assert_eq!(
+            grid.stamp(&prefab, pos((usize::MAX, usize::MAX - 1)), false),
+            0
+        );
+    }
+
+    #[test]
+    fn set_square() {
+        init();
+
+        let mut grid = MapGrid::random_fill_percent((8, 8), 0.5);
+        let invalid = grid.invalid_cells_count();
+        assert_eq!(grid.set_square(&square(&(2, 2), 3, 3), Cell::invalid()), 9);
+        assert_eq!(grid.invalid_cells_count(), invalid + 9);
+        assert_eq!(grid.set_square(&square(&(2, 2), 3, 3), Cell::invalid()), 0);
+
+        // Squares hanging off of the grid are clamped
+        let mut grid = MapGrid::empty((5, 5));
+        assert_eq!(grid.set_square(&square(&(3, 3), 4, 4), Cell::on()), 4);
+        assert_eq!(grid.on_cells_count(), 4);
+        assert_eq!(grid.set_square(&square(&(0, 0), 5, 5), Cell::off()), 4);
+        assert_eq!(grid.set_square(&square(&(7, 7), 2, 2), Cell::on()), 0);
+    }
+
+    #[test]
+    fn outline() {
+        init();
+
+        let mut room = MapGrid::empty((9, 7));
+        room.set_outer_cells(true);
+        let outline = room.outline();
+        let mut ring = MapGrid::empty((9, 7));
+        ring.invert_region(&square(&(1, 1), 7, 5));
+        ring.invert_region(&square(&(2, 2), 5, 3));
+        assert_eq!(outline, ring);
+        assert_eq!(outline.on_cells_count(), 2 * 7 + 2 * 3);
+
+        // A pillar in the middle of the room gets its own ring
+        room.set_cell_state(4, 3, true);
+        let outline = room.outline();
+        assert_eq!(outline.on_cells_count(), 2 * 7 + 2 * 3 + 8);
+        assert!(outline.cell((4, 3)).unwrap().is_off());
+        assert_eq!(MapGrid::empty((5, 5)).outline().on_cells_count(), 0);
+    }
+
+    #[test]
+    fn skeletonize() {
+        init();
+
+        let mut grid = MapGrid::empty((16, 9));
+        grid.set_outer_cells(true);
+        // The 14x7 open interior thins to its horizontal center line
+        let skeleton = grid.skeletonize();
+        assert_eq!(skeleton.label_regions(false).1, 1);
+        assert!(!skeleton.has_cycle(false));
+        let open = skeleton.iter_off().collect::<Vec<_>>();
+        assert!(open.len() >= 14 - 7);
+        assert!(open.iter().all(|p| p.y == 4));
+        let (min_x, max_x) = (open[0].x, open[open.len() - 1].x);
+        assert_eq!(max_x - min_x + 1, open.len());
+        assert!(absdiff(min_x + max_x, 15) <= 1);
+
+        let mut cross = MapGrid::empty((15, 15));
+        for ((x, y), cell) in cross.iter_pos_mut() {
+            let in_arm = |a: usize, b: usize| (1..14).contains(&a) && (5..10).contains(&b);
+            cell.set_state((!in_arm(x, y) && !in_arm(y, x)).into());
+        }
+        let skeleton = cross.skeletonize();
+        assert_eq!(skeleton.label_regions(false).1, 1);
+        assert!(skeleton.off_cells_count() < cross.off_cells_count() / 3);
+        assert!(skeleton.cell((7, 3)).unwrap().is_off());
+        assert!(skeleton.cell((3, 7)).unwrap().is_off());
+        assert!(skeleton.iter_off().all(|p| cross.cell(p).unwrap().is_off()));
+    }
+
+    #[test]
+    fn kaleidoscope() {
+        init();
+
+        for (width, height) in [(20, 12), (15, 9), (3, 3)] {
+            let source = MapGrid::random_fill_percent((width, height), 0.45);
+            let grid = source.kaleidoscope();
+            assert_eq!(grid.size(), source.size());
+            for ((x, y), cell) in grid.iter_pos() {
+                assert_eq!(cell, grid.cell((width - 1 - x, y)).unwrap());
+                assert_eq!(cell, grid.cell((x, height - 1 - y)).unwrap());
+                assert_eq!(cell, grid.cell((width - 1 - x, height - 1 - y)).unwrap());
+            }
+
+            for y in 0..(height + 1) / 2 {
+                for x in 0..(width + 1) / 2 {
+                    assert_eq!(grid.cell((x, y)), source.cell((x, y)));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn reverse_in_place() {
+        init();
+
+        let mut grid = MapGrid::empty((5, 5));
+        assert_eq!(grid.invalid_cells_count(), 0);
+        assert_eq!(grid.on_cells_count(), 0);
+        assert_eq!(grid.off_cells_count(), 25);
+
+        grid.reverse_in_place();
+        assert_eq!(grid.invalid_cells_count(), 0);
+        assert_eq!(grid.on_cells_count(), 25);
+        assert_eq!(grid.off_cells_count(), 0);
+    }
+
+    #[test]
+    fn set_outer_works() {
+        init();
+
+        let mut grid = MapGrid::empty((3, 3));
+        grid.set_outer_cells(true);
+        assert_eq!(grid.on_cells_count(), 8);
+        assert_eq!(grid.off_cells_count(), 1);
+        assert_eq!(
+            grid.to_strings().join("\n"),
+            "###\n#.#\n###",
+            "Grid did not match expected output"
+        );
+
+        let mut grid = MapGrid::empty((5, 5));
+        grid.set_outer_cells(true);
+        assert_eq!(grid.on_cells_count(), 16);
+        assert_eq!(grid.off_cells_count(), 9);
+        assert_eq!(
+            grid.to_strings().join("\n"),
+            "#####\n#...#\n#...#\n#...#\n#####"
+        );
+
+        grid.reverse_in_place();
+        assert_eq!(
+            grid.to_strings().join("\n"),
+            ".....\n.###.\n.###.\n.###.\n....."
+        );
+    }
+
+    #[test]
+    fn set_border() {
+        init();
+
+        let mut grid = MapGrid::empty((7, 7));
+        grid.set_border(2, true);
+        assert_eq!(grid.off_cells_count(), 9);
+        assert_eq!(
+            grid.to_strings().join("\n"),
+            "#######\n#######\n##...##\n##...##\n##...##\n#######\n#######"
+        );
+
+        let mut outer = MapGrid::empty((5, 4));
+        outer.set_border(1, true);
+        let mut expected = MapGrid::empty((5, 4));
+        expected.set_outer_cells(true);
+        assert_eq!(outer, expected);
+
+        grid.set_border(4, false);
+        assert_eq!(grid.off_cells_count(), 49);
+    }
+
+    #[test]
+    fn ellipses() {
+        init();
+
+        let mut grid = MapGrid::empty((15, 15));
+        grid.fill_ellipse(pos((7, 7)), 4, 4, true);
+        for ((x, y), cell) in grid.iter_pos() {
+            let (dx, dy) = (absdiff(x, 7), absdiff(y, 7));
+            assert_eq!(cell.is_on(), dx * dx + dy * dy <= 16, "({}, {})", x, y);
+        }
+
+        let mut wide = MapGrid::empty((11, 5));
+        wide.fill_ellipse(pos((5, 2)), 5, 2, true);
+        assert!(wide.cell((0, 2)).unwrap().is_on());
+        assert!(wide.cell((10, 2)).unwrap().is_on());
+        assert!(wide.cell((5, 0)).unwrap().is_on());
+        assert!(wide.cell((0, 0)).unwrap().is_off());
+
+        let mut clipped = MapGrid::empty((5, 5));
+        clipped.fill_ellipse(pos((0, 0)), 3, 3, true);
+        assert!(clipped.cell((3, 0)).unwrap().is_on());
+
+        let mut outline = MapGrid::empty((15, 15));
+        outline.draw_ellipse(pos((7, 7)), 4, 4, true);
+        assert!(outline.cell((7, 3)).unwrap().is_on());
+        assert!(outline.cell((7, 7)).unwrap().is_off());
+        assert!(outline.on_cells_count() < grid.on_cells_count());
+        assert!(outline
+            .iter_pos()
+            .all(|(p, c)| c.is_off() || grid.cell(p).unwrap().is_on()));
+    }
+
+    #[test]
+    fn random_cells_work() {
+        init();
+
+        let mut grid = MapGrid::empty((10, 10));
+        for _ in 0..50 {
+            let (x, y) = grid.random_cell_pos().into();
+            assert!(x < 25);
+            assert!(y < 25);
+        }
+
+        for _ in 0..50 {
+            let _ = grid.random_cell();
+        }
+
+        for _ in 0..50 {
+            let _ = grid.random_cell_mut();
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn panics_invalid_height() {
+        init();
+        MapGrid::empty((100, 2));
+    }
+
+    #[test]
+    #[should_panic]
+    fn panics_invalid_width() {
+        init();
+        MapGrid::empty((2, 100));
+    }
+
+    #[test]
+    fn names_work() {
+        init();
+
+        let mut grid = MapGrid::empty((5, 5));
+        assert_eq!(grid.name, None);
+        assert!(!grid.has_name());
+
+        grid.set_name("test");
+        assert_eq!(grid.name, Some("test".to_string()));
+        assert!(grid.has_name());
+
+        let mut grid = MapGrid::empty_named("Test", (5, 5));
+        assert_eq!(grid.name, Some("Test".to_string()));
+        assert!(grid.has_name());
+
+        grid.clear_name();
+        assert_eq!(grid.name, None);
+        assert!(!grid.has_name());
+    }
+
+    #[test]
+    fn get_neighbors() {
+        init();
+        let grid1 = MapGrid::parse_string("###\n#-#\n###", '#', '-')
+            .expect("Failed to parse standard grid, something is very wrong.");
+        assert_eq!(
+            grid1.size(),
+            (3, 3).into(),
+            "Grid should have 3 rows & 3 columns."
+        );
+        assert_unordered_match!(
+            grid1.neighbor_positions_wrapping((1, 1)),
+            vec![
+                (0, 0),
+                (1, 0),
+                (2, 0),
+                (0, 1),
+                (2, 1),
+                (0, 2),
+                (1, 2),
+                (2, 2)
+            ]
+        );
+        assert_unordered_match!(
+            grid1.neighbor_positions_wrapping((1, 0)),
+            vec![
+                (0, 2),
+                (1, 2),
+                (2, 2),
+                (0, 0),
+                (2, 0),
+                (0, 1),
+                (1, 1),
+                (2, 1)
+            ]
+        );
+        assert_unordered_match!(
+            grid1.neighbor_positions_wrapping((0, 0)),
+            vec![
+                (2, 2),
+                (0, 2),
+                (1, 2),
+                (2, 0),
+                (1, 0),
+                (2, 1),
+                (0, 1),
+                (1, 1)
+            ]
+        );
+
+        let grid2 = MapGrid::parse_string("#-#-#\n-#-#-\n#-#-#", '#', '-')
+            .expect("Failed to parse standard grid, something is very wrong.");
+        assert_eq!(
+            grid2.size(),
+            (5, 3).into(),
+            "Grid should be 5 wide and 3 tall"
+        );
+        assert_unordered_match!(
+            grid2.neighbor_positions_wrapping((3, 1)),
+            vec![
+                (2, 0),
+                (3, 0),
+                (4, 0),
+                (2, 1),
+                (4, 1),
+                (2, 2),
+                (3, 2),
+                (4, 2)
+            ]
+        );
+        assert_unordered_match!(
+            grid2.neighbor_positions_wrapping((4, 2)),
+            vec![
+                (3, 1),
+                (4, 1),
+                (0, 1),
+                (3, 2),
+                (0, 2),
+                (3, 0),
+                (4, 0),
+                (0, 0)
+            ]
+        );
+
+        let grid3 =
+            MapGrid::parse_string("#-#-#\n-#-#-\n#-#-#", '#', '-').expect("Unable to parse grid3");
+        let neighbors = grid3.neighbor_positions((3, 1));
+        assert_eq!(neighbors.len(), 8);
+        assert_unordered_match!(
+            neighbors,
+            [
+                (2, 0),
+                (3, 0),
+                (4, 0),
+                (2, 1),
+                (4, 1),
+                (2, 2),
+                (3, 2),
+                (4, 2),
+            ]
+        );
+        let neighbors = grid3.neighbor_positions((0, 0));
+        assert_eq!(neighbors.len(), 3);
+        assert_unordered_match!(neighbors, [(0, 1), (1, 1), (1, 0)]);
+        let neighbors = grid3.neighbor_positions((1, 0));
+        assert_eq!(neighbors.len(), 5);
+        assert_unordered_match!(neighbors, [(0, 0), (2, 0), (0, 1), (1, 1), (2, 1)]);
+    }
+
+    #[test]
+    fn ring_positions() {
+        init();
+
+        let grid = MapGrid::empty((7, 7));
+        assert_eq!(grid.ring_positions(pos((3, 3)), 0), vec![pos((3, 3))]);
+        assert_eq!(grid.ring_positions(pos((3, 3)), 1).len(), 8);
+        assert_eq!(grid.ring_positions(pos((0, 0)), 1).len(), 3);
+        assert_eq!(grid.ring_positions(pos((3, 3)), 3).len(), 24);
+        assert_unordered_match!(
+            grid.ring_positions(pos((0, 0)), 1),
+            vec![pos((1, 0)), pos((0, 1)), pos((1, 1))]
+        );
+        assert!(grid.ring_positions(pos((3, 3)), 4).is_empty());
+    }
+
+    #[test]
+    fn nearest_open() {
+        init();
+
+        let grid = MapGrid::parse_string(
+            "#######\n#######\n###.###\n#######\n#.....#\n#######",
+            '#',
+            '.',
+        )
+        .expect("Unable to parse grid!");
+        assert_eq!(grid.nearest_open(pos((3, 1))), Some(pos((3, 2))));
+        assert_eq!(grid.nearest_open(pos((3, 2))), Some(pos((3, 2))));
+        assert_eq!(grid.nearest_open(pos((5, 5))), Some(pos((5, 4))));
+        assert_eq!(grid.nearest_open(pos((20, 4))), Some(pos((5, 4))));
+
+        let mut solid = MapGrid::empty((4, 4));
+        solid.set_all_cells(true);
+        assert_eq!(solid.nearest_open(pos((1, 1))), None);
+    }
+
+    #[test]
+    fn carve_to_nearest_open() {
+        init();
+
+        let mut grid = MapGrid::parse_string(
+            "#########\n#...#####\n#...#####\n#...###.#\n#########",
+            '#',
+            '.',
+        )
+        .expect("Unable to parse grid!");
+        assert_eq!(grid.label_regions(false).1, 2);
+
+        let carved = grid
+            .carve_to_nearest_open(pos((7, 3)))
+            .expect("Isolated cell should be connected");
+        assert_eq!(carved, vec![pos((6, 3)), pos((5, 3)), pos((4, 3))]);
+        assert_eq!(grid.label_regions(false).1, 1);
+        assert_eq!(grid.carve_to_nearest_open(pos((7, 3))), None);
+        assert_eq!(grid.carve_to_nearest_open(pos((20, 3))), None);
+
+        let mut grid = MapGrid::parse_string(
+            "#########\n#..######\n#..######\n#########\n#########\n######.##\n#########",
+            '#',
+            '.',
+        )
+        .expect("Unable to parse grid!");
+        assert_eq!(grid.label_regions(false).1, 2);
+        let carved = grid
+            .carve_to_nearest_open(pos((6, 5)))
+            .expect("Isolated cell should be connected");
+        assert!(carved.iter().all(|p| grid.cell(*p).unwrap().is_off()));
+        assert_eq!(grid.label_regions(false).1, 1);
+    }
+
+    #[test]
+    fn neighbor_count_works() {
+        init();
+        let grid1 = MapGrid::parse_string("###\n#-#\n###", '#', '-')
+            .expect("Failed to parse standard grid, something is very wrong.");
+        assert_eq!(grid1.rows(), 3, "Grid 1 should have 3 rows.");
+        assert_eq!(grid1.cols(), 3, "Grid 1 should have 3 cols.");
+
+        assert_eq!(
+            grid1.active_neighbor_count((1, 1), true),
+            8,
+            "Wrong neighbor count for grid 1 cell (1,1)"
+        );
+        assert_eq!(
+            grid1.active_neighbor_count((1, 0), true),
+            7,
+            "Wrong neighbor count for grid 1 cell (1,0)"
+        );
+        assert_eq!(
+            grid1.active_neighbor_count((0, 0), true),
+            7,
+            "Wrong neighbor count for grid 1 cell (0,0)"
+        );
+
+        let grid2 = MapGrid::parse_string("0000\n0110\n0000", '1', '0')
+            .expect("Failed to parse standard grid, something is very wrong.");
+        assert_eq!(grid2.rows(), 3, "Grid 2 should have 3 rows.");
+        assert_eq!(grid2.cols(), 4, "Grid 2 should have 4 cols.");
+        assert_eq!(
+            grid2.active_neighbor_count((1, 1), true),
+            1,
+            "Wrong neighbor count for grid 2 cell (1,1)"
+        );
+        assert_eq!(
+            grid2.active_neighbor_count((2, 1), true),
+            1,
+            "Wrong neighbor count for grid 2 cell (2,1)"
+        );
+        assert_eq!(
+            grid2.active_neighbor_count((1, 0), true),
+            2,
+            "Wrong neighbor count for grid 1 cell (2,0)"
+        );
+        assert_eq!(
+            grid2.active_neighbor_count((2, 0), true),
+            2,
+            "Wrong neighbor count for grid 1 cell (1,0)"
+        );
+    }
+
+    #[test]
+    fn subgrids() {
+        init();
+
+        // #########
+        // #.......#
+        // #.#####.#
+        // #.#...#.#
+        // #.#.#.#.#
+        // #.#...#.#
+        // #.#####.#
+        // #.......#
+        // #########
+        let grid = MapGrid::parse_string("#########\n#.......#\n#.#####.#\n#.#...#.#\n#.#.#.#.#\n#.#...#.#\n#.#####.#\n#.......#\n#########", '#', '.').expect("Unable to parse grid.");
+        assert_eq!(grid.width, 9);
+        assert_eq!(grid.height, 9);
+        assert_eq!(grid.to_strings().join("\n"), "#########\n#.......#\n#.#####.#\n#.#...#.#\n#.#.#.#.#\n#.#...#.#\n#.#####.#\n#.......#\n#########");
+
+        let square = square(&(1, 1), 7, 7);
+        // let section = GridSection {
+        //     center: (4, 4),
+        //     u_extent: 3,
+        //     d_extent: 3,
+        //     l_extent: 3,
+        //     r_extent: 3,
+        // };
+
+        assert_eq!((square.width(), square.height()), (7, 7));
+        assert_eq!(square.size(), size(7, 7));
+        assert_eq!(square.center(), pos((4, 4)));
+        assert_eq!(square.x_range(), 1..8);
+
+        let sub = MapGrid::sub_grid(&grid, &square);
+        assert_eq!(
+            sub.to_strings().join("\n"),
+            ".......\n.#####.\n.#...#.\n.#.#.#.\n.#...#.\n.#####.\n......."
+        );
+        assert_eq!(sub.size(), (7, 7).into());
+    }
+
+    #[test]
+    fn clone_region() {
+        init();
+
+        let grid = MapGrid::parse_string("#########\n#.......#\n#.#####.#\n#.#...#.#\n#.#.#.#.#\n#.#...#.#\n#.#####.#\n#.......#\n#########", '#', '.').expect("Unable to parse grid.");
+        let (region, to_parent) = grid.clone_region(&square(&(2, 2), 5, 5));
+        assert_eq!(region.size(), (5, 5).into());
+        assert_eq!(to_parent(pos((0, 0))), pos((2, 2)));
+        assert_eq!(to_parent(pos((2, 2))), pos((4, 4)));
+        for ((x, y), cell) in region.iter_pos() {
+            assert_eq!(Some(cell), grid.cell(to_parent(pos((x, y)))));
+        }
+    }
+
+    #[test]
+    fn n_neighbors() {
+        init();
+
+        // #...#
+        // ..#..
+        // ..#..
+        // ..#..
+        // #...#
+        let grid = MapGrid::parse_string("#...#\n..#..\n..#..\n..#..\n#...#", '#', '.')
+            .expect("Unable to parse grid!");
+        assert_eq!(grid.on_cells_count(), 7);
+        assert_eq!(grid.active_neighbor_count((2, 2), true), 2);
+        assert_eq!(grid.active_neighbors_n(2, 2, 2), 6);
+    }
+
+    #[test]
+    fn label_regions() {
+        init();
+
+        let grid = MapGrid::parse_string(
+            "#########\n#...#...#\n#...#...#\n#...#...#\n#########",
+            '#',
+            '.',
+        )
+        .expect("Unable to parse grid!");
+        let (labels, count) = grid.label_regions(false);
+        assert_eq!(count, 2);
+        assert_eq!(labels[0][0], -1);
+        assert_eq!(labels[1][1], 0);
+        assert_eq!(labels[3][3], 0);
+        assert_eq!(labels[1][5], 1);
+        assert_eq!(labels[3][7], 1);
+
+        let mut distinct: Vec<i32> = labels
+            .iter()
+            .flatten()
+            .copied()
+            .filter(|&l| l >= 0)
+            .collect();
+        distinct.sort_unstable();
+        distinct.dedup();
+        assert_eq!(distinct, vec![0, 1]);
+
+        let (_, walls) = grid.label_regions(true);
+        assert_eq!(walls, 1);
+    }
+
+    #[test]
+    fn cycle_detection() {
+        init();
+
+        let perfect = MapGrid::parse_string(
+            "#######\n#.....#\n#####.#\n#.....#\n#.#####\n#.....#\n#######",
+            '#',
+            '.',
+        )
+        .expect("Unable to parse grid!");
+        assert!(!perfect.has_cycle(false));
+        assert_eq!(perfect.cycle_count(false), 0);
+
+        let braided = MapGrid::parse_string(
+            "#######\n#.....#\n#.###.#\n#.....#\n#.###.#\n#.....#\n#######",
+            '#',
+            '.',
+        )
+        .expect("Unable to parse grid!");
+        assert!(braided.has_cycle(false));
+        assert_eq!(braided.cycle_count(false), 2);
+    }
+
+    #[test]
+    fn count_all_matches() {
+        init();
+
+        let mut grid = MapGrid::random((9, 7));
+        grid.set_cell_invalid(0, 0);
+        grid.set_cell_invalid(4, 3);
+        let (on, off, invalid) = grid.count_all();
+        assert_eq!(on, grid.on_cells_count());
+        assert_eq!(off, grid.off_cells_count());
+        assert_eq!(invalid, grid.invalid_cells_count());
+        assert_eq!(on + off, grid.valid_cells_count());
+        assert_eq!(on + off + invalid, grid.cell_count());
+    }
+
+    #[test]
+    fn resampling() {
+        init();
+
+        let grid = MapGrid::parse_string(
+            "##########\n##########\n##......##\n##......##\n##..##..##\n##..##..##\n##......##\n##......##\n##########\n##########",
+            '#',
+            '.',
+        )
+        .expect("Unable to parse grid!");
+        let small = grid.downscale(2);
+        assert_eq!(small.size(), (5, 5).into());
+        assert_eq!(
+            small.to_strings().join("\n"),
+            "#####\n#...#\n#.#.#\n#...#\n#####"
+        );
+
+        let tiny = grid.downscale(8);
+        assert_eq!(tiny.size(), (3, 3).into());
+
+        let big = small.upscale(2);
+        assert_eq!(big, grid);
+    }
+
+    #[test]
+    fn rasterize_path() {
+        init();
+
+        let grid = MapGrid::empty((10, 10));
+        let band = grid.rasterize_path(&[pos((2, 5)), pos((7, 5))], 1);
+        assert_eq!(band.len(), 8 * 3);
+        assert!(band
+            .iter()
+            .all(|p| (4..=6).contains(&p.y) && (1..=8).contains(&p.x)));
+
+        let clamped = grid.rasterize_path(&[pos((0, 0)), pos((0, 9)), pos((9, 9))], 2);
+        assert!(clamped.iter().all(|p| p.x < 10 && p.y < 10));
+        assert!(clamped.contains(&pos((9, 9))));
+        assert!(grid.rasterize_path(&[], 3).is_empty());
+    }
+
+    #[test]
+    fn border_openings() {
+        init();
+
+        let maze = crate::data::PremadeGrids::maze3();
+        let (start, goal) = crate::data::PremadeGrids::maze3_start_end();
+        let openings = maze.border_openings();
+        assert!(openings.contains(&(start, Direction::South)));
+        assert!(openings.contains(&(goal, Direction::North)));
+        assert_eq!(openings.len(), 6);
+        for &(p, dir) in &openings {
+            assert!(matches!(dir, Direction::North | Direction::South));
+            assert!(maze.cell(p).unwrap().is_off());
+        }
+
+        let closed = MapGrid::parse_string("###\n#.#\n###", '#', '.').unwrap();
+        assert!(closed.border_openings().is_empty());
+    }
+
+    #[test]
+    fn carve_rooms_merged() {
+        init();
+
+        let mut grid = MapGrid::empty((10, 8));
+        grid.set_all_cells(true);
+        grid.carve_rooms_merged(&[Room::new((1, 1), 3, 3), Room::new((5, 1), 3, 5)]);
+        assert_eq!(
+            grid.to_strings().join("\n"),
+            "##########\n#.......##\n#.......##\n#.......##\n#####...##\n#####...##\n##########\n##########"
+        );
+        assert_eq!(grid.label_regions(false).1, 1);
+
+        let mut apart = MapGrid::empty((10, 8));
+        apart.set_all_cells(true);
+        apart.carve_rooms_merged(&[Room::new((1, 1), 3, 3), Room::new((6, 1), 3, 3)]);
+        assert_eq!(apart.label_regions(false).1, 2);
+    }
+
+    #[test]
+    fn diameter() {
+        init();
+
+        // RedditEasy (`res/mazes/Maze3.txt`) with the start and goal markers cleared.
+        let maze = MapGrid::parse_string(
+            "###############\n#.........#...#\n###.###.###.#.#\n#...#...#...#.#\n#.#####.#####.#\n#.....#...#...#\n#.###.#.###.###\n#.#...#.#...#.#\n#.#.###.#.###.#\n#.#.#.#.#.#...#\n###.#.#.#.#.#.#\n#...#...#.#.#.#\n#.#######.#.#.#\n#...........#.#\n###############",
+            '#',
+            '.',
+        )
+        .expect("Unable to parse RedditEasy");
+        assert!(!maze.has_cycle(false));
+        let (first, second, distance) = maze.diameter().expect("Maze should have open cells");
+        assert!(maze.cell(first).unwrap().is_off());
+        assert!(maze.cell(second).unwrap().is_off());
+        assert!(distance >= absdiff(first.x, second.x) + absdiff(first.y, second.y));
+        assert!(distance >= 24);
+        for end in [first, second] {
+            let open = maze
+                .neighbors_with_state(end, false, false)
+                .into_iter()
+                .filter(|&(x, y)| x == end.x || y == end.y)
+                .count();
+            assert_eq!(
+                open, 1,
+                "Diameter endpoints of a perfect maze are dead ends"
+            );
+        }
+
+        let mut walls = MapGrid::empty((3, 3));
+        walls.set_all_cells(true);
+        assert!(walls.diameter().is_none());
+    }
+
+    #[test]
+    fn component_stats() {
+        init();
+
+        let grid = MapGrid::parse_string(
+            "##########\n#...######\n#...######\n#...###..#\n#######..#\n##########",
+            '#',
+            '.',
+        )
+        .expect("Unable to parse grid!");
+        let stats = grid.component_stats(false);
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats[0].size, 9);
+        assert_eq!(stats[0].bounding_square, square(&(1, 1), 3, 3));
+        assert_eq!(stats[0].centroid, pos((2, 2)));
+        assert_eq!(stats[1].size, 4);
+        assert_eq!(stats[1].bounding_square, square(&(7, 3), 2, 2));
+        assert!(stats[1].bounding_square.contains(stats[1].centroid));
+        assert_eq!(grid.component_stats(true).len(), 1);
+    }
+
+    #[test]
+    fn cell_ratio() {
+        init();
+
+        let grid = MapGrid::parse_string("####\n####\n....\n....", '#', '.')
+            .expect("Failed to parse standard grid, something is very wrong.");
+
+        let (on, off, inv) = grid.cell_state_ratio();
+        assert_float_relative_eq!(on, 0.5);
+        assert_float_relative_eq!(off, 0.5);
+        assert_float_absolute_eq!(inv, 0.0);
+
+        let grid = MapGrid::new(size(4, 4));
+        let (on, off, inv) = grid.cell_state_ratio();
+        assert_float_absolute_eq!(on, 0.0);
+        assert_float_absolute_eq!(off, 0.0);
+        assert_float_relative_eq!(inv, 1.0);
+
+        let grid = MapGrid::empty((4, 4));
+        let (on, off, inv) = grid.cell_state_ratio();
+        assert_float_absolute_eq!(on, 0.0);
+        assert_float_relative_eq!(off, 1.0);
+        assert_float_absolute_eq!(inv, 0.0);
+
+        let mut grid = MapGrid::parse_string("#..\n#..\n#..", '#', '.')
+            .expect("Failed to parse standard grid, something is very wrong.");
+
+        let (on, off, inv) = grid.cell_state_ratio();
+        assert_float_relative_eq!(on, (1.0 / 3.0));
+        assert_float_relative_eq!(off, (2.0 / 3.0));
+        assert_float_absolute_eq!(inv, 0.0);
+        grid.reverse_in_place();
+        let (on, off, inv) = grid.cell_state_ratio();
+        assert_float_relative_eq!(on, (2.0 / 3.0));
+        assert_float_relative_eq!(off, (1.0 / 3.0));
+        assert_float_absolute_eq!(inv, 0.0);
+    }
+
+    #[test]
+    fn resize_works() {
+        init();
+
+        let mut grid = MapGrid::empty((5, 5));
+        assert_eq!(grid.cell_count(), 25);
+        let size = (10, 10);
+        let cell_value = Cell::on();
+        grid.resize_rows_with(size.0, cell_value);
+        grid.resize_cols_with(size.1, cell_value);
+        warn!("{}", grid.to_string());
+        assert_eq!(grid.cell_count(), 100);
+    }
+
+    #[test]
+    fn combining_grids() {
+        let grid1 = MapGrid::parse_string("#...#\n.....\n.....\n.....\n#...#", '#', '.')
+            .expect("Failed to parse standard grid, something is very wrong.");
+        assert_eq!(grid1.size(), (5, 5).into());
+        let grid2 = MapGrid::parse_string(".....\n.###.\n.###.\n.###.\n.....", '#', '.')
+            .expect("Failed to parse standard grid, something is very wrong.");
+        assert_eq!(grid2.size(), (5, 5).into());
+        let grid3 = MapGrid::parse_string("###\n###\n###", '#', '.')
+            .expect("Failed to parse standard grid, something is very wrong.");
+        assert_eq!(grid3.size(), (3, 3).into());
+
+        let union = MapGrid::union(&grid1, &grid3);
+        assert_eq!(union.to_strings().join("\n"), "###.#\n###..\n###..\n.....\n#...#");
+
+        let integrated = MapGrid::integrate(&grid1, &grid3, (2, 2));
+        assert_eq!(integrated.to_strings().join("\n"), "#...#\n.....\n..###\n..###\n#.###");
+    }
+
+    #[test]
+    fn tiled_export() {
+        init();
+
+        let mut grid = MapGrid::parse_string("#...#\n.....\n..#..\n.....\n#...#", '#', '.')
+            .expect("Unable to parse grid!");
+        grid.set_cell_invalid(1, 1);
+        let exported = grid.to_tiled_json(2, 1);
+        let value: serde_json::Value =
+            serde_json::from_str(&exported).expect("Tiled export should be valid json");
+        assert_eq!(value["width"], 5);
+        assert_eq!(value["height"], 5);
+
+        let data = value["layers"][0]["data"]
+            .as_array()
+            .expect("Tile layer should contain a data array");
+        assert_eq!(data.len(), grid.cell_count());
+        assert_eq!(data[0], 2);
+        assert_eq!(data[1], 1);
+        assert_eq!(data[6], 1);
+        assert_eq!(data[12], 2);
+    }
+
+    #[test]
+    fn tiled_round_trip() {
+        init();
+
+        let grid = MapGrid::parse_string("#...#\n.....\n..#..\n.....\n#...#", '#', '.')
+            .expect("Unable to parse grid!");
+        let imported = MapGrid::from_tiled_json(&grid.to_tiled_json(7, 3), &[7])
+            .expect("Unable to import exported Tiled map");
+        assert_eq!(imported.to_strings(), grid.to_strings());
+
+        assert!(MapGrid::from_tiled_json("not json", &[1]).is_err());
+        assert!(MapGrid::from_tiled_json(r#"{"layers": []}"#, &[1]).is_err());
+        let huge = r#"{"layers": [{"type": "tilelayer", "width": 100000000000, "height": 100000000000, "data": [1, 1, 1]}]}"#;
+        let error =
+            MapGrid::from_tiled_json(huge, &[1]).expect_err("Overflowing size should be rejected");
+        assert!(error.contains("3 tiles"));
+    }
+
+    #[test]
+    fn parse_strict_rectangular() {
+        init();
+
+        let input = "#####\n#...#\n#..#\n#...#\n#####";
+        let strict = ParseOptions {
+            strict_rectangular: true,
+            ..ParseOptions::default()
+        };
+        let errors = MapGrid::parse_string_with_options(input, '#', '.', strict)
+            .expect_err("Short row should be rejected in strict mode");
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("Line(s) 3 "));
+
+        let named =
+            MapGrid::parse_string_with_options(format!("Named\n{}", input), '#', '.', strict)
+                .expect_err("Short row should be rejected in strict mode");
+        assert!(named[0].contains("Line(s) 4 "));
+
+        let padded = MapGrid::parse_string(input, '#', '.').expect("Lenient parse should succeed");
+        assert_eq!(padded.size(), size(5, 5));
+        assert!(padded.cell((3, 2)).unwrap().is_on());
+        assert!(padded.cell((4, 2)).unwrap().is_invalid());
+
+        assert!(MapGrid::parse_string_with_options("###\n#.#\n###", '#', '.', strict).is_ok());
+        let trailing = MapGrid::parse_string_with_options("###\n#.#\n###\n", '#', '.', strict)
+            .expect("A trailing newline should be accepted in strict mode");
+        assert_eq!(trailing.size(), size(3, 3));
+        assert!(MapGrid::parse_string_with_options("###\n#.#\n###\n\n", '#', '.', strict).is_err());
+
+        let wide = MapGrid::parse_string("████\n█··█\n██\n████", '█', '·')
+            .expect("Lenient parse should succeed");
+        assert_eq!(wide.size(), size(4, 4));
+        assert!(wide.cell((1, 2)).unwrap().is_on());
+        assert!(wide.cell((2, 2)).unwrap().is_invalid());
+    }
+
+    #[test]
+    fn find_cells() {
+        init();
+
+        let mut grid = MapGrid::empty(size(5, 4));
+        grid.set_cell_invalid(3, 1);
+        grid.set_cell_invalid(1, 2);
+        grid.set_cell_invalid(4, 3);
+
+        let (first, cell) = grid
+            .find_cell(|_, cell| cell.is_invalid())
+            .expect("Grid should contain an invalid cell");
+        assert_eq!(first, pos((3, 1)));
+        assert!(cell.is_invalid());
+
+        let below_first_row = grid.find_cell(|p, cell| p.y > 1 && cell.is_invalid());
+        assert_eq!(below_first_row.map(|(p, _)| p), Some(pos((1, 2))));
+        assert!(grid.find_cell(|_, cell| cell.is_on()).is_none());
+
+        let all = grid
+            .find_all(|_, cell| cell.is_invalid())
+            .into_iter()
+            .map(|(p, _)| p)
+            .collect::<Vec<_>>();
+        assert_eq!(all, vec![pos((3, 1)), pos((1, 2)), pos((4, 3))]);
+        assert!(grid.find_all(|_, cell| cell.is_on()).is_empty());
+    }
+
+    #[test]
+    fn base64_round_trip() {
+        init();
+
+        let mut grid = PremadeGrids::maze3();
+        grid.set_name("Maze 3");
+        let code = grid.to_base64();
+        assert!(!code.contains(|c: char| c.is_whitespace() || c == '/' || c == '+'));
+        assert!(code.len() < grid.to_json_string(false).unwrap().len() / 4);
+
+        let decoded = MapGrid::from_base64(&code).expect("Unable to decode grid!");
+        assert_eq!(decoded, grid);
+        assert_eq!(decoded.name_copy(), Some("Maze 3".to_string()));
+
+        // Invalid cells come back as off
+        let mut odd = MapGrid::random_fill_percent((7, 5), 0.5);
+        odd.set_cell_invalid(2, 2);
+        let decoded = MapGrid::from_base64(odd.to_base64()).expect("Unable to decode grid!");
+        assert!(!decoded.has_name());
+        assert!(decoded.cell((2, 2)).unwrap().is_off());
+        odd.set_cell_state(2, 2, false);
+        assert_eq!(decoded, odd);
+
+        assert!(MapGrid::from_base64("not base64!").is_err());
+        assert!(MapGrid::from_base64(&code[..code.len() - 4]).is_err());
+        assert!(MapGrid::from_base64("").is_err());
+    }
+
+    #[test]
+    fn map_file_round_trip() {
+        init();
+
+        let mut grid = MapGrid::parse_string("#######\n#...#.#\n#.#...#\n#######", '#', '.')
+            .expect("Unable to parse grid!");
+        grid.set_name("Round Trip");
+        let path = std::env::temp_dir().join("dungen_map_file_round_trip.txt");
+        grid.save_map_file(&path, Some(pos((1, 1))), Some(pos((5, 2))))
+            .expect("Unable to save map file!");
+
+        let (loaded, start, goal) =
+            MapGrid::parse_map_file(&path).expect("Unable to parse map file!");
+        std::fs::remove_file(&path).ok();
+        assert_eq!(loaded, grid);
+        assert_eq!(loaded.name_copy(), Some("Round Trip".to_string()));
+        assert_eq!(start, pos((1, 1)));
+        assert_eq!(goal, pos((5, 2)));
+    }
+
+    #[test]
+    fn to_map_file() {
+        init();
+
+        let grid = PremadeGrids::maze5();
+        let (start, goal) = (pos((1, 1)), pos((19, 18)));
+        let path = std::env::temp_dir().join("dungen_to_map_file.txt");
+        grid.to_map_file(&path, start, goal)
+            .expect("Unable to save map file!");
+        let (loaded, loaded_start, loaded_goal) =
+            MapGrid::parse_map_file(&path).expect("Unable to parse map file!");
+        assert_eq!(loaded.dump_all_cells(), grid.dump_all_cells());
+        assert_eq!(loaded.name_copy(), Some(String::new()));
+        assert_eq!((loaded_start, loaded_goal), (start, goal));
+
+        // Markers on walls are still written, the wall is lost
+        grid.to_map_file(&path, pos((0, 0)), goal)
+            .expect("Unable to save map file!");
+        let (loaded, loaded_start, _) =
+            MapGrid::parse_map_file(&path).expect("Unable to parse map file!");
+        std::fs::remove_file(&path).ok();
+        assert_eq!(loaded_start, pos((0, 0)));
+        assert!(loaded.cell((0, 0)).unwrap().is_off());
+    }
+
+    #[test]
+    fn checksum() {
+        init();
+
+        let grid = MapGrid::random_fill_percent((12, 8), 0.4);
+        let mut copy = MapGrid::create_copy(&grid);
+        assert_eq!(grid.checksum(), copy.checksum());
+
+        copy.toggle_cell(3, 3);
+        assert_ne!(grid.checksum(), copy.checksum());
+        copy.toggle_cell(3, 3);
+        assert_eq!(grid.checksum(), copy.checksum());
+
+        assert_ne!(
+            MapGrid::empty((4, 6)).checksum(),
+            MapGrid::empty((6, 4)).checksum()
+        );
+    }
+
+    #[test]
+    fn iter_on_off() {
+        init();
+
+        let grid = MapGrid::random_fill_percent((20, 20), 0.45);
+        assert_eq!(grid.iter_on().count(), grid.on_cells_count());
+        assert_eq!(grid.iter_off().count(), grid.off_cells_count());
+        assert!(grid.iter_on().all(|p| grid.cell(p).unwrap().is_on()));
+        assert!(grid.iter_off().all(|p| grid.cell(p).unwrap().is_off()));
+
+        let grid = MapGrid::parse_string("###\n#.#\n###", '#', '.').expect("Unable to parse grid!");
+        assert_eq!(grid.iter_off().collect::<Vec<_>>(), vec![pos((1, 1))]);
+        assert_eq!(grid.iter_on().count(), 8);
+    }
+
+    #[test]
+    fn overlay_render() {
+        init();
+
+        let grid = MapGrid::parse_string("#####\n#...#\n#.#.#\n#...#\n#####", '#', '.')
+            .expect("Unable to parse grid!");
+        let overlay = [
+            (pos((1, 1)), '*'),
+            (pos((2, 1)), '→'),
+            (pos((3, 2)), '↓'),
+            (pos((2, 2)), '@'),
+            (pos((9, 9)), '!'),
+        ];
+        let rendered = grid.render_with_overlay(&overlay);
+        assert_eq!(rendered, vec!["#####", "#*→.#", "#.@↓#", "#...#", "#####"]);
+        assert_eq!(grid.render_with_overlay(&[]), grid.to_strings());
+    }
+
+    #[test]
+    fn invert_region_corner() {
+        init();
+
+        let mut grid = MapGrid::empty(size(6, 6));
+        grid.set_cell_invalid(5, 5);
+        grid.invert_region(&square(&(0, 0), 3, 3));
+        assert_eq!(grid.count_all(), (9, 26, 1));
+        for ((x, y), cell) in grid.iter_pos() {
+            if x < 3 && y < 3 {
+                assert!(cell.is_on());
+            } else if (x, y) != (5, 5) {
+                assert!(cell.is_off());
+            }
+        }
+
+        grid.invert_region(&square(&(4, 4), 5, 5));
+        assert_eq!(grid.count_all(), (12, 23, 1));
+        assert!(grid.cell((5, 5)).unwrap().is_invalid());
+    }
+
+    #[test]
+    fn path_turns() {
+        init();
+
+        let straight = (0..5).map(|x| pos((x, 2))).collect::<Vec<_>>();
+        assert_eq!(MapGrid::count_turns(&straight), 0);
+        assert_eq!(MapGrid::count_turns(&[]), 0);
+        assert_eq!(MapGrid::count_turns(&[pos((1, 1))]), 0);
+
+        let l_shape = vec![
+            pos((0, 0)),
+            pos((1, 0)),
+            pos((2, 0)),
+            pos((2, 1)),
+            pos((2, 2)),
+        ];
+        assert_eq!(MapGrid::count_turns(&l_shape), 1);
+
+        let zigzag = vec![
+            pos((0, 0)),
+            pos((1, 0)),
+            pos((1, 1)),
+            pos((2, 1)),
+            pos((2, 2)),
+        ];
+        assert_eq!(MapGrid::count_turns(&zigzag), 3);
+
+        let repeated = vec![pos((0, 0)), pos((1, 0)), pos((1, 0)), pos((2, 0))];
+        assert_eq!(MapGrid::count_turns(&repeated), 0);
     }
 
     #[test]
-    fn construction_works() {
-        crate_before_test();
-
-        let mut grid = MapGrid::new(size(10, 10));
-        assert_eq!(grid.width, 10);
-        assert_eq!(grid.height, 10);
-        assert_eq!(grid.rows(), 10);
-        assert_eq!(grid.cols(), 10);
-        assert_eq!(grid.cells.len(), 10);
-        assert_eq!(grid.cells[0].len(), 10);
-        assert_eq!(grid.invalid_cells_count(), 100);
-        assert_eq!(grid.on_cells_count(), 0);
-        assert_eq!(grid.off_cells_count(), 0);
+    fn difficulty_zones() {
+        init();
 
-        grid.set_cell(0, 0, Cell::on());
-        grid.set_cell(1, 0, Cell::off());
-        assert_eq!(grid.invalid_cells_count(), 98);
-        assert_eq!(grid.on_cells_count(), 1);
-        assert_eq!(grid.off_cells_count(), 1);
+        let mut grid = MapGrid::parse_string(
+            "#########\n#.......#\n#.#####.#\n#.#...#.#\n#.#.#.#.#\n#...#...#\n#########",
+            '#',
+            '.',
+        )
+        .expect("Unable to parse grid!");
+        grid.set_cell_state(3, 3, true);
+        grid.set_cell_invalid(5, 3);
+        let start = pos((1, 1));
+        let distances = grid.distance_field(start);
+        assert_eq!(distances[1][1], Some(0));
+        assert_eq!(distances[5][1], Some(4));
+        assert_eq!(distances[0][0], None);
+
+        let zones = grid.difficulty_zones(start, 3);
+        assert_eq!(zones.len(), 3);
+        let reachable = distances.iter().flatten().flatten().count();
+        assert_eq!(zones.iter().map(Vec::len).sum::<usize>(), reachable);
+        for (tier, zone) in zones.iter().enumerate() {
+            assert!(!zone.is_empty());
+            for p in zone {
+                let distance = distances[p.y][p.x].expect("Zoned cells should be reachable");
+                for later in zones.iter().skip(tier + 1).flatten() {
+                    assert!(distance < distances[later.y][later.x].unwrap());
+                }
+            }
+        }
+        assert!(zones[0].contains(&start));
 
-        let mut grid = MapGrid::empty((5, 5));
-        assert_eq!(grid.width, 5);
-        assert_eq!(grid.height, 5);
-        assert_eq!(grid.rows(), 5);
-        assert_eq!(grid.cols(), 5);
-        assert_eq!(grid.cells.len(), 5);
-        assert_eq!(grid.cells[0].len(), 5);
-        assert_eq!(grid.invalid_cells_count(), 0);
-        assert_eq!(grid.on_cells_count(), 0);
-        assert_eq!(grid.off_cells_count(), 25);
+        assert!(grid
+            .difficulty_zones(pos((0, 0)), 2)
+            .iter()
+            .all(Vec::is_empty));
+        assert!(grid.difficulty_zones(start, 0).is_empty());
+    }
 
-        grid.set_cell_state(0, 0, true);
-        assert_eq!(grid.invalid_cells_count(), 0);
-        assert_eq!(grid.on_cells_count(), 1);
-        assert_eq!(grid.off_cells_count(), 24);
+    #[test]
+    fn bool_matrix_round_trip() {
+        init();
 
-        grid.set_cell_invalid(0, 1);
-        assert_eq!(grid.invalid_cells_count(), 1);
-        assert_eq!(grid.on_cells_count(), 1);
-        assert_eq!(grid.off_cells_count(), 23);
+        let mut grid = MapGrid::parse_string("#...#\n.#.#.\n..#..\n.....", '#', '.')
+            .expect("Unable to parse grid!");
+        let matrix = grid.to_bool_matrix();
+        assert_eq!(matrix.len(), 4);
+        assert_eq!(matrix[1], vec![false, true, false, true, false]);
+        let imported = MapGrid::from_bool_matrix(&matrix).expect("Unable to import matrix");
+        assert_eq!(imported.to_strings(), grid.to_strings());
+
+        grid.set_cell_invalid(0, 0);
+        assert!(!grid.to_bool_matrix()[0][0]);
+
+        let mut ragged = matrix.clone();
+        ragged[2].pop();
+        assert!(MapGrid::from_bool_matrix(&ragged).is_err());
+        assert!(MapGrid::from_bool_matrix(&matrix[..2]).is_err());
+        assert!(MapGrid::from_bool_matrix(&[]).is_err());
     }
 
     #[test]
-    fn random_fill_works() {
+    fn fit_to() {
         init();
 
-        let grid = MapGrid::random_fill_percent((10, 10), 0.5);
-        assert_eq!(grid.on_cells_count(), 50);
-        assert_eq!(grid.off_cells_count(), 50);
+        let mut grid = MapGrid::random((20, 10));
+        grid.set_name("Fit");
+        let fitted = grid.fit_to(size(12, 12));
+        assert_eq!(fitted.size(), size(10, 5));
+        assert_eq!(fitted.to_strings(), grid.downscale(2).to_strings());
 
-        let grid = MapGrid::random_fill_number((10, 10), 50);
-        assert_eq!(grid.on_cells_count(), 50);
-        assert_eq!(grid.off_cells_count(), 50);
+        assert_eq!(grid.fit_to(size(6, 6)).size(), size(5, 3));
+        assert_eq!(grid.fit_to(size(20, 10)), grid);
+        assert_eq!(grid.fit_to(size(100, 100)).size(), size(20, 10));
     }
 
     #[test]
-    fn set_all_cells() {
+    fn nearest_source() {
         init();
 
-        let mut grid = MapGrid::new(size(5, 5));
-        assert_eq!(grid.invalid_cells_count(), 25);
-        assert_eq!(grid.on_cells_count(), 0);
-        assert_eq!(grid.off_cells_count(), 0);
-
-        grid.set_all_cells(true);
-        assert_eq!(grid.invalid_cells_count(), 0);
-        assert_eq!(grid.on_cells_count(), 25);
-        assert_eq!(grid.off_cells_count(), 0);
+        let mut grid = MapGrid::empty(size(9, 5));
+        grid.set_outer_cells(true);
+        let owners = grid.nearest_source(&[pos((1, 2)), pos((7, 2))]);
+        for ((x, y), cell) in grid.iter_pos() {
+            let expected = if cell.is_on() {
+                None
+            } else if x <= 4 {
+                Some(0)
+            } else {
+                Some(1)
+            };
+            assert_eq!(owners[y][x], expected, "Unexpected owner at ({}, {})", x, y);
+        }
 
-        grid.set_all_cells(false);
-        assert_eq!(grid.invalid_cells_count(), 0);
-        assert_eq!(grid.on_cells_count(), 0);
-        assert_eq!(grid.off_cells_count(), 25);
+        grid.set_cell_state(4, 2, true);
+        let walled = grid.nearest_source(&[pos((0, 0)), pos((4, 2)), pos((7, 3))]);
+        assert!(walled.iter().flatten().flatten().all(|&owner| owner == 2));
+        assert!(grid
+            .nearest_source(&[])
+            .iter()
+            .flatten()
+            .all(Option::is_none));
     }
 
     #[test]
-    fn reverse_in_place() {
+    fn punch_exits() {
         init();
 
-        let mut grid = MapGrid::empty((5, 5));
-        assert_eq!(grid.invalid_cells_count(), 0);
-        assert_eq!(grid.on_cells_count(), 0);
-        assert_eq!(grid.off_cells_count(), 25);
+        let mut cave = MapGrid::empty(size(12, 9));
+        cave.set_border(2, true);
+        cave.set_cell_state(2, 4, true);
+        assert!(cave.border_openings().is_empty());
+
+        let exits = cave.punch_exits(4, 42);
+        assert_eq!(exits.len(), 4);
+        let openings = cave.border_openings();
+        let (labels, _) = cave.label_regions(false);
+        for exit in &exits {
+            assert!(openings.iter().any(|(p, _)| p == exit));
+            assert_eq!(
+                labels[exit.y][exit.x], labels[4][5],
+                "Exit should reach the interior"
+            );
+            for other in &exits {
+                if other != exit {
+                    assert!(absdiff(exit.x, other.x) > 1 || absdiff(exit.y, other.y) > 1);
+                }
+            }
+        }
 
-        grid.reverse_in_place();
-        assert_eq!(grid.invalid_cells_count(), 0);
-        assert_eq!(grid.on_cells_count(), 25);
-        assert_eq!(grid.off_cells_count(), 0);
+        let mut again = MapGrid::empty(size(12, 9));
+        again.set_border(2, true);
+        again.set_cell_state(2, 4, true);
+        assert_eq!(again.punch_exits(4, 42), exits);
+
+        let mut sealed = MapGrid::empty(size(5, 5));
+        sealed.set_all_cells(true);
+        assert!(sealed.punch_exits(2, 42).is_empty());
     }
 
     #[test]
-    fn set_outer_works() {
+    fn integrate_names() {
         init();
 
-        let mut grid = MapGrid::empty((3, 3));
-        grid.set_outer_cells(true);
-        assert_eq!(grid.on_cells_count(), 8);
-        assert_eq!(grid.off_cells_count(), 1);
+        let mut first = MapGrid::empty((3, 3));
+        let mut second = MapGrid::empty((4, 4));
+        assert!(MapGrid::integrate(&first, &second, (1, 1))
+            .name_copy()
+            .is_none());
+
+        second.set_name("Rooms");
         assert_eq!(
-            grid.to_strings().join("\n"),
-            "###\n#.#\n###",
-            "Grid did not match expected output"
+            MapGrid::integrate(&first, &second, (1, 1)).name_copy(),
+            Some("Rooms".to_string())
         );
 
-        let mut grid = MapGrid::empty((5, 5));
-        grid.set_outer_cells(true);
-        assert_eq!(grid.on_cells_count(), 16);
-        assert_eq!(grid.off_cells_count(), 9);
+        first.set_name("Cave");
         assert_eq!(
-            grid.to_strings().join("\n"),
-            "#####\n#...#\n#...#\n#...#\n#####"
+            MapGrid::integrate(&first, &second, (1, 1)).name_copy(),
+            Some("Cave + Rooms".to_string())
+        );
+        assert_eq!(
+            MapGrid::union(&second, &first).name_copy(),
+            Some("Rooms + Cave".to_string())
         );
 
-        grid.reverse_in_place();
+        second.clear_name();
         assert_eq!(
-            grid.to_strings().join("\n"),
-            ".....\n.###.\n.###.\n.###.\n....."
+            MapGrid::union(&first, &second).name_copy(),
+            Some("Cave".to_string())
         );
     }
 
     #[test]
-    fn random_cells_work() {
+    fn try_constructors() {
         init();
 
-        let mut grid = MapGrid::empty((10, 10));
-        for _ in 0..50 {
-            let (x, y) = grid.random_cell_pos().into();
-            assert!(x < 25);
-            assert!(y < 25);
-        }
-
-        for _ in 0..50 {
-            let _ = grid.random_cell();
-        }
+        let err = MapGrid::try_empty((2, 2)).expect_err("2x2 grid should be rejected");
+        assert_eq!(err, "Grid must be at least 3x3, but 2x2 was requested");
+        assert!(MapGrid::try_new((5, 1)).is_err());
+        assert!(MapGrid::try_new(size(0, 4)).is_err());
 
-        for _ in 0..50 {
-            let _ = grid.random_cell_mut();
-        }
+        let empty = MapGrid::try_empty((3, 4)).expect("3x4 grid should be allowed");
+        assert_eq!(empty.size(), size(3, 4));
+        assert!(empty.iter().all(|c| c.is_off()));
+        let new = MapGrid::try_new((4, 3)).expect("4x3 grid should be allowed");
+        assert!(new.iter().all(|c| c.is_invalid()));
     }
 
     #[test]
-    #[should_panic]
-    fn panics_invalid_height() {
+    fn chunks() {
         init();
-        MapGrid::empty((100, 2));
+
+        let grid = MapGrid::random((9, 9));
+        let chunks = grid.chunks(3, 3).collect::<Vec<_>>();
+        assert_eq!(chunks.len(), 9);
+        let mut covered = vec![vec![0; 9]; 9];
+        for (section, chunk) in &chunks {
+            assert_eq!(chunk.size(), size(3, 3));
+            for p in section.size().positions() {
+                let (x, y) = (section.min.x + p.x, section.min.y + p.y);
+                covered[y][x] += 1;
+                assert_eq!(chunk.cell(p), grid.cell((x, y)));
+            }
+        }
+        assert!(covered.iter().flatten().all(|&count| count == 1));
+
+        let uneven = MapGrid::random((10, 7)).chunks(4, 4).collect::<Vec<_>>();
+        assert_eq!(uneven.len(), 6);
+        let (last_square, last_chunk) = &uneven[5];
+        assert_eq!(*last_square, square(&(8, 4), 2, 3));
+        assert_eq!(last_chunk.size(), size(3, 3));
+        assert!(last_chunk.cell((2, 0)).unwrap().is_invalid());
     }
 
     #[test]
-    #[should_panic]
-    fn panics_invalid_width() {
+    fn ortho_neighbors_block_corner_cutting() {
         init();
-        MapGrid::empty((2, 100));
+
+        // The two open areas only touch diagonally, through the corner between two walls
+        let grid = MapGrid::parse_string("#####\n#.###\n##..#\n#####", '#', '.')
+            .expect("Unable to parse grid!");
+        assert_eq!(
+            grid.neighbors_with_state((1, 1), false, false),
+            vec![(2, 2)]
+        );
+        assert!(grid
+            .neighbors_with_state_ortho((1, 1), false, false)
+            .is_empty());
+        assert_eq!(
+            grid.neighbors_with_state_ortho((2, 2), false, false),
+            vec![(3, 2)]
+        );
+        assert_eq!(
+            grid.neighbors_with_state_ortho((2, 2), true, false),
+            vec![(2, 1), (1, 2), (2, 3)]
+        );
+
+        let goal = (3, 2);
+        let diagonal = pathfinding::prelude::bfs(
+            &(1, 1),
+            |&p| grid.neighbors_with_state(p, false, false),
+            |&p| p == goal,
+        );
+        let ortho = pathfinding::prelude::bfs(
+            &(1, 1),
+            |&p| grid.neighbors_with_state_ortho(p, false, false),
+            |&p| p == goal,
+        );
+        assert!(diagonal.is_some());
+        assert!(ortho.is_none());
     }
 
     #[test]
-    fn names_work() {
+    fn neighbor_positions_edges() {
         init();
 
-        let mut grid = MapGrid::empty((5, 5));
-        assert_eq!(grid.name, None);
-        assert!(!grid.has_name());
-
-        grid.set_name("test");
-        assert_eq!(grid.name, Some("test".to_string()));
-        assert!(grid.has_name());
+        let grid = MapGrid::empty((5, 5));
+        let corners = [
+            ((0, 0), vec![(1, 0), (0, 1), (1, 1)]),
+            ((4, 0), vec![(3, 0), (3, 1), (4, 1)]),
+            ((0, 4), vec![(0, 3), (1, 3), (1, 4)]),
+            ((4, 4), vec![(3, 3), (4, 3), (3, 4)]),
+        ];
+        for (corner, expected) in corners {
+            assert_eq!(grid.neighbor_positions(corner), expected);
+        }
 
-        let mut grid = MapGrid::empty_named("Test", (5, 5));
-        assert_eq!(grid.name, Some("Test".to_string()));
-        assert!(grid.has_name());
+        let edges = [
+            ((2, 0), vec![(1, 0), (3, 0), (1, 1), (2, 1), (3, 1)]),
+            ((4, 2), vec![(3, 1), (4, 1), (3, 2), (3, 3), (4, 3)]),
+            ((2, 4), vec![(1, 3), (2, 3), (3, 3), (1, 4), (3, 4)]),
+            ((0, 2), vec![(0, 1), (1, 1), (1, 2), (0, 3), (1, 3)]),
+        ];
+        for (edge, expected) in edges {
+            assert_eq!(grid.neighbor_positions(edge), expected);
+        }
 
-        grid.clear_name();
-        assert_eq!(grid.name, None);
-        assert!(!grid.has_name());
+        for (p, _) in grid.iter_pos() {
+            let truncated = grid.neighbor_positions(p);
+            let wrapped = grid.neighbor_positions_wrapping(p);
+            assert!(truncated.iter().all(|n| wrapped.contains(n)));
+            assert!(!truncated.contains(&p));
+        }
+        assert!(grid.neighbor_positions((5, 2)).is_empty());
+        assert!(grid.neighbor_positions((2, 9)).is_empty());
     }
 
     #[test]
-    fn get_neighbors() {
+    fn neighbor_positions_wrapping_far_edges() {
         init();
-        let grid1 = MapGrid::parse_string("###\n#-#\n###", '#', '-')
-            .expect("Failed to parse standard grid, something is very wrong.");
+
+        let small = MapGrid::empty((3, 3));
         assert_eq!(
-            grid1.size(),
-            (3, 3).into(),
-            "Grid should have 3 rows & 3 columns."
-        );
-        assert_unordered_match!(
-            grid1.neighbor_positions_wrapping((1, 1)),
+            small.neighbor_positions_wrapping((2, 2)),
             vec![
-                (0, 0),
-                (1, 0),
-                (2, 0),
-                (0, 1),
+                (1, 1),
                 (2, 1),
-                (0, 2),
+                (0, 1),
                 (1, 2),
-                (2, 2)
+                (0, 2),
+                (1, 0),
+                (2, 0),
+                (0, 0)
             ]
         );
-        assert_unordered_match!(
-            grid1.neighbor_positions_wrapping((1, 0)),
+        assert_eq!(
+            small.neighbor_positions_wrapping((2, 0)),
             vec![
-                (0, 2),
                 (1, 2),
                 (2, 2),
+                (0, 2),
+                (1, 0),
                 (0, 0),
-                (2, 0),
-                (0, 1),
                 (1, 1),
-                (2, 1)
+                (2, 1),
+                (0, 1)
             ]
         );
-        assert_unordered_match!(
-            grid1.neighbor_positions_wrapping((0, 0)),
+        assert_eq!(
+            small.neighbor_positions_wrapping((0, 2)),
             vec![
+                (2, 1),
+                (0, 1),
+                (1, 1),
                 (2, 2),
-                (0, 2),
                 (1, 2),
                 (2, 0),
-                (1, 0),
-                (2, 1),
-                (0, 1),
-                (1, 1)
+                (0, 0),
+                (1, 0)
             ]
         );
 
-        let grid2 = MapGrid::parse_string("#-#-#\n-#-#-\n#-#-#", '#', '-')
-            .expect("Failed to parse standard grid, something is very wrong.");
+        for grid in [
+            MapGrid::empty((3, 3)),
+            MapGrid::empty((4, 3)),
+            MapGrid::empty((3, 5)),
+        ] {
+            for (p, _) in grid.iter_pos() {
+                let neighbors = grid.neighbor_positions_wrapping(p);
+                let distinct = neighbors.iter().collect::<std::collections::HashSet<_>>();
+                assert_eq!(neighbors.len(), 8, "{:?} in {:?}", p, grid.size());
+                assert_eq!(distinct.len(), 8, "{:?} in {:?}", p, grid.size());
+                assert!(!neighbors.contains(&p));
+            }
+        }
+
+        assert!(small.neighbor_positions_wrapping((3, 0)).is_empty());
+        assert!(small.neighbor_positions_wrapping((0, 3)).is_empty());
+    }
+
+    #[test]
+    fn active_neighbors_n_reference() {
+        init();
+
+        let grid = MapGrid::random_fill_percent((7, 6), 0.5);
+        let reference = |cx: usize, cy: usize, n: usize| {
+            grid.iter_pos()
+                .filter(|&((x, y), cell)| {
+                    (x, y) != (cx, cy) && absdiff(x, cx) <= n && absdiff(y, cy) <= n && cell.is_on()
+                })
+                .count()
+        };
+
+        // Center, the four edges, then the four corners.
+        let probes = [
+            (3, 3),
+            (0, 2),
+            (6, 3),
+            (3, 0),
+            (2, 5),
+            (0, 0),
+            (6, 0),
+            (0, 5),
+            (6, 5),
+        ];
+        for n in 0..4 {
+            for (x, y) in probes {
+                assert_eq!(
+                    grid.active_neighbors_n(x, y, n),
+                    reference(x, y, n),
+                    "Mismatch at ({}, {}) with n = {}",
+                    x,
+                    y,
+                    n
+                );
+            }
+        }
+
+        assert_eq!(
+            grid.count_state_in_square(&square(&(5, 4), 10, 10), TriState::True),
+            reference(6, 5, 1) + usize::from(grid.cell((6, 5)).unwrap().is_on())
+        );
+        assert_eq!(
+            grid.count_state_in_square(&square(&(9, 9), 2, 2), TriState::True),
+            0
+        );
+    }
+
+    #[test]
+    fn bordered_strings() {
+        init();
+
+        let mut grid = MapGrid::empty((12, 3));
+        grid.set_outer_cells(true);
+        let bordered = grid.to_strings_bordered();
+        assert_eq!(bordered.len(), 4);
+        assert_eq!(bordered[0], " 012345678901");
+        assert_eq!(bordered[1], "0############");
+        assert_eq!(bordered[2], "1#..........#");
+        assert_eq!(bordered[3], "2############");
+
+        let debug = format!("{:?}", grid);
+        assert!(debug.ends_with(&format!("{}\n", bordered.join("\n"))));
+    }
+
+    #[test]
+    fn valid_mazes() {
+        init();
+
+        for maze in [
+            PremadeGridStrings::One,
+            PremadeGridStrings::Two,
+            PremadeGridStrings::Three,
+            PremadeGridStrings::Four,
+            PremadeGridStrings::Five,
+            PremadeGridStrings::Six,
+        ] {
+            let grid = maze.get_maze().expect("Premade maze should exist");
+            assert!(grid.is_valid_maze(), "{:?} should be a valid maze", maze);
+        }
+
+        let report = crate::data::PremadeGrids::maze1().maze_report();
+        assert_eq!(report.open_components, 1);
+        assert!(!report.exits.is_empty());
+
+        let mut pocket = MapGrid::parse_string("#######\n#...#.#\n#...###\n#######", '#', '.')
+            .expect("Unable to parse grid!");
+        let report = pocket.maze_report();
+        assert!(!pocket.is_valid_maze());
+        assert_eq!(report.open_cells, 7);
+        assert_eq!(report.open_components, 2);
+        assert_eq!(report.unreachable_cells, 1);
+        assert!(report.exits.is_empty());
+
+        pocket.set_cell_state(5, 1, true);
+        assert!(pocket.is_valid_maze());
+        pocket.set_cell_invalid(0, 0);
+        assert!(!pocket.is_valid_maze());
+    }
+
+    #[test]
+    fn enclosed_regions() {
+        init();
+
+        // The left room opens onto the border, the right pocket is sealed
+        let mut grid = MapGrid::parse_string(
+            "#########\n....#...#\n#...#.#.#\n#...#...#\n#########",
+            '#',
+            '.',
+        )
+        .expect("Unable to parse grid!");
+        assert_eq!(grid.label_regions(false).1, 2);
+        assert_eq!(grid.enclosed_region_count(), 1);
+
+        grid.set_cell_state(0, 1, true);
+        assert_eq!(grid.enclosed_region_count(), 2);
+        assert_eq!(MapGrid::empty((5, 5)).enclosed_region_count(), 0);
+    }
+
+    #[test]
+    fn region_diff() {
+        init();
+
+        // Two rooms separated by a thin wall, which a single cellular automata pass breaks through
+        let before = MapGrid::parse_string(
+            "###########\n#....#....#\n#....#....#\n#....#....#\n#....#....#\n#....#....#\n###########",
+            '#',
+            '.',
+        )
+        .expect("Unable to parse grid!");
+        let after = crate::gen::cell_auto::CellularAutomata::execute_on(
+            &before,
+            1,
+            crate::gen::cell_auto::Algorithm::default_first(),
+        );
+        let diff = before.region_diff(&after);
+        assert_eq!((diff.regions_before, diff.regions_after), (2, 1));
+        assert_eq!(diff.merged, 1);
+        assert_eq!((diff.appeared, diff.vanished, diff.split), (0, 0, 0));
+
+        // The middle of the wall opens up (+3), while the 8 room corners fill in
+        assert_eq!(diff.area_change, -5);
+
+        let reverse = after.region_diff(&before);
+        assert_eq!((reverse.split, reverse.merged), (1, 0));
+        assert_eq!(reverse.area_change, -diff.area_change);
+
+        let mut pocket = MapGrid::create_copy(&before);
+        pocket.set_all_cells(true);
+        pocket.set_cell_state(3, 3, false);
+        let diff = pocket.region_diff(&before);
+        assert_eq!((diff.appeared, diff.vanished), (1, 0));
+        assert_eq!(
+            before.region_diff(&before),
+            RegionDiff {
+                regions_before: 2,
+                regions_after: 2,
+                ..RegionDiff::default()
+            }
+        );
+    }
+
+    #[test]
+    fn region_geometry() {
+        init();
+
+        let mut grid = MapGrid::empty((7, 7));
+        grid.set_all_cells(true);
+        grid.invert_region(&square(&(2, 2), 3, 3));
+        let block = grid.iter_off().collect::<Vec<_>>();
+        assert_eq!(grid.region_area(&block), 9);
+        assert_eq!(grid.region_perimeter(&block), 12);
+
+        // A 1x9 corridor has the same area but a much longer perimeter
+        let mut grid = MapGrid::empty((11, 3));
+        grid.set_all_cells(true);
+        grid.invert_region(&square(&(1, 1), 9, 1));
+        let corridor = grid.iter_off().collect::<Vec<_>>();
+        assert_eq!(grid.region_area(&corridor), 9);
+        assert_eq!(grid.region_perimeter(&corridor), 20);
+
+        // The edge of the grid counts as a wall, and positions outside of it are skipped
+        let open = MapGrid::empty((3, 3));
+        let mut all = open.iter_off().collect::<Vec<_>>();
+        all.push(pos((5, 5)));
+        assert_eq!(open.region_area(&all), 9);
+        assert_eq!(open.region_perimeter(&all), 12);
+
+        // Only sides bordering cells outside of the component count, whatever their state
+        let top_row = vec![pos((0, 0)), pos((1, 0)), pos((2, 0)), pos((1, 0))];
+        assert_eq!(open.region_perimeter(&top_row), 8);
+        let mut walled = MapGrid::empty((3, 3));
+        walled.set_all_cells(true);
+        assert_eq!(walled.region_perimeter(&[pos((1, 1))]), 4);
+    }
+
+    #[test]
+    fn edge_open_runs() {
+        init();
+
+        // Maze 3 opens up in the top right and bottom left corners
+        let runs = PremadeGrids::maze3().edge_open_runs();
+        let run = |dir: Direction, xs: std::ops::Range<usize>, y: usize| {
+            (dir, xs.map(|x| pos((x, y))).collect::<Vec<_>>())
+        };
         assert_eq!(
-            grid2.size(),
-            (5, 3).into(),
-            "Grid should be 5 wide and 3 tall"
-        );
-        assert_unordered_match!(
-            grid2.neighbor_positions_wrapping((3, 1)),
+            runs,
             vec![
-                (2, 0),
-                (3, 0),
-                (4, 0),
-                (2, 1),
-                (4, 1),
-                (2, 2),
-                (3, 2),
-                (4, 2)
+                run(Direction::North, 69..72, 0),
+                run(Direction::South, 1..4, 22)
             ]
         );
-        assert_unordered_match!(
-            grid2.neighbor_positions_wrapping((4, 2)),
+
+        let mut grid = MapGrid::empty((5, 4));
+        grid.set_outer_cells(true);
+        grid.set_cell_state(0, 0, false);
+        grid.set_cell_state(1, 0, false);
+        grid.set_cell_state(3, 0, false);
+        grid.set_cell_state(4, 2, false);
+        assert_eq!(
+            grid.edge_open_runs(),
             vec![
-                (3, 1),
-                (4, 1),
-                (0, 1),
-                (3, 2),
-                (0, 2),
-                (3, 0),
-                (4, 0),
-                (0, 0)
+                (Direction::North, vec![pos((0, 0)), pos((1, 0))]),
+                (Direction::North, vec![pos((3, 0))]),
+                (Direction::East, vec![pos((4, 2))]),
+                (Direction::West, vec![pos((0, 0))]),
             ]
         );
+    }
 
-        let grid3 =
-            MapGrid::parse_string("#-#-#\n-#-#-\n#-#-#", '#', '-').expect("Unable to parse grid3");
-        let neighbors = grid3.neighbor_positions((3, 1));
-        assert_eq!(neighbors.len(), 8);
-        assert_unordered_match!(
-            neighbors,
-            [
-                (2, 0),
-                (3, 0),
-                (4, 0),
-                (2, 1),
-                (4, 1),
-                (2, 2),
-                (3, 2),
-                (4, 2),
-            ]
-        );
-        let neighbors = grid3.neighbor_positions((0, 0));
-        assert_eq!(neighbors.len(), 3);
-        assert_unordered_match!(neighbors, [(0, 1), (1, 1), (1, 0)]);
-        let neighbors = grid3.neighbor_positions((1, 0));
-        assert_eq!(neighbors.len(), 5);
-        assert_unordered_match!(neighbors, [(0, 0), (2, 0), (0, 1), (1, 1), (2, 1)]);
+    #[test]
+    fn ray_cast() {
+        init();
+
+        let grid = MapGrid::parse_string("#######\n#.....#\n#..#..#\n#.....#\n#######", '#', '.')
+            .expect("Unable to parse grid!");
+        let hits = [
+            ((1, 2), Direction::East, 10, Some(pos((3, 2)))),
+            ((1, 2), Direction::East, 1, None),
+            ((4, 2), Direction::East, 2, Some(pos((6, 2)))),
+            ((1, 1), Direction::North, 5, Some(pos((1, 0)))),
+            ((3, 2), Direction::South, 5, Some(pos((3, 4)))),
+            ((5, 3), Direction::West, 3, None),
+        ];
+        for (from, dir, max_dist, expected) in hits {
+            assert_eq!(grid.ray_cast(pos(from), dir, max_dist), expected);
+        }
+
+        // Rays leaving the grid without hitting anything find nothing
+        let open = MapGrid::empty((5, 5));
+        assert_eq!(open.ray_cast(pos((2, 2)), Direction::West, 10), None);
+        assert_eq!(open.ray_cast(pos((2, 2)), Direction::South, 10), None);
     }
 
     #[test]
-    fn neighbor_count_works() {
+    fn secret_room() {
         init();
-        let grid1 = MapGrid::parse_string("###\n#-#\n###", '#', '-')
-            .expect("Failed to parse standard grid, something is very wrong.");
-        assert_eq!(grid1.rows(), 3, "Grid 1 should have 3 rows.");
-        assert_eq!(grid1.cols(), 3, "Grid 1 should have 3 cols.");
 
+        let mut grid = MapGrid::empty((20, 10));
+        grid.set_all_cells(true);
+        grid.invert_region(&square(&(0, 1), 6, 8));
+        assert_eq!(grid.enclosed_region_count(), 0);
+
+        let room = grid
+            .add_secret_room(pos((14, 5)), size(3, 2), 11)
+            .expect("Room should fit in the solid area");
+        assert_eq!(room.size(), size(3, 2));
+        assert_eq!(grid.enclosed_region_count(), 1);
+        assert_eq!(grid.count_state_in_square(&room, TriState::False), 6);
+        let walls = square(&(room.min.x - 1, room.min.y - 1), 5, 4);
+        assert_eq!(grid.count_state_in_square(&walls, TriState::True), 20 - 6);
+        assert!(absdiff(room.min.x + 1, 14) + absdiff(room.min.y + 1, 5) <= 1);
+
+        let mut full = MapGrid::empty((10, 10));
+        assert!(full.add_secret_room(pos((5, 5)), size(3, 3), 11).is_none());
+        assert!(grid.add_secret_room(pos((5, 5)), size(30, 3), 11).is_none());
+    }
+
+    #[test]
+    fn flood_fill() {
+        init();
+        let mut grid = MapGrid::parse_string(
+            "#####\n\
+             #..##\n\
+             ##.##\n\
+             ###.#\n\
+             #####",
+            '#',
+            '.',
+        )
+        .expect("Unable to parse grid!");
+        grid.set_cell_invalid(1, 1);
+
+        assert!(grid.flood_region((1, 1), true).is_empty());
+        assert_eq!(grid.flood_region((2, 1), false), vec![(2, 1), (2, 2)]);
+        assert_eq!(grid.flood_region((2, 1), true).len(), 3);
+        assert_eq!(grid.flood_region((0, 0), false).len(), 21);
+        assert!(grid.flood_region((9, 9), false).is_empty());
+
+        assert_eq!(grid.flood_fill((1, 1), TriState::True, false), 0);
+        assert_eq!(grid.flood_fill((2, 1), TriState::False, false), 0);
+        assert_eq!(grid.flood_fill((2, 1), TriState::True, true), 3);
+        assert_eq!(grid.off_cells_count(), 0);
+        assert!(grid.cell((1, 1)).unwrap().is_invalid());
+        assert_eq!(grid.on_cells_count(), 24);
+    }
+
+    #[test]
+    fn connected_components() {
+        init();
+        let mut grid = MapGrid::parse_string(
+            "#######\n\
+             #..#..#\n\
+             #..##.#\n\
+             ####.##\n\
+             #.#####\n\
+             #######",
+            '#',
+            '.',
+        )
+        .expect("Unable to parse grid!");
+
+        let ortho = grid.connected_components(false, false);
         assert_eq!(
-            grid1.active_neighbor_count((1, 1), true),
-            8,
-            "Wrong neighbor count for grid 1 cell (1,1)"
+            ortho.iter().map(Vec::len).collect::<Vec<_>>(),
+            vec![4, 3, 1, 1]
         );
+        assert_eq!(ortho[1], vec![(4, 1), (5, 1), (5, 2)]);
+        assert_eq!(ortho[2], vec![(4, 3)]);
+        assert_eq!(ortho[3], vec![(1, 4)]);
+
+        let diagonal = grid.connected_components(false, true);
         assert_eq!(
-            grid1.active_neighbor_count((1, 0), true),
-            7,
-            "Wrong neighbor count for grid 1 cell (1,0)"
+            diagonal.iter().map(Vec::len).collect::<Vec<_>>(),
+            vec![4, 4, 1]
+        );
+        assert_eq!(grid.connected_components(true, false).len(), 1);
+
+        grid.set_cell_invalid(1, 1);
+        assert_eq!(grid.largest_component(false, false).unwrap().len(), 3);
+        assert!(grid
+            .connected_components(false, true)
+            .iter()
+            .all(|c| !c.contains(&(1, 1))));
+
+        assert!(MapGrid::new((4, 4))
+            .largest_component(false, true)
+            .is_none());
+    }
+
+    #[test]
+    fn count_state_on_line() {
+        init();
+        let grid = MapGrid::parse_string(
+            "#########\n\
+             ..#..#...\n\
+             #########",
+            '#',
+            '.',
+        )
+        .expect("Unable to parse grid!");
+
+        assert_eq!(grid.count_state_on_line(pos((0, 1)), pos((8, 1)), true), 2);
+        assert_eq!(grid.count_state_on_line(pos((8, 1)), pos((0, 1)), false), 7);
+        assert_eq!(grid.count_state_on_line(pos((3, 1)), pos((4, 1)), true), 0);
+        assert_eq!(grid.count_state_on_line(pos((0, 0)), pos((0, 2)), true), 2);
+        assert_eq!(grid.count_state_on_line(pos((0, 0)), pos((0, 6)), true), 2);
+    }
+
+    #[test]
+    fn remove_small_regions() {
+        init();
+        crate::util::random::init_rng_seeded(11);
+        let mut grid = crate::gen::cell_auto::CellularAutomata::execute_on(
+            &MapGrid::random_fill_percent((60, 30), 0.45),
+            5,
+            crate::gen::cell_auto::Algorithm::default_first(),
         );
+        let before = grid.connected_components(true, false);
+        let small = before.iter().filter(|c| c.len() < 6).count();
+        assert!(small > 0, "Expected some noise in the generated grid");
+
+        let untouched = grid.clone();
+        assert_eq!(grid.remove_small_regions(true, 0, false), 0);
+        assert_eq!(grid.to_strings(), untouched.to_strings());
+
+        let removed = grid.remove_small_regions(true, 6, false);
         assert_eq!(
-            grid1.active_neighbor_count((0, 0), true),
-            7,
-            "Wrong neighbor count for grid 1 cell (0,0)"
+            removed,
+            before
+                .iter()
+                .filter(|c| c.len() < 6)
+                .map(Vec::len)
+                .sum::<usize>()
         );
+        let after = grid.connected_components(true, false);
+        assert_eq!(after.len(), before.len() - small);
+        assert!(after.iter().all(|c| c.len() >= 6));
+
+        // A lone wall on the edge of the grid is removed too
+        let mut edge = MapGrid::empty((5, 5));
+        edge.set_cell_state(0, 2, true);
+        assert_eq!(edge.remove_small_regions(true, 2, false), 1);
+        assert_eq!(edge.on_cells_count(), 0);
+    }
 
-        let grid2 = MapGrid::parse_string("0000\n0110\n0000", '1', '0')
-            .expect("Failed to parse standard grid, something is very wrong.");
-        assert_eq!(grid2.rows(), 3, "Grid 2 should have 3 rows.");
-        assert_eq!(grid2.cols(), 4, "Grid 2 should have 4 cols.");
+    #[test]
+    fn rotate_and_flip() {
+        init();
+        let mut grid =
+            MapGrid::parse_string("##..\n#...\n....", '#', '.').expect("Unable to parse grid!");
+        grid.set_cell_invalid(3, 2);
+        grid.set_name("Piece");
+
+        let rotated = grid.rotate90(1);
+        assert_eq!(rotated.size(), (3, 4).into());
+        assert_eq!(rotated.name_copy(), Some(String::from("Piece (Rotated)")));
+        assert_eq!(rotated.to_strings(), vec![".##", "..#", "...", "X.."]);
+        assert_eq!(rotated.count_all(), grid.count_all());
+
+        assert_eq!(grid.rotate90(2).to_strings(), vec!["X...", "...#", "..##"]);
         assert_eq!(
-            grid2.active_neighbor_count((1, 1), true),
-            1,
-            "Wrong neighbor count for grid 2 cell (1,1)"
+            grid.rotate90(3).to_strings(),
+            vec!["..X", "...", "#..", "##."]
         );
+        assert_eq!(grid.rotate90(4), grid);
+        assert_eq!(grid.rotate90(0), grid);
+        assert_eq!(rotated.rotate90(3).dump_all_cells(), grid.dump_all_cells());
         assert_eq!(
-            grid2.active_neighbor_count((2, 1), true),
-            1,
-            "Wrong neighbor count for grid 2 cell (2,1)"
+            rotated.rotate90(3).name_copy(),
+            Some(String::from("Piece (Rotated)"))
         );
+
+        let flipped = grid.flip_horizontal();
+        assert_eq!(flipped.name_copy(), Some(String::from("Piece (Flipped)")));
+        for twice in [
+            flipped.flip_horizontal(),
+            grid.flip_vertical().flip_vertical(),
+        ] {
+            assert_eq!(twice.name_copy(), Some(String::from("Piece (Flipped)")));
+            assert_eq!(twice.dump_all_cells(), grid.dump_all_cells());
+        }
+        assert_eq!(flipped.to_strings(), vec!["..##", "...#", "X..."]);
         assert_eq!(
-            grid2.active_neighbor_count((1, 0), true),
-            2,
-            "Wrong neighbor count for grid 1 cell (2,0)"
+            grid.flip_vertical().to_strings(),
+            vec!["...X", "#...", "##.."]
         );
         assert_eq!(
-            grid2.active_neighbor_count((2, 0), true),
-            2,
-            "Wrong neighbor count for grid 1 cell (1,0)"
+            grid.flip_horizontal().flip_vertical().dump_all_cells(),
+            grid.rotate90(2).dump_all_cells()
         );
     }
 
     #[test]
-    fn subgrids() {
+    fn widen_corridors() {
         init();
+        let grid = MapGrid::parse_string(
+            "#########\n\
+             #.#######\n\
+             #.#...###\n\
+             #...#.###\n\
+             #####...#\n\
+             #########",
+            '#',
+            '.',
+        )
+        .expect("Unable to parse grid!");
+        let is_border = |(x, y): (usize, usize)| x == 0 || y == 0 || x == 8 || y == 5;
+
+        let once = grid.widen_corridors(1);
+        assert!(once.off_cells_count() > grid.off_cells_count());
+        assert!(once.cell((2, 2)).unwrap().is_off());
+        let twice = grid.widen_corridors(2);
+        assert!(twice.off_cells_count() > once.off_cells_count());
+        for widened in [&once, &twice] {
+            assert!(widened
+                .iter_pos()
+                .filter(|&(p, _)| is_border(p))
+                .all(|(_, cell)| cell.is_on()));
+        }
 
-        // #########
-        // #.......#
-        // #.#####.#
-        // #.#...#.#
-        // #.#.#.#.#
-        // #.#...#.#
-        // #.#####.#
-        // #.......#
-        // #########
-        let grid = MapGrid::parse_string("#########\n#.......#\n#.#####.#\n#.#...#.#\n#.#.#.#.#\n#.#...#.#\n#.#####.#\n#.......#\n#########", '#', '.').expect("Unable to parse grid.");
-        assert_eq!(grid.width, 9);
-        assert_eq!(grid.height, 9);
-        assert_eq!(grid.to_strings().join("\n"), "#########\n#.......#\n#.#####.#\n#.#...#.#\n#.#.#.#.#\n#.#...#.#\n#.#####.#\n#.......#\n#########");
-
-        let square = square(&(1, 1), 7, 7);
-        // let section = GridSection {
-        //     center: (4, 4),
-        //     u_extent: 3,
-        //     d_extent: 3,
-        //     l_extent: 3,
-        //     r_extent: 3,
-        // };
+        assert_eq!(grid.widen_corridors(0).to_strings(), grid.to_strings());
+    }
 
-        assert_eq!((square.width(), square.height()), (7, 7));
-        assert_eq!(square.size(), size(7, 7));
-        assert_eq!(square.center(), pos((4, 4)));
-        assert_eq!(square.x_range(), 1..8);
+    #[test]
+    fn neighbor_states() {
+        use TriState::{False as F, Invalid as I, True as T};
+        init();
+        let mut grid =
+            MapGrid::parse_string("#..#\n.#..\n...#", '#', '.').expect("Unable to parse grid!");
+        grid.set_cell_invalid(2, 1);
 
-        let sub = MapGrid::sub_grid(&grid, &square);
         assert_eq!(
-            sub.to_strings().join("\n"),
-            ".......\n.#####.\n.#...#.\n.#.#.#.\n.#...#.\n.#####.\n......."
+            grid.neighbor_states(pos((0, 0)), false),
+            [I, I, F, T, F, I, I, I]
+        );
+        assert_eq!(
+            grid.neighbor_states(pos((0, 0)), true),
+            [F, F, F, T, F, F, T, T]
+        );
+        assert_eq!(
+            grid.neighbor_states(pos((1, 1)), false),
+            [F, F, I, F, F, F, F, T]
+        );
+        assert_eq!(
+            grid.neighbor_states(pos((3, 2)), false),
+            [F, I, I, I, I, I, F, I]
         );
-        assert_eq!(sub.size(), (7, 7).into());
     }
 
     #[test]
-    fn n_neighbors() {
+    fn trim_and_crop() {
         init();
-
-        // #...#
-        // ..#..
-        // ..#..
-        // ..#..
-        // #...#
-        let grid = MapGrid::parse_string("#...#\n..#..\n..#..\n..#..\n#...#", '#', '.')
-            .expect("Unable to parse grid!");
-        assert_eq!(grid.on_cells_count(), 7);
-        assert_eq!(grid.active_neighbor_count((2, 2), true), 2);
-        assert_eq!(grid.active_neighbors_n(2, 2, 2), 6);
+        let mut grid = MapGrid::parse_string(
+            "........\n\
+             ........\n\
+             ..##....\n\
+             ...#.#..\n\
+             ........",
+            '#',
+            '.',
+        )
+        .expect("Unable to parse grid!");
+        grid.set_name("Padded");
+
+        assert_eq!(grid.bounding_box(false), Some(square(&(2, 2), 4, 2)));
+        assert_eq!(grid.bounding_box(true), Some(square(&(0, 0), 8, 5)));
+        assert_eq!(MapGrid::empty((4, 4)).bounding_box(false), None);
+
+        // Only two rows of content, so one row of padding is kept
+        let cropped = grid.cropped(false);
+        assert_eq!(cropped.to_strings(), vec!["##..", ".#.#", "...."]);
+        assert_eq!(cropped.name_copy(), Some(String::from("Padded")));
+
+        grid.trim(false);
+        assert_eq!(grid.to_strings(), cropped.to_strings());
+        grid.trim(false);
+        assert_eq!(grid.size(), (4, 3).into());
+
+        let mut single = MapGrid::empty((6, 6));
+        single.set_cell_state(5, 5, true);
+        single.trim(false);
+        assert_eq!(single.to_strings(), vec!["...", "...", "..#"]);
+
+        let mut empty = MapGrid::empty((6, 6));
+        empty.trim(false);
+        assert_eq!(empty.size(), (3, 3).into());
+        assert_eq!(empty.off_cells_count(), 9);
     }
 
     #[test]
-    fn cell_ratio() {
+    fn parse_string_autodetect() {
         init();
+        let maze = PremadeGrids::maze3();
+        let (grid, on, off) = MapGrid::parse_string_autodetect(maze.to_strings().join("\n"))
+            .expect("Unable to detect characters!");
+        assert_eq!((on, off), ('#', '.'));
+        assert_eq!(grid.dump_all_cells(), maze.dump_all_cells());
+
+        let (grid, on, off) = MapGrid::parse_string_autodetect("Named\n5 3\n,,~,,\nS,~,G\n,,,,,")
+            .expect("Unable to detect characters!");
+        assert_eq!((on, off), ('~', ','));
+        assert_eq!(grid.name_copy(), Some(String::from("Named")));
+        assert_eq!(grid.on_cells_count(), 2);
+
+        assert!(MapGrid::parse_string_autodetect("#.#\n.o.\n#.#").is_err());
+        assert!(MapGrid::parse_string_autodetect("###\n###\n###").is_err());
+    }
 
-        let grid = MapGrid::parse_string("####\n####\n....\n....", '#', '.')
-            .expect("Failed to parse standard grid, something is very wrong.");
-
-        let (on, off, inv) = grid.cell_state_ratio();
-        assert_float_relative_eq!(on, 0.5);
-        assert_float_relative_eq!(off, 0.5);
-        assert_float_absolute_eq!(inv, 0.0);
-
-        let grid = MapGrid::new(size(4, 4));
-        let (on, off, inv) = grid.cell_state_ratio();
-        assert_float_absolute_eq!(on, 0.0);
-        assert_float_absolute_eq!(off, 0.0);
-        assert_float_relative_eq!(inv, 1.0);
+    #[test]
+    fn parse_string_with_invalid() {
+        init();
+        let mut grid = PremadeGrids::maze5();
+        grid.set_cell_invalid(3, 4);
+        grid.set_cell_invalid(0, 0);
+        let text = grid.to_strings().join("\n");
+
+        let parsed = MapGrid::parse_string_with(&text, '#', '.', Some('X'))
+            .expect("Invalid cells should round trip");
+        assert_eq!(parsed.dump_all_cells(), grid.dump_all_cells());
+        assert_eq!(parsed.invalid_cells_count(), 2);
+
+        assert!(MapGrid::parse_string(&text, '#', '.').is_err());
+        assert!(MapGrid::parse_string_with(&text, '#', '.', Some('@')).is_err());
+    }
 
-        let grid = MapGrid::empty((4, 4));
-        let (on, off, inv) = grid.cell_state_ratio();
-        assert_float_absolute_eq!(on, 0.0);
-        assert_float_relative_eq!(off, 1.0);
-        assert_float_absolute_eq!(inv, 0.0);
+    #[test]
+    fn similarity() {
+        init();
+        let grid = PremadeGrids::maze4();
+        assert_float_absolute_eq!(grid.similarity(&grid).unwrap(), 1.0);
+        assert_float_absolute_eq!(grid.similarity(&MapGrid::reverse(&grid)).unwrap(), 0.0);
 
-        let mut grid = MapGrid::parse_string("#..\n#..\n#..", '#', '.')
-            .expect("Failed to parse standard grid, something is very wrong.");
+        let mut changed = grid.clone();
+        changed.toggle_cell(1, 1);
+        changed.toggle_cell(2, 2);
+        assert_eq!(grid.cell_count(), 33 * 18);
+        assert_float_absolute_eq!(changed.similarity(&grid).unwrap(), 1.0 - 2.0 / 594.0);
 
-        let (on, off, inv) = grid.cell_state_ratio();
-        assert_float_relative_eq!(on, (1.0 / 3.0));
-        assert_float_relative_eq!(off, (2.0 / 3.0));
-        assert_float_absolute_eq!(inv, 0.0);
-        grid.reverse_in_place();
-        let (on, off, inv) = grid.cell_state_ratio();
-        assert_float_relative_eq!(on, (2.0 / 3.0));
-        assert_float_relative_eq!(off, (1.0 / 3.0));
-        assert_float_absolute_eq!(inv, 0.0);
+        assert!(grid.similarity(&MapGrid::empty((4, 4))).is_err());
     }
 
     #[test]
-    fn resize_works() {
+    fn reachable_avoiding() {
         init();
-
-        let mut grid = MapGrid::empty((5, 5));
-        assert_eq!(grid.cell_count(), 25);
-        let size = (10, 10);
-        let cell_value = Cell::on();
-        grid.resize_rows_with(size.0, cell_value);
-        grid.resize_cols_with(size.1, cell_value);
-        warn!("{}", grid.to_string());
-        assert_eq!(grid.cell_count(), 100);
+        let grid = PremadeGrids::maze5();
+        let (start, goal) = (pos((1, 1)), pos((10, 17)));
+        assert!(grid.reachable_avoiding(start, goal, &[]));
+        assert!(grid.reachable_avoiding(start, goal, &[pos((15, 8)), pos((30, 30))]));
+
+        // The three cell wide corridor in the middle is the only way through
+        let barrier = [pos((9, 10)), pos((10, 10)), pos((11, 10))];
+        assert!(!grid.reachable_avoiding(start, goal, &barrier));
+        assert!(grid.reachable_avoiding(start, goal, &barrier[..2]));
+        assert!(grid.cell((10, 10)).unwrap().is_off());
+
+        assert!(grid.reachable_avoiding(start, start, &[]));
+        assert!(!grid.reachable_avoiding(start, goal, &[goal]));
+        assert!(!grid.reachable_avoiding(pos((0, 0)), goal, &[]));
     }
 
     #[test]
-    fn combining_grids() {
-        let grid1 = MapGrid::parse_string("#...#\n.....\n.....\n.....\n#...#", '#', '.')
-            .expect("Failed to parse standard grid, something is very wrong.");
-        assert_eq!(grid1.size(), (5, 5).into());
-        let grid2 = MapGrid::parse_string(".....\n.###.\n.###.\n.###.\n.....", '#', '.')
-            .expect("Failed to parse standard grid, something is very wrong.");
-        assert_eq!(grid2.size(), (5, 5).into());
-        let grid3 = MapGrid::parse_string("###\n###\n###", '#', '.')
-            .expect("Failed to parse standard grid, something is very wrong.");
-        assert_eq!(grid3.size(), (3, 3).into());
-
-        let union = MapGrid::union(&grid1, &grid3);
-        assert_eq!(union.to_strings().join("\n"), "###.#\n###..\n###..\n.....\n#...#");
-
-        let integrated = MapGrid::integrate(&grid1, &grid3, (2, 2));
-        assert_eq!(integrated.to_strings().join("\n"), "#...#\n.....\n..###\n..###\n#.###");
+    fn carve_random_walk() {
+        init();
+        let (from, to) = (pos((2, 2)), pos((25, 15)));
+
+        let mut direct = MapGrid::new((30, 20));
+        let path = direct.carve_random_walk(from, to, 0.0, 3);
+        assert_eq!(path.len(), 23 + 13 + 1);
+        assert_eq!((path[0], path[path.len() - 1]), (from, to));
+        assert!(path
+            .windows(2)
+            .all(|w| absdiff(w[0].x, w[1].x) + absdiff(w[0].y, w[1].y) == 1));
+        assert_eq!(direct.off_cells_count(), path.len());
+
+        let mut cave = MapGrid::new((30, 20));
+        let path = cave.carve_random_walk(from, to, 0.6, 3);
+        assert!(path.len() > 37);
+        assert_eq!((path[0], path[path.len() - 1]), (from, to));
+        assert!(path.iter().all(|p| cave.cell(*p).unwrap().is_off()));
+        assert!(cave.reachable_avoiding(from, to, &[]));
+        assert!(cave
+            .iter_pos()
+            .filter(|&((x, y), _)| x == 0 || y == 0 || x == 29 || y == 19)
+            .all(|(_, cell)| !cell.is_off()));
+
+        let mut again = MapGrid::new((30, 20));
+        assert_eq!(again.carve_random_walk(from, to, 0.6, 3), path);
+        assert!(again
+            .carve_random_walk(from, pos((30, 2)), 0.6, 3)
+            .is_empty());
     }
 }
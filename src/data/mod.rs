@@ -59,9 +59,13 @@ mod premade;
 /// by the parent module, [`crate::data`].
 mod types;
 
-pub use self::grid::{GridIterator, MapGrid};
+pub use self::grid::{ComponentStat, GridIterator, MapGrid, MazeReport, ParseOptions, RegionDiff};
 pub use cell::TriCell as Cell;
 pub use premade::{
     GridFiles as PremadeGridFiles, GridStrings as PremadeGridStrings, Grids as PremadeGrids,
 };
-pub use types::{pos, size, square, AsPos, GridIndex, GridPos, GridSize, GridSquare};
+pub use types::{
+    pos, size, square, square_radius, AsPos, Direction, GridBounds, GridDimensions, GridDisplay,
+    GridIndex, GridPos, GridPositions, GridRanges, GridSize, GridSquare, PositionIterator,
+    SquareDisplay,
+};
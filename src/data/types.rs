@@ -1,3 +1,5 @@
+use std::{fmt, ops::Range};
+
 use euclid::{Box2D, Point2D, Size2D};
 
 /// Marker for [`euclid::Point2D`].
@@ -23,6 +25,21 @@ pub fn square<T1: AsPos<B1>, B1>(top_left: &T1, x_size: usize, y_size: usize) ->
     GridSquare::from_origin_and_size(top_left.as_pos(), Size2D::new(x_size, y_size))
 }
 
+/// Convenience function to create a [`GridSquare`][`crate::data::GridSquare`] covering every position
+/// within `radius` (Chebyshev distance) of `center`. The square is truncated at 0 on the top and left
+/// edges, but is not clamped to any grid on the bottom and right edges.
+#[must_use]
+pub fn square_radius<T: AsPos<B>, B>(center: &T, radius: usize) -> GridSquare {
+    let center = center.as_pos();
+    GridSquare::new(
+        GridPos::new(
+            center.x.saturating_sub(radius),
+            center.y.saturating_sub(radius),
+        ),
+        GridPos::new(center.x + radius + 1, center.y + radius + 1),
+    )
+}
+
 /// Convenience function to create a [`GridSize`][`crate::data::GridSize`] from a width and height.
 #[must_use]
 pub fn size(width: usize, height: usize) -> GridSize {
@@ -46,3 +63,268 @@ where
         GridPos::new(tup.0, tup.1)
     }
 }
+
+/// An iterator over every [`GridPos`] within a rectangular area, in row-major order.
+#[derive(Debug, Clone)]
+pub struct PositionIterator {
+    min_x: GridIndex,
+    max_x: GridIndex,
+    max_y: GridIndex,
+    curr: (GridIndex, GridIndex),
+}
+
+impl PositionIterator {
+    /// Creates a new [`PositionIterator`] over `min.x..max.x` x `min.y..max.y`.
+    #[must_use]
+    pub fn new(min: GridPos, max: GridPos) -> Self {
+        Self {
+            min_x: min.x,
+            max_x: max.x,
+            max_y: max.y,
+            curr: (min.x, min.y),
+        }
+    }
+}
+
+impl Iterator for PositionIterator {
+    type Item = GridPos;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.min_x >= self.max_x || self.curr.1 >= self.max_y {
+            return None;
+        }
+
+        let result = GridPos::new(self.curr.0, self.curr.1);
+        self.curr.0 += 1;
+        if self.curr.0 >= self.max_x {
+            self.curr.0 = self.min_x;
+            self.curr.1 += 1;
+        }
+
+        Some(result)
+    }
+}
+
+/// Trait used to iterate over all of the [`GridPos`](`crate::data::GridPos`)s contained in a type.
+pub trait GridPositions {
+    /// Returns an iterator over each contained position in row-major order.
+    fn positions(&self) -> PositionIterator;
+}
+
+impl GridPositions for GridSize {
+    fn positions(&self) -> PositionIterator {
+        PositionIterator::new(GridPos::zero(), GridPos::new(self.width, self.height))
+    }
+}
+
+/// Trait used to get the ranges covered by a [`GridSquare`](`crate::data::GridSquare`) without
+/// casting through `isize`.
+pub trait GridRanges {
+    /// Returns the `(x, y)` ranges covered by this square, in `usize`. Use [`euclid::Box2D::cast`]
+    /// instead when the ranges may need to wrap or go negative.
+    fn ranges_usize(&self) -> (Range<usize>, Range<usize>);
+}
+
+impl GridRanges for GridSquare {
+    fn ranges_usize(&self) -> (Range<usize>, Range<usize>) {
+        (self.min.x..self.max.x, self.min.y..self.max.y)
+    }
+}
+
+/// Trait used to get a printable version of a [`GridSquare`](`crate::data::GridSquare`), which can
+/// not implement [`std::fmt::Display`] itself since it is an alias of [`euclid::Box2D`].
+pub trait GridDisplay {
+    /// Returns a value that formats this square as `[(min_x,min_y)..(max_x,max_y) WxH]`, where the
+    /// max corner is exclusive.
+    fn display(&self) -> SquareDisplay;
+}
+
+/// Helper returned by [`GridDisplay::display`] that implements [`std::fmt::Display`] for a
+/// [`GridSquare`](`crate::data::GridSquare`).
+#[derive(Debug, Clone, Copy)]
+pub struct SquareDisplay(GridSquare);
+
+impl GridDisplay for GridSquare {
+    fn display(&self) -> SquareDisplay {
+        SquareDisplay(*self)
+    }
+}
+
+impl fmt::Display for SquareDisplay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "[({},{})..({},{}) {}x{}]",
+            self.0.min.x,
+            self.0.min.y,
+            self.0.max.x,
+            self.0.max.y,
+            self.0.width(),
+            self.0.height()
+        )
+    }
+}
+
+/// Trait used to check and fix up a [`GridPos`](`crate::data::GridPos`) against the bounds of a
+/// [`GridSize`](`crate::data::GridSize`).
+pub trait GridBounds {
+    /// Returns this position clamped so that `x < size.width` and `y < size.height`. Dimensions of
+    /// zero clamp that coordinate to 0.
+    #[must_use]
+    fn clamp_to(self, size: GridSize) -> Self;
+    /// Checks whether this position lies inside of a grid of the given size.
+    fn is_within(&self, size: GridSize) -> bool;
+}
+
+impl GridBounds for GridPos {
+    fn clamp_to(self, size: GridSize) -> Self {
+        GridPos::new(
+            self.x.min(size.width.saturating_sub(1)),
+            self.y.min(size.height.saturating_sub(1)),
+        )
+    }
+
+    fn is_within(&self, size: GridSize) -> bool {
+        self.x < size.width && self.y < size.height
+    }
+}
+
+/// Trait adding some convenience math to [`GridSize`](`crate::data::GridSize`).
+pub trait GridDimensions {
+    /// Gets the total number of cells (width * height).
+    fn area(&self) -> usize;
+    /// Gets the ratio of width to height, or 0 if the height is 0.
+    fn aspect_ratio(&self) -> f64;
+    /// Checks whether this size is wider than it is tall.
+    fn is_landscape(&self) -> bool;
+    /// Checks whether this size fits inside of `other`, meaning neither dimension is larger.
+    fn fits_within(&self, other: &Self) -> bool;
+}
+
+impl GridDimensions for GridSize {
+    fn area(&self) -> usize {
+        self.width * self.height
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn aspect_ratio(&self) -> f64 {
+        if self.height == 0 {
+            return 0.0;
+        }
+
+        self.width as f64 / self.height as f64
+    }
+
+    fn is_landscape(&self) -> bool {
+        self.width > self.height
+    }
+
+    fn fits_within(&self, other: &Self) -> bool {
+        self.width <= other.width && self.height <= other.height
+    }
+}
+
+/// One of the four cardinal directions, also used to refer to an edge of a [`crate::data::MapGrid`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Direction {
+    /// Up, towards the first row.
+    North,
+    /// Right, towards the last column.
+    East,
+    /// Down, towards the last row.
+    South,
+    /// Left, towards the first column.
+    West,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use assert_float_eq::{
+        afe_abs, afe_absolute_error_msg, afe_is_absolute_eq, assert_float_absolute_eq,
+    };
+
+    #[test]
+    fn size_positions() {
+        let positions: Vec<GridPos> = size(3, 2).positions().collect();
+        assert_eq!(positions.len(), 6);
+        assert_eq!(
+            positions,
+            vec![
+                pos((0, 0)),
+                pos((1, 0)),
+                pos((2, 0)),
+                pos((0, 1)),
+                pos((1, 1)),
+                pos((2, 1)),
+            ]
+        );
+
+        assert_eq!(size(10, 7).positions().count(), 70);
+        assert_eq!(size(0, 7).positions().count(), 0);
+    }
+
+    #[test]
+    fn size_dimensions() {
+        let wide = size(80, 15);
+        assert_eq!(GridDimensions::area(&wide), 1200);
+        assert_float_absolute_eq!(wide.aspect_ratio(), 5.333, 0.001);
+        assert!(wide.is_landscape());
+        assert!(!size(15, 80).is_landscape());
+        assert!(!size(10, 10).is_landscape());
+        assert_float_absolute_eq!(size(10, 0).aspect_ratio(), 0.0);
+
+        assert!(wide.fits_within(&size(80, 15)));
+        assert!(wide.fits_within(&size(81, 16)));
+        assert!(!wide.fits_within(&size(79, 15)));
+        assert!(!wide.fits_within(&size(80, 14)));
+    }
+
+    #[test]
+    fn square_ranges() {
+        let sq = square(&(1, 2), 7, 4);
+        let (xs, ys) = sq.ranges_usize();
+        assert_eq!(xs, sq.x_range());
+        assert_eq!(ys, sq.y_range());
+        assert_eq!((xs, ys), (1..8, 2..6));
+
+        let cast = sq.cast::<isize>();
+        assert_eq!((cast.x_range(), cast.y_range()), (1..8, 2..6));
+    }
+
+    #[test]
+    fn pos_bounds() {
+        let bounds = size(10, 10);
+        assert_eq!(pos((100, 100)).clamp_to(bounds), pos((9, 9)));
+        assert_eq!(pos((3, 42)).clamp_to(bounds), pos((3, 9)));
+        assert_eq!(pos((4, 5)).clamp_to(bounds), pos((4, 5)));
+        assert_eq!(pos((4, 5)).clamp_to(size(0, 0)), pos((0, 0)));
+
+        assert!(pos((9, 9)).is_within(bounds));
+        assert!(!pos((10, 9)).is_within(bounds));
+        assert!(!pos((9, 10)).is_within(bounds));
+        assert!(!pos((0, 0)).is_within(size(0, 5)));
+    }
+
+    #[test]
+    fn square_around() {
+        assert_eq!(square_radius(&(5, 5), 2), square(&(3, 3), 5, 5));
+        assert_eq!(square_radius(&(1, 0), 2), square(&(0, 0), 4, 3));
+        assert_eq!(square_radius(&(4, 4), 0), square(&(4, 4), 1, 1));
+    }
+
+    #[test]
+    fn square_display_and_serde() {
+        let sq = square(&(1, 1), 7, 7);
+        assert_eq!(sq.display().to_string(), "[(1,1)..(8,8) 7x7]");
+        assert_eq!(
+            format!("{}", square(&(0, 2), 3, 1).display()),
+            "[(0,2)..(3,3) 3x1]"
+        );
+
+        let json = serde_json::to_string(&sq).expect("Unable to serialize square");
+        let back: GridSquare = serde_json::from_str(&json).expect("Unable to deserialize square");
+        assert_eq!(back, sq);
+    }
+}
@@ -1,7 +1,11 @@
 use lazy_static::lazy_static;
 use tiny_skia::{Color, Paint, Pixmap, Rect, Transform};
 
-use crate::{data::MapGrid, util::TriState};
+use crate::{
+    data::{Cell, MapGrid},
+    draw::font::{text_pixels, text_width, GLYPH_HEIGHT},
+    util::TriState,
+};
 
 lazy_static! {
     /// ### Const reference to the color white.
@@ -36,6 +40,9 @@ lazy_static! {
     };
 }
 
+/// A single entry in an image legend, the label text and the (r, g, b, a) color it describes.
+pub type LegendEntry = (String, (u8, u8, u8, u8));
+
 enum Group {
     Fg,
     Bg,
@@ -150,4 +157,260 @@ impl Artist {
     ) -> Result<(), String> {
         Artist::draw_mapgrid(grid, out_file, 50, (255, 255, 255, 255), (0, 0, 0, 255))
     }
+
+    /// Draws a [`MapGrid`](`crate::data::MapGrid`) to a png file, coloring each cell using `color_fn`
+    /// and adding a labeled color key (the `legend`) below the grid.
+    ///
+    /// ### Arguments
+    /// - `grid` - The [`MapGrid`](`crate::data::MapGrid`) to draw.
+    /// - `file_name` - The name of the output file. This name will be prefixed with `output/` and suffixed with `.png`.
+    /// - `block_size` - The size of each block in the grid, default would be 50.
+    /// - `color_fn` - Function which is passed the position and cell, and returns the (r, g, b, a) color to draw it with.
+    /// - `legend` - The label and color of each entry in the color key, drawn one entry per line.
+    ///
+    /// ### Errors
+    /// - Function errors if the size of the image is too big to fit into a u32.
+    /// - Function errors if the [`PixMap`](`tiny_skia::pixmap::PixMap`) cannot be created.
+    /// - Function errors if the png cannot be saved.
+    pub fn draw_mapgrid_with_legend<S, F>(
+        grid: &MapGrid,
+        file_name: S,
+        block_size: u32,
+        color_fn: F,
+        legend: &[LegendEntry],
+    ) -> Result<(), String>
+    where
+        S: std::fmt::Display,
+        F: Fn((usize, usize), &Cell) -> (u8, u8, u8, u8),
+    {
+        Self::render_with_legend(grid, block_size, color_fn, legend)?
+            .save_png(format!("output/{}.png", file_name))
+            .map_err(|e| format!("Failed to save pixmap: {}", e))
+    }
+
+    /// Draws several [`MapGrid`](`crate::data::MapGrid`)s side by side in a single png file, each with its
+    /// label above it. Grids are laid out left to right in the given order, separated by a one block gap,
+    /// and shorter grids are padded with the background color. This is the image version of printing grids
+    /// side by side in the terminal.
+    ///
+    /// ### Arguments
+    /// - `grids` - The label and [`MapGrid`](`crate::data::MapGrid`) of each grid to draw.
+    /// - `file_name` - The name of the output file. This name will be prefixed with `output/` and suffixed with `.png`.
+    /// - `block_size` - The size of each block in the grids, default would be 50.
+    /// - `fg_color` - The (r, g, b, a) color of any blocks that are `on`, also used for the labels.
+    /// - `bg_color` - The (r, g, b, a) color of any blocks that are `off`, and of the padding.
+    ///
+    /// ### Errors
+    /// - Function errors if `grids` is empty.
+    /// - Function errors if the size of the image is too big to fit into a u32.
+    /// - Function errors if the [`PixMap`](`tiny_skia::pixmap::PixMap`) cannot be created.
+    /// - Function errors if the png cannot be saved.
+    pub fn draw_comparison<S: std::fmt::Display>(
+        grids: &[(&str, &MapGrid)],
+        file_name: S,
+        block_size: u32,
+        fg_color: (u8, u8, u8, u8),
+        bg_color: (u8, u8, u8, u8),
+    ) -> Result<(), String> {
+        Self::render_comparison(grids, block_size, fg_color, bg_color)?
+            .save_png(format!("output/{}.png", file_name))
+            .map_err(|e| format!("Failed to save pixmap: {}", e))
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn render_comparison(
+        grids: &[(&str, &MapGrid)],
+        block_size: u32,
+        fg_color: (u8, u8, u8, u8),
+        bg_color: (u8, u8, u8, u8),
+    ) -> Result<Pixmap, String> {
+        if grids.is_empty() {
+            return Err("No grids to draw!".to_string());
+        }
+
+        let too_big = |_| "Grid too big for u32".to_string();
+        let bsf = block_size as f32;
+        let font_scale = Self::legend_font_scale(block_size);
+        let fsf = font_scale as f32;
+        let label_height = (GLYPH_HEIGHT + 2) * font_scale;
+
+        let mut columns = Vec::with_capacity(grids.len());
+        let mut left = 0;
+        let mut grid_height = 0;
+        for &(label, grid) in grids {
+            let width = u32::try_from(grid.cols()).map_err(too_big)? * block_size;
+            let height = u32::try_from(grid.rows()).map_err(too_big)? * block_size;
+            columns.push(left);
+            left += width.max(text_width(label) * font_scale) + block_size;
+            grid_height = grid_height.max(height);
+        }
+
+        let total_width = left - block_size;
+        let mut pixmap = if let Some(p) = Pixmap::new(total_width, label_height + grid_height) {
+            p
+        } else {
+            return Err("Could not create pixmap!".to_string());
+        };
+
+        let (r, g, b, a) = bg_color;
+        pixmap.fill(Color::from_rgba8(r, g, b, a));
+
+        let mut fg = Paint::default();
+        fg.set_color_rgba8(fg_color.0, fg_color.1, fg_color.2, fg_color.3);
+        let mut bg = Paint::default();
+        bg.set_color_rgba8(r, g, b, a);
+        for (&(label, grid), &column) in grids.iter().zip(&columns) {
+            let left = column as f32;
+            for (px, py) in text_pixels(label) {
+                let (tx, ty) = (left + px as f32 * fsf, (py + 1) as f32 * fsf);
+                if let Some(rect) = Rect::from_xywh(tx, ty, fsf, fsf) {
+                    pixmap.fill_rect(rect, &fg, Transform::identity(), None);
+                }
+            }
+
+            for ((x, y), cell) in grid.iter_pos() {
+                let paint = match cell.state() {
+                    TriState::True => &fg,
+                    TriState::False => &bg,
+                    TriState::Invalid => Group::Error.paint(),
+                };
+                let (xf, yf) = (left + x as f32 * bsf, label_height as f32 + y as f32 * bsf);
+                if let Some(rect) = Rect::from_xywh(xf, yf, bsf, bsf) {
+                    pixmap.fill_rect(rect, paint, Transform::identity(), None);
+                }
+            }
+        }
+
+        Ok(pixmap)
+    }
+
+    /// Gets the size of a single font pixel used when drawing legend text for the given `block_size`.
+    fn legend_font_scale(block_size: u32) -> u32 {
+        (block_size / 8).max(1)
+    }
+
+    /// Gets the total height (in pixels) of a legend with `entries` lines for the given `block_size`.
+    fn legend_height(entries: usize, block_size: u32) -> Result<u32, String> {
+        let entries = u32::try_from(entries).map_err(|_| "Too many legend entries".to_string())?;
+
+        Ok(entries * (GLYPH_HEIGHT + 2) * Self::legend_font_scale(block_size))
+    }
+
+    #[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation)]
+    fn render_with_legend<F>(
+        grid: &MapGrid,
+        block_size: u32,
+        color_fn: F,
+        legend: &[LegendEntry],
+    ) -> Result<Pixmap, String>
+    where
+        F: Fn((usize, usize), &Cell) -> (u8, u8, u8, u8),
+    {
+        let too_big = |_| "Grid too big for u32".to_string();
+        let bsf = block_size as f32;
+        let font_scale = Self::legend_font_scale(block_size);
+        let fsf = font_scale as f32;
+        let row_height = (GLYPH_HEIGHT + 2) * font_scale;
+        let grid_width = u32::try_from(grid.cols()).map_err(too_big)? * block_size;
+        let grid_height = u32::try_from(grid.rows()).map_err(too_big)? * block_size;
+        let legend_width = legend
+            .iter()
+            .map(|(label, _)| (text_width(label) + 8) * font_scale)
+            .max()
+            .unwrap_or(0);
+
+        let mut pixmap = if let Some(p) = Pixmap::new(
+            grid_width.max(legend_width),
+            grid_height + Self::legend_height(legend.len(), block_size)?,
+        ) {
+            p
+        } else {
+            return Err("Could not create pixmap!".to_string());
+        };
+
+        pixmap.fill(*BLACK_COLOR);
+
+        let mut paint = Paint::default();
+        for ((x, y), cell) in grid.iter_pos() {
+            let (r, g, b, a) = color_fn((x, y), cell);
+            paint.set_color_rgba8(r, g, b, a);
+            if let Some(rect) = Rect::from_xywh(x as f32 * bsf, y as f32 * bsf, bsf, bsf) {
+                pixmap.fill_rect(rect, &paint, Transform::identity(), None);
+            }
+        }
+
+        for (i, (label, (r, g, b, a))) in legend.iter().enumerate() {
+            let top = (grid_height + i as u32 * row_height) as f32;
+            paint.set_color_rgba8(*r, *g, *b, *a);
+            if let Some(rect) = Rect::from_xywh(fsf, top + fsf, fsf * 5.0, fsf * 5.0) {
+                pixmap.fill_rect(rect, &paint, Transform::identity(), None);
+            }
+
+            for (px, py) in text_pixels(label) {
+                let (tx, ty) = ((px + 7) as f32 * fsf, top + (py + 1) as f32 * fsf);
+                if let Some(rect) = Rect::from_xywh(tx, ty, fsf, fsf) {
+                    pixmap.fill_rect(rect, &WHITE_PAINT, Transform::identity(), None);
+                }
+            }
+        }
+
+        Ok(pixmap)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::util::testing::crate_before_test;
+
+    #[test]
+    fn legend_adds_height() {
+        crate_before_test();
+
+        let grid = MapGrid::random((8, 6));
+        let legend = vec![
+            ("Wall".to_string(), (255, 255, 255, 255)),
+            ("Floor".to_string(), (0, 0, 0, 255)),
+            ("Path 50%".to_string(), (255, 0, 0, 255)),
+        ];
+        let color_fn = |_, cell: &Cell| {
+            if cell.is_on() {
+                (255, 255, 255, 255)
+            } else {
+                (0, 0, 0, 255)
+            }
+        };
+
+        let plain = Artist::render_with_legend(&grid, 10, color_fn, &[]).unwrap();
+        assert_eq!((plain.width(), plain.height()), (80, 60));
+
+        let with_legend = Artist::render_with_legend(&grid, 10, color_fn, &legend).unwrap();
+        let legend_height = Artist::legend_height(legend.len(), 10).unwrap();
+        assert!(legend_height > 0);
+        assert_eq!(with_legend.height(), plain.height() + legend_height);
+        assert_eq!(with_legend.width(), plain.width());
+    }
+
+    #[test]
+    fn comparison_lays_out_in_a_row() {
+        crate_before_test();
+
+        let first = MapGrid::random((8, 6));
+        let second = MapGrid::random((5, 4));
+        let fg = (255, 255, 255, 255);
+        let bg = (0, 0, 0, 255);
+
+        let pixmap =
+            Artist::render_comparison(&[("Before", &first), ("After", &second)], 10, fg, bg)
+                .unwrap();
+        let label_height = Artist::legend_height(1, 10).unwrap();
+        assert_eq!(pixmap.width(), 80 + 10 + 50);
+        assert_eq!(pixmap.height(), label_height + 60);
+
+        let single = Artist::render_comparison(&[("Only", &second)], 10, fg, bg).unwrap();
+        assert_eq!((single.width(), single.height()), (50, label_height + 40));
+
+        assert!(Artist::render_comparison(&[], 10, fg, bg).is_err());
+    }
 }
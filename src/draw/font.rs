@@ -0,0 +1,96 @@
+/// The width (in font pixels) of a single glyph.
+pub const GLYPH_WIDTH: u32 = 3;
+/// The height (in font pixels) of a single glyph.
+pub const GLYPH_HEIGHT: u32 = 5;
+/// The horizontal distance (in font pixels) between the start of two consecutive glyphs.
+pub const GLYPH_ADVANCE: u32 = GLYPH_WIDTH + 1;
+
+/// Gets the 3x5 bitmap for the given character. Each entry is a row (top to bottom) and the three
+/// lowest bits of each row are the pixels (left to right, highest bit first).
+///
+/// Lowercase letters are drawn as uppercase, and any unsupported character is drawn as a blank space.
+#[must_use]
+pub fn glyph(ch: char) -> [u8; 5] {
+    match ch.to_ascii_uppercase() {
+        'A' => [0b010, 0b101, 0b111, 0b101, 0b101],
+        'B' => [0b110, 0b101, 0b110, 0b101, 0b110],
+        'C' => [0b011, 0b100, 0b100, 0b100, 0b011],
+        'D' => [0b110, 0b101, 0b101, 0b101, 0b110],
+        'E' => [0b111, 0b100, 0b110, 0b100, 0b111],
+        'F' => [0b111, 0b100, 0b110, 0b100, 0b100],
+        'G' => [0b011, 0b100, 0b101, 0b101, 0b011],
+        'H' => [0b101, 0b101, 0b111, 0b101, 0b101],
+        'I' => [0b111, 0b010, 0b010, 0b010, 0b111],
+        'J' => [0b001, 0b001, 0b001, 0b101, 0b010],
+        'K' => [0b101, 0b101, 0b110, 0b101, 0b101],
+        'L' => [0b100, 0b100, 0b100, 0b100, 0b111],
+        'M' => [0b101, 0b111, 0b111, 0b101, 0b101],
+        'N' => [0b110, 0b101, 0b101, 0b101, 0b101],
+        'O' => [0b010, 0b101, 0b101, 0b101, 0b010],
+        'P' => [0b110, 0b101, 0b110, 0b100, 0b100],
+        'Q' => [0b010, 0b101, 0b101, 0b110, 0b011],
+        'R' => [0b110, 0b101, 0b110, 0b101, 0b101],
+        'S' => [0b011, 0b100, 0b010, 0b001, 0b110],
+        'T' => [0b111, 0b010, 0b010, 0b010, 0b010],
+        'U' => [0b101, 0b101, 0b101, 0b101, 0b111],
+        'V' => [0b101, 0b101, 0b101, 0b101, 0b010],
+        'W' => [0b101, 0b101, 0b111, 0b111, 0b101],
+        'X' => [0b101, 0b101, 0b010, 0b101, 0b101],
+        'Y' => [0b101, 0b101, 0b010, 0b010, 0b010],
+        'Z' => [0b111, 0b001, 0b010, 0b100, 0b111],
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b110, 0b001, 0b010, 0b100, 0b111],
+        '3' => [0b110, 0b001, 0b010, 0b001, 0b110],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b110, 0b001, 0b110],
+        '6' => [0b011, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b010, 0b010, 0b010],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b110],
+        '.' => [0b000, 0b000, 0b000, 0b000, 0b010],
+        ',' => [0b000, 0b000, 0b000, 0b010, 0b100],
+        ':' => [0b000, 0b010, 0b000, 0b010, 0b000],
+        '-' => [0b000, 0b000, 0b111, 0b000, 0b000],
+        '_' => [0b000, 0b000, 0b000, 0b000, 0b111],
+        '+' => [0b000, 0b010, 0b111, 0b010, 0b000],
+        '/' => [0b001, 0b001, 0b010, 0b100, 0b100],
+        '%' => [0b101, 0b001, 0b010, 0b100, 0b101],
+        '(' => [0b010, 0b100, 0b100, 0b100, 0b010],
+        ')' => [0b010, 0b001, 0b001, 0b001, 0b010],
+        '#' => [0b101, 0b111, 0b101, 0b111, 0b101],
+        '!' => [0b010, 0b010, 0b010, 0b000, 0b010],
+        '?' => [0b110, 0b001, 0b010, 0b000, 0b010],
+        '\'' => [0b010, 0b010, 0b000, 0b000, 0b000],
+        _ => [0; 5],
+    }
+}
+
+/// Gets the width (in font pixels) of `text` when drawn with [`glyph`].
+#[must_use]
+pub fn text_width(text: &str) -> u32 {
+    match u32::try_from(text.chars().count()) {
+        Ok(0) | Err(_) => 0,
+        Ok(count) => count * GLYPH_ADVANCE - 1,
+    }
+}
+
+/// Gets the position (in font pixels, relative to the top left of the text) of every lit pixel
+/// when `text` is drawn on a single line.
+#[must_use]
+pub fn text_pixels(text: &str) -> Vec<(u32, u32)> {
+    let mut pixels = Vec::new();
+    let mut x_offset = 0;
+    for ch in text.chars() {
+        for (y, row) in (0u32..).zip(glyph(ch)) {
+            for x in 0..GLYPH_WIDTH {
+                if row & (0b100 >> x) != 0 {
+                    pixels.push((x_offset + x, y));
+                }
+            }
+        }
+        x_offset += GLYPH_ADVANCE;
+    }
+
+    pixels
+}
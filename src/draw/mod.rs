@@ -2,4 +2,8 @@
 /// Contains functions for creating and drawing to png files and other outputs.
 mod artist;
 
-pub use artist::Artist;
+/// ## `Font` Module
+/// Contains a tiny bitmap font used for drawing text (labels, legends, etc.) into images.
+mod font;
+
+pub use artist::{Artist, LegendEntry};
@@ -1,5 +1,5 @@
 use crate::{
-    data::MapGrid,
+    data::{GridPos, GridSize, MapGrid},
     logging::{trace, warn},
 };
 
@@ -27,6 +27,42 @@ pub struct Flex2Args {
     predicate: fn((usize, usize), usize, usize, bool) -> bool,
 }
 
+/// The arguments for a "life-like" cellular automata rule, such as Conway's Game of Life (`B3/S23`),
+/// where a cell is born or survives depending only on its exact number of active neighbors. This should
+/// be created by calling [`Algorithm::conway`], [`Algorithm::life_like`] or
+/// [`Algorithm::life_like_wrapped`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LifeLikeArgs {
+    born: u16,
+    survive: u16,
+    wrap_edges: bool,
+}
+
+impl LifeLikeArgs {
+    /// Converts a list of neighbor counts into a bit mask, ignoring any count above 8.
+    fn mask(counts: &[u8]) -> u16 {
+        counts.iter().fold(0, |mask, &n| {
+            if n > 8 {
+                warn!(
+                    "LifeLikeArgs::mask - Ignoring impossible neighbor count {}",
+                    n
+                );
+                mask
+            } else {
+                mask | (1 << n)
+            }
+        })
+    }
+
+    /// The rule described by these arguments, in the form used by [`CellularAutomata::flexible`].
+    fn rule(self) -> impl Fn((usize, usize), usize, bool) -> bool {
+        move |_, n, s| {
+            let mask = if s { self.survive } else { self.born };
+            n <= 8 && mask & (1 << n) != 0
+        }
+    }
+}
+
 /// This enum is used to pass arguments to the [`CellularAutomata`] runner.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Algorithm {
@@ -36,6 +72,8 @@ pub enum Algorithm {
     Flex(FlexArgs),
     /// The second version arguments. See [`Flex2Args`].
     Flex2(Flex2Args),
+    /// The life-like rule arguments. See [`LifeLikeArgs`].
+    LifeLike(LifeLikeArgs),
 }
 
 impl Algorithm {
@@ -66,6 +104,56 @@ impl Algorithm {
     pub fn flex2(predicate: fn((usize, usize), usize, usize, bool) -> bool) -> Self {
         Self::Flex2(Flex2Args { predicate })
     }
+
+    /// Conway's Game of Life (`B3/S23`): a dead cell with exactly 3 active neighbors is born, and a
+    /// live cell with 2 or 3 active neighbors survives. Neighbors are not wrapped around the edges.
+    #[must_use]
+    pub fn conway() -> Self {
+        Self::life_like(&[3], &[2, 3])
+    }
+
+    /// A life-like rule given by its rulestring, e.g. `B36/S23` is `life_like(&[3, 6], &[2, 3])`. An
+    /// off cell turns on if its number of active neighbors is in `born`, and an on cell stays on if its
+    /// number of active neighbors is in `survive`. Neighbors are not wrapped around the edges.
+    #[must_use]
+    pub fn life_like(born: &[u8], survive: &[u8]) -> Self {
+        Self::LifeLike(LifeLikeArgs {
+            born: LifeLikeArgs::mask(born),
+            survive: LifeLikeArgs::mask(survive),
+            wrap_edges: false,
+        })
+    }
+
+    /// Same as [`Algorithm::life_like`], but neighbors wrap around the edges of the grid.
+    #[must_use]
+    pub fn life_like_wrapped(born: &[u8], survive: &[u8]) -> Self {
+        Self::LifeLike(LifeLikeArgs {
+            born: LifeLikeArgs::mask(born),
+            survive: LifeLikeArgs::mask(survive),
+            wrap_edges: true,
+        })
+    }
+}
+
+/// Describes which cell state a grid uses for walls, so that [`CellularAutomata`] rules are always
+/// applied the same way no matter where the grid came from.
+///
+/// Every rule counts *walls*: the neighbor counts passed to the predicates are the number of
+/// surrounding walls, and a predicate returning true makes the cell a wall. The library's own
+/// convention is that walls are on (`#`) and floors are off (`.`), which is the [`Default`]. Grids that
+/// use on for floors (like those from [`crate::gen::room_based::RoomBased`]) should be run with
+/// `wall_is_on` set to false instead of being [reversed](`MapGrid::reverse`) beforehand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CaConvention {
+    /// Whether on cells are walls (true) or floors (false).
+    pub wall_is_on: bool,
+}
+
+impl Default for CaConvention {
+    /// Creates the default [`CaConvention`], where walls are on.
+    fn default() -> Self {
+        Self { wall_is_on: true }
+    }
 }
 
 /// Static struct holding cellular automata algorithms.
@@ -81,13 +169,116 @@ impl CellularAutomata {
             alg_args
         );
 
-        match alg_args {
+        let mut grid = MapGrid::create_copy(original);
+        for _ in 0..passes {
+            grid = Self::step(&grid, &alg_args);
+        }
+
+        grid
+    }
+
+    /// Runs passes of the indicated algorithm on `original` one at a time until a pass no longer changes
+    /// the grid or `max_iters` passes have been executed, whichever comes first. Returns the final grid
+    /// along with the number of passes that changed it, so a grid that is already stable is returned
+    /// as is with a count of 0. Only the cells are compared, and the result keeps the name of `original`.
+    #[must_use]
+    pub fn execute_until_stable(
+        original: &MapGrid,
+        max_iters: usize,
+        alg_args: Algorithm,
+    ) -> (MapGrid, usize) {
+        trace!(
+            "CellularAutomata::execute_until_stable(Grid,{}, {:?})",
+            max_iters,
+            alg_args
+        );
+
+        let mut grid = original.clone();
+        for iteration in 0..max_iters {
+            let mut next = Self::step(&grid, &alg_args);
+            if next.iter_states().eq(grid.iter_states()) {
+                return (grid, iteration);
+            }
+
+            next.name_ref_mut().clone_from(grid.name_ref());
+            grid = next;
+        }
+
+        (grid, max_iters)
+    }
+
+    /// Same as [`CellularAutomata::execute_on`], but interprets the cells of `original` using the given
+    /// [`CaConvention`]. The result uses the same convention as `original`.
+    #[must_use]
+    pub fn execute_with_convention(
+        original: &MapGrid,
+        passes: usize,
+        alg_args: Algorithm,
+        convention: CaConvention,
+    ) -> MapGrid {
+        trace!(
+            "CellularAutomata::execute_with_convention(Grid,{}, {:?}, {:?})",
+            passes,
+            alg_args,
+            convention
+        );
+
+        if convention.wall_is_on {
+            return Self::execute_on(original, passes, alg_args);
+        }
+
+        let mut walls_on = original.clone();
+        walls_on.reverse_in_place();
+        let mut grid = Self::execute_on(&walls_on, passes, alg_args);
+        grid.reverse_in_place();
+
+        grid
+    }
+
+    /// Runs a single pass of the indicated algorithm on `grid`, returning the result. Calling this
+    /// `n` times is equivalent to [`CellularAutomata::execute_on`] with `n` passes, which makes it
+    /// suitable for stepping through the algorithm interactively.
+    #[must_use]
+    pub fn step(grid: &MapGrid, algorithm: &Algorithm) -> MapGrid {
+        trace!("CellularAutomata::step(Grid, {:?})", algorithm);
+        match algorithm {
             Algorithm::First(faa) => {
-                Self::first(original, passes, false, faa.on_min, faa.off_min).0
+                Self::flexible_pass(grid, true, Self::first_rule(faa.on_min, faa.off_min))
+            }
+            Algorithm::Flex(f) => Self::flexible_pass(grid, true, f.predicate),
+            Algorithm::Flex2(f2) => Self::flexible2_pass(grid, f2.predicate),
+            Algorithm::LifeLike(l) => Self::flexible_pass(grid, l.wrap_edges, l.rule()),
+        }
+    }
+
+    /// Executes the indicated algorithm on the provided map for the given number of passes, without
+    /// ever modifying the cells at the positions in `protect`. Protected cells still influence the
+    /// state of their neighbors and are simply restored after each pass.
+    #[must_use]
+    pub fn execute_on_masked(
+        original: &MapGrid,
+        passes: usize,
+        alg_args: Algorithm,
+        protect: &[GridPos],
+    ) -> MapGrid {
+        trace!(
+            "CellularAutomata::execute_on_masked(Grid,{}, {:?}, {} protected)",
+            passes,
+            alg_args,
+            protect.len()
+        );
+
+        let mut grid = MapGrid::create_copy(original);
+        for _ in 0..passes {
+            grid = Self::execute_on(&grid, 1, alg_args);
+            for pos in protect {
+                if let Some(&cell) = original.cell(*pos) {
+                    grid.set_cell(pos.x, pos.y, cell);
+                }
             }
-            Algorithm::Flex(f) => Self::flexible(original, passes, false, &f.predicate).0,
-            Algorithm::Flex2(f2) => Self::flexible2(original, passes, false, &f2.predicate).0,
         }
+
+        grid
     }
 
     /// Executes the first cellular automata method, returning the final product
@@ -105,8 +296,11 @@ impl CellularAutomata {
         );
         match alg_args {
             Algorithm::First(ffa) => Self::first(original, passes, true, ffa.on_min, ffa.off_min),
-            Algorithm::Flex(f) => Self::flexible(original, passes, true, f.predicate),
+            Algorithm::Flex(f) => Self::flexible(original, passes, true, true, f.predicate),
             Algorithm::Flex2(f2) => Self::flexible2(original, passes, true, &f2.predicate),
+            Algorithm::LifeLike(l) => {
+                Self::flexible(original, passes, true, l.wrap_edges, l.rule())
+            }
         }
     }
 
@@ -131,13 +325,34 @@ impl CellularAutomata {
 
         let (last, history) = match alg_args {
             Algorithm::First(ffa) => Self::first(&original, passes, false, ffa.on_min, ffa.off_min),
-            Algorithm::Flex(f) => Self::flexible(&original, passes, false, f.predicate),
+            Algorithm::Flex(f) => Self::flexible(&original, passes, false, true, f.predicate),
             Algorithm::Flex2(f2) => Self::flexible2(&original, passes, false, f2.predicate),
+            Algorithm::LifeLike(l) => {
+                Self::flexible(&original, passes, false, l.wrap_edges, l.rule())
+            }
         };
 
         (original, last, history)
     }
 
+    /// Creates a new random grid of the given size with `fill` percent of its cells on (clamped to
+    /// `0.0..=1.0`, see [`MapGrid::random_fill_percent`]) and runs `iterations` passes of `alg` on it,
+    /// returning the final grid. With 0 iterations the random grid is returned as is. The global
+    /// [`fastrand`] RNG is used, so results can be reproduced with
+    /// [`init_rng_seeded`](`crate::util::random::init_rng_seeded`).
+    #[must_use]
+    pub fn generate(size: GridSize, fill: f64, iterations: usize, alg: Algorithm) -> MapGrid {
+        trace!(
+            "CellularAutomata::generate({:?}, {}, {}, {:?})",
+            size,
+            fill,
+            iterations,
+            alg
+        );
+
+        Self::execute_on(&MapGrid::random_fill_percent(size, fill), iterations, alg)
+    }
+
     fn first(
         grid: &MapGrid,
         passes: usize,
@@ -145,13 +360,28 @@ impl CellularAutomata {
         on_minimum: usize,
         off_minimum: usize,
     ) -> (MapGrid, Vec<MapGrid>) {
-        Self::flexible(grid, passes, track_changes, |_, n, s| {
+        Self::flexible(
+            grid,
+            passes,
+            track_changes,
+            true,
+            Self::first_rule(on_minimum, off_minimum),
+        )
+    }
+
+    /// The rule used by the basic algorithm, a cell is turned on if it has at least `on_minimum`
+    /// active neighbors while on, or at least `off_minimum` while off.
+    fn first_rule(
+        on_minimum: usize,
+        off_minimum: usize,
+    ) -> impl Fn((usize, usize), usize, bool) -> bool {
+        move |_, n, s| {
             if s {
                 n >= on_minimum
             } else {
                 n >= off_minimum
             }
-        })
+        }
     }
 
     /// Flexible Cellular Automata algorithm that iterates over each cell in the given grid
@@ -166,12 +396,16 @@ impl CellularAutomata {
     /// - The number of active neighbors to the cell
     /// - The current state of the cell
     ///
+    /// Active neighbors are counted by wrapping around the edges of the grid if [`wrap_edges`] is
+    /// true.
+    ///
     /// The returned tuple contains the final grid, as well as the complete history of each
     /// iteration **if [`track_changes`] is true**, otherwise it will be an empty [Vec].
     fn flexible<StateFunc>(
         original: &MapGrid,
         passes: usize,
         track_changes: bool,
+        wrap_edges: bool,
         mut predicate: StateFunc,
     ) -> (MapGrid, Vec<MapGrid>)
     where
@@ -196,27 +430,7 @@ impl CellularAutomata {
 
         for p in 0..passes {
             trace!("CellularAutomata::flexible pass #{}/{}", p + 1, passes);
-            let mut temp = MapGrid::create_copy(&grid);
-
-            for x in 0..grid.cols() {
-                for y in 0..grid.rows() {
-                    if let Some(cell) = grid.cell((x, y)) {
-                        let cell_state: bool = cell.state().into();
-                        let neighbors = grid.active_neighbor_count((x, y), true);
-
-                        let new_state = predicate((x, y), neighbors, cell_state);
-
-                        temp.set_cell_state(x, y, new_state);
-                    } else {
-                        warn!(
-                            "CellularAutomata::flexible Invalid cell found at ({}, {})",
-                            x, y
-                        );
-                    }
-                }
-            }
-
-            grid = temp;
+            grid = Self::flexible_pass(&grid, wrap_edges, &mut predicate);
             if track_changes {
                 history.push(MapGrid::create_copy(&grid));
             }
@@ -267,28 +481,7 @@ impl CellularAutomata {
 
         for p in 0..passes {
             trace!("CellularAutomata::flexible pass #{}/{}", p + 1, passes);
-            let mut temp = MapGrid::create_copy(&grid);
-
-            for x in 0..grid.cols() {
-                for y in 0..grid.rows() {
-                    if let Some(cell) = grid.cell((x, y)) {
-                        let cell_state: bool = cell.state().into();
-                        let n = grid.active_neighbor_count((x, y), true);
-                        let n2 = grid.active_neighbors_n(x, y, 2);
-
-                        let new_state = predicate((x, y), n, n2, cell_state);
-
-                        temp.set_cell_state(x, y, new_state);
-                    } else {
-                        warn!(
-                            "CellularAutomata::flexible Invalid cell found at ({}, {})",
-                            x, y
-                        );
-                    }
-                }
-            }
-
-            grid = temp;
+            grid = Self::flexible2_pass(&grid, &mut predicate);
             if track_changes {
                 history.push(MapGrid::create_copy(&grid));
             }
@@ -296,6 +489,67 @@ impl CellularAutomata {
 
         (grid, history)
     }
+
+    /// A single pass of [`CellularAutomata::flexible`], see there for details.
+    fn flexible_pass<StateFunc>(
+        grid: &MapGrid,
+        wrap_edges: bool,
+        mut predicate: StateFunc,
+    ) -> MapGrid
+    where
+        StateFunc: FnMut((usize, usize), usize, bool) -> bool,
+    {
+        let mut temp = MapGrid::create_copy(grid);
+
+        for x in 0..grid.cols() {
+            for y in 0..grid.rows() {
+                if let Some(cell) = grid.cell((x, y)) {
+                    let cell_state: bool = cell.state().into();
+                    let neighbors = grid.active_neighbor_count((x, y), wrap_edges);
+
+                    let new_state = predicate((x, y), neighbors, cell_state);
+
+                    temp.set_cell_state(x, y, new_state);
+                } else {
+                    warn!(
+                        "CellularAutomata::flexible Invalid cell found at ({}, {})",
+                        x, y
+                    );
+                }
+            }
+        }
+
+        temp
+    }
+
+    /// A single pass of [`CellularAutomata::flexible2`], see there for details.
+    fn flexible2_pass<StateFunc>(grid: &MapGrid, mut predicate: StateFunc) -> MapGrid
+    where
+        StateFunc: FnMut((usize, usize), usize, usize, bool) -> bool,
+    {
+        let mut temp = MapGrid::create_copy(grid);
+
+        for x in 0..grid.cols() {
+            for y in 0..grid.rows() {
+                if let Some(cell) = grid.cell((x, y)) {
+                    let cell_state: bool = cell.state().into();
+                    let n = grid.active_neighbor_count((x, y), true);
+                    let n2 = grid.active_neighbors_n(x, y, 2);
+
+                    let new_state = predicate((x, y), n, n2, cell_state);
+
+                    temp.set_cell_state(x, y, new_state);
+                } else {
+                    warn!(
+                        "CellularAutomata::flexible Invalid cell found at ({}, {})",
+                        x, y
+                    );
+                }
+            }
+        }
+
+        temp
+    }
 }
 
 #[cfg(test)]
@@ -314,4 +568,195 @@ mod tests {
         let result = CellularAutomata::execute_on(&original, 1, Algorithm::first(4, 5));
         assert_eq!(result.to_strings().join("\n"), "...\n...\n...");
     }
+
+    #[test]
+    fn ca_masked() {
+        crate_before_test();
+
+        let original = MapGrid::parse_string(".....\n.....\n..#..\n.....\n.....", '#', '.')
+            .expect("Unable to parse standard grid string");
+        let result = CellularAutomata::execute_on_masked(
+            &original,
+            3,
+            Algorithm::first(4, 5),
+            &[GridPos::new(2, 2)],
+        );
+        assert!(result.cell((2, 2)).unwrap().is_on());
+        assert_eq!(result.on_cells_count(), 1);
+    }
+
+    #[test]
+    fn ca_step() {
+        crate_before_test();
+
+        let original = MapGrid::random_fill_percent((20, 15), 0.45);
+        let algorithms = [
+            Algorithm::default_first(),
+            Algorithm::flex(|_, n, s| n >= 5 || (s && n >= 4)),
+            Algorithm::flex2(|_, n, n2, _| n >= 5 || n2 <= 2),
+        ];
+        for algorithm in algorithms {
+            let mut stepped = MapGrid::create_copy(&original);
+            for _ in 0..4 {
+                stepped = CellularAutomata::step(&stepped, &algorithm);
+            }
+
+            let executed = CellularAutomata::execute_on(&original, 4, algorithm);
+            let (tracked, history) =
+                CellularAutomata::execute_with_history(&original, 4, algorithm);
+            assert_eq!(stepped.to_strings(), executed.to_strings());
+            assert_eq!(stepped.to_strings(), tracked.to_strings());
+            assert_eq!(history.len(), 5);
+        }
+    }
+
+    #[test]
+    fn ca_convention() {
+        crate_before_test();
+
+        let floors_on = MapGrid::random_fill_percent((20, 15), 0.55);
+        let algorithm = Algorithm::default_first();
+        let reversed = MapGrid::reverse(&CellularAutomata::execute_on(
+            &MapGrid::reverse(&floors_on),
+            3,
+            algorithm,
+        ));
+        let converted = CellularAutomata::execute_with_convention(
+            &floors_on,
+            3,
+            algorithm,
+            CaConvention { wall_is_on: false },
+        );
+        assert_eq!(reversed.to_strings(), converted.to_strings());
+
+        let walls_on = MapGrid::reverse(&floors_on);
+        assert_eq!(
+            CellularAutomata::execute_with_convention(
+                &walls_on,
+                3,
+                algorithm,
+                CaConvention::default()
+            )
+            .to_strings(),
+            CellularAutomata::execute_on(&walls_on, 3, algorithm).to_strings()
+        );
+
+        // A horizontal "blinker" of walls in a grid where floors are on. Under Conway's rules only the
+        // walls are alive, so the blinker turns vertical and every floor cell stays a floor.
+        let blinker = MapGrid::parse_string(".....\n.....\n.###.\n.....\n.....", '.', '#')
+            .expect("Unable to parse standard grid string");
+        let result = CellularAutomata::execute_with_convention(
+            &blinker,
+            1,
+            Algorithm::conway(),
+            CaConvention { wall_is_on: false },
+        );
+        assert_eq!(
+            result.to_strings_with('.', '#'),
+            vec![".....", "..#..", "..#..", "..#..", "....."]
+        );
+    }
+
+    #[test]
+    fn ca_generate() {
+        crate_before_test();
+
+        let size = crate::data::size(30, 20);
+        crate::util::random::init_rng_seeded(5);
+        let raw = MapGrid::random_fill_percent(size, 0.45);
+        crate::util::random::init_rng_seeded(5);
+        let generated = CellularAutomata::generate(size, 0.45, 0, Algorithm::default_first());
+        assert_eq!(generated.to_strings(), raw.to_strings());
+
+        crate::util::random::init_rng_seeded(5);
+        let generated = CellularAutomata::generate(size, 0.45, 3, Algorithm::default_first());
+        let expected = CellularAutomata::execute_on(&raw, 3, Algorithm::default_first());
+        assert_eq!(generated.to_strings(), expected.to_strings());
+
+        let full = CellularAutomata::generate(size, 1.5, 0, Algorithm::default_first());
+        assert_eq!(full.on_cells_count(), 600);
+        let empty = CellularAutomata::generate(size, -1.0, 0, Algorithm::default_first());
+        assert_eq!(empty.on_cells_count(), 0);
+    }
+
+    #[test]
+    fn ca_until_stable() {
+        crate_before_test();
+
+        let block =
+            MapGrid::parse_string("......\n......\n..##..\n..##..\n......\n......", '#', '.')
+                .expect("Unable to parse standard grid string");
+        let (result, iterations) =
+            CellularAutomata::execute_until_stable(&block, 10, Algorithm::conway());
+        assert_eq!(iterations, 0);
+        assert_eq!(result.to_strings(), block.to_strings());
+
+        crate::util::random::init_rng_seeded(11);
+        let noise = MapGrid::random_fill_percent(crate::data::size(30, 20), 0.45);
+        let (result, iterations) =
+            CellularAutomata::execute_until_stable(&noise, 50, Algorithm::default_first());
+        assert!(iterations > 0 && iterations < 50);
+        let expected = CellularAutomata::execute_on(&noise, iterations, Algorithm::default_first());
+        assert_eq!(result.to_strings(), expected.to_strings());
+        let next = CellularAutomata::step(&result, &Algorithm::default_first());
+        assert_eq!(next.to_strings(), result.to_strings());
+
+        let mut named =
+            MapGrid::parse_string("......\n......\n..##..\n..##..\n......\n......", '#', '.')
+                .expect("Unable to parse standard grid string");
+        named.set_name("Block");
+        let (stable, stable_iterations) =
+            CellularAutomata::execute_until_stable(&named, 10, Algorithm::conway());
+        assert_eq!(stable_iterations, 0);
+        assert_eq!(stable, named);
+
+        let mut named_noise = noise.clone();
+        named_noise.set_name("Noise");
+        let (named_result, named_iterations) =
+            CellularAutomata::execute_until_stable(&named_noise, 50, Algorithm::default_first());
+        assert_eq!(named_iterations, iterations);
+        assert_eq!(named_result.name_ref().as_deref(), Some("Noise"));
+        assert_eq!(named_result.to_strings(), expected.to_strings());
+
+        let blinker = MapGrid::parse_string(".....\n.....\n.###.\n.....\n.....", '#', '.')
+            .expect("Unable to parse standard grid string");
+        let (_, iterations) =
+            CellularAutomata::execute_until_stable(&blinker, 7, Algorithm::conway());
+        assert_eq!(iterations, 7);
+    }
+
+    #[test]
+    fn ca_conway_glider() {
+        crate_before_test();
+
+        let glider = |dx: usize, dy: usize| {
+            let mut grid = MapGrid::empty((8, 8));
+            for (x, y) in [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)] {
+                grid.set_cell_state(x + dx, y + dy, true);
+            }
+            grid
+        };
+
+        let start = glider(0, 0);
+        let (last, history) =
+            CellularAutomata::execute_with_history(&start, 4, Algorithm::conway());
+        assert_eq!(history.len(), 5);
+        assert!(history[1..4]
+            .iter()
+            .all(|g| g.to_strings() != start.to_strings()));
+        assert_eq!(last.to_strings(), glider(1, 1).to_strings());
+        assert_eq!(
+            CellularAutomata::execute_on(&start, 4, Algorithm::life_like(&[3], &[2, 3]))
+                .to_strings(),
+            last.to_strings()
+        );
+
+        // Without wrapping a glider dies against the corner, with wrapping it comes back around
+        let corner = glider(5, 5);
+        let unwrapped = CellularAutomata::execute_on(&corner, 12, Algorithm::conway());
+        assert_ne!(unwrapped.on_cells_count(), 5);
+        let wrapped =
+            CellularAutomata::execute_on(&corner, 12, Algorithm::life_like_wrapped(&[3], &[2, 3]));
+        assert_eq!(wrapped.on_cells_count(), 5);
+    }
 }
@@ -10,3 +10,7 @@ pub mod room_based;
 ///
 /// Contains the data-types for the [`crate::gen::RoomBasedGenerator`].
 pub mod rooms;
+/// `WaveFunctionCollapse` Generation Module
+///
+/// Contains the [`crate::gen::wfc::WaveFunctionCollapse`] generator.
+pub mod wfc;
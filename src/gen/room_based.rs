@@ -2,7 +2,7 @@ use std::{collections::HashMap, ops::Range};
 
 use crate::{
     data::{GridPos, GridSize, MapGrid},
-    gen::rooms::{Room, RoomSize},
+    gen::rooms::{Room, RoomGraph, RoomSize},
     logging::{info, trace, warn},
     util::math::get_curve_between,
 };
@@ -292,6 +292,26 @@ impl From<usize> for GridClassification {
     }
 }
 
+/// Options controlling how [`RoomBased::tiered_with_options`] connects rooms.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TieredOptions {
+    /// The width (in cells) of the corridors carved between rooms. Values below 1 are treated as 1.
+    pub corridor_width: usize,
+    /// The chance (0.0 to 1.0) that two rooms are connected with a curved corridor instead of an
+    /// L-shaped one. The default of one in three consumes the RNG exactly like the generators without
+    /// options always have, so their seeded output is unchanged.
+    pub curve_chance: f64,
+}
+
+impl Default for TieredOptions {
+    fn default() -> Self {
+        Self {
+            corridor_width: 1,
+            curve_chance: 1.0 / 3.0,
+        }
+    }
+}
+
 /// Static struct holding room based generation methods.
 pub struct RoomBased;
 
@@ -368,10 +388,60 @@ impl RoomBased {
     ///
     /// ### Panics
     /// - Function panics if it takes more than 10000 total iterations to generate the map.
-    #[allow(clippy::too_many_lines)]
     #[must_use]
     pub fn tiered(size: GridSize) -> MapGrid {
         trace!("RoomGen::tiered({:?})", size);
+        Self::tiered_with_options(size, TieredOptions::default())
+    }
+
+    /// "Tiered" Room Based Generator that also returns the generated rooms and the [`RoomGraph`]
+    /// describing which of them were connected. Graph indices refer to the returned room list. Unlike
+    /// [`RoomBased::tiered`], any rooms left unreachable by the random connections are joined to the
+    /// rest afterwards, so the returned graph is always connected.
+    ///
+    /// ### Panics
+    /// - Function panics if it takes more than 10000 total iterations to generate the map.
+    #[must_use]
+    pub fn tiered_connected(size: GridSize) -> (MapGrid, Vec<Room>, RoomGraph) {
+        trace!("RoomGen::tiered_connected({:?})", size);
+        Self::tiered_connected_with_options(size, TieredOptions::default())
+    }
+
+    /// "Tiered" Room Based Generator using the given [`TieredOptions`] to control the corridors.
+    ///
+    /// ### Panics
+    /// - Function panics if it takes more than 10000 total iterations to generate the map.
+    #[must_use]
+    pub fn tiered_with_options(size: GridSize, options: TieredOptions) -> MapGrid {
+        trace!("RoomGen::tiered_with_options({:?}, {:?})", size, options);
+        Self::tiered_rooms(size, options, false).0
+    }
+
+    /// Combination of [`RoomBased::tiered_connected`] and [`RoomBased::tiered_with_options`].
+    ///
+    /// ### Panics
+    /// - Function panics if it takes more than 10000 total iterations to generate the map.
+    #[must_use]
+    pub fn tiered_connected_with_options(
+        size: GridSize,
+        options: TieredOptions,
+    ) -> (MapGrid, Vec<Room>, RoomGraph) {
+        trace!(
+            "RoomGen::tiered_connected_with_options({:?}, {:?})",
+            size,
+            options
+        );
+        Self::tiered_rooms(size, options, true)
+    }
+
+    /// Shared implementation of the "Tiered" generators. Disconnected rooms are only joined up when
+    /// `ensure_connected` is true, so that [`RoomBased::tiered`] keeps producing the same maps.
+    #[allow(clippy::too_many_lines)]
+    fn tiered_rooms(
+        size: GridSize,
+        options: TieredOptions,
+        ensure_connected: bool,
+    ) -> (MapGrid, Vec<Room>, RoomGraph) {
         let (map_width, map_height) = size.into();
         let (big_room_x, big_room_width) = {
             let size_start = (map_width / 7).max(5);
@@ -594,9 +664,9 @@ impl RoomBased {
             Self::fill_room_on_grid(room, &mut grid);
         }
 
-        Self::connect_all_rooms(&mut grid, &mut rooms);
+        let graph = Self::connect_all_rooms(&mut grid, &mut rooms, &options, ensure_connected);
 
-        grid
+        (grid, rooms, graph)
     }
 
     /// "Tiered" "Heuristic" Room Based Generator
@@ -894,7 +964,7 @@ impl RoomBased {
             Self::fill_room_on_grid(room, &mut grid);
         }
 
-        Self::connect_all_rooms(&mut grid, &mut rooms);
+        Self::connect_all_rooms(&mut grid, &mut rooms, &TieredOptions::default(), false);
 
         grid
     }
@@ -902,69 +972,114 @@ impl RoomBased {
 
 /// Impl block for private functions.
 impl RoomBased {
-    fn connect_all_rooms(grid: &mut MapGrid, rooms: &mut [Room]) {
+    /// Connects the given `rooms` on the `grid`, returning the resulting [`RoomGraph`]. The `rooms` are
+    /// shuffled, so the graph indices refer to their order after this function returns. If
+    /// `ensure_connected` is true, rooms left unreachable by the random connections are joined to a
+    /// random reachable room.
+    fn connect_all_rooms(
+        grid: &mut MapGrid,
+        rooms: &mut [Room],
+        options: &TieredOptions,
+        ensure_connected: bool,
+    ) -> RoomGraph {
         fastrand::shuffle(rooms);
         let room_count = rooms.len();
-        for room in rooms.windows(2) {
+        let mut graph = RoomGraph::new(room_count);
+        for first in 0..room_count.saturating_sub(1) {
             let mut connections = 0;
-            let (r1, r2) = (room[0], room[1]);
+            let second = first + 1;
             if fastrand::u8(0..5) > 1 {
                 connections += 1;
-                Self::connect_rooms(grid, &r1, &r2);
+                Self::connect_rooms(grid, &rooms[first], &rooms[second], options);
+                graph.connect(first, second);
             }
 
-            for sub in room {
+            for sub in [first, second] {
                 let mut sub_conn = connections;
                 for _i in 0..=(fastrand::u8(0..3)) {
                     sub_conn += 1;
-                    let random_room = &rooms[fastrand::usize(0..room_count)];
-                    Self::connect_rooms(grid, sub, random_room);
+                    let random_room = fastrand::usize(0..room_count);
+                    Self::connect_rooms(grid, &rooms[sub], &rooms[random_room], options);
+                    graph.connect(sub, random_room);
                 }
                 if sub_conn < 1 {
-                    let random_room = &rooms[fastrand::usize(0..room_count)];
-                    Self::connect_rooms(grid, sub, random_room);
+                    let random_room = fastrand::usize(0..room_count);
+                    Self::connect_rooms(grid, &rooms[sub], &rooms[random_room], options);
+                    graph.connect(sub, random_room);
                 }
             }
         }
+
+        // The random connections above do not guarantee that every room is reachable, so join any
+        // leftover rooms to a random room that already is.
+        while ensure_connected && !graph.is_connected() {
+            let reachable = graph.reachable_from(0);
+            let missing = (0..room_count)
+                .find(|i| !reachable.contains(i))
+                .expect("Disconnected graph must have an unreachable room");
+            let target = reachable[fastrand::usize(0..reachable.len())];
+            Self::connect_rooms(grid, &rooms[missing], &rooms[target], options);
+            graph.connect(missing, target);
+        }
+
+        graph
     }
 
-    fn connect_rooms(grid: &mut MapGrid, first: &Room, second: &Room) {
+    fn connect_rooms(grid: &mut MapGrid, first: &Room, second: &Room, options: &TieredOptions) {
         let c1 = first.square().center();
         let c2 = second.square().center();
+        let width = options.corridor_width.max(1);
 
-        if fastrand::u8(0..3) == 2 {
-            // 33% chance of connecting with curve
-            Self::curved_path(grid, c1, c2);
+        // The default chance keeps the original one in three roll, so seeded maps stay the same
+        let default_chance = TieredOptions::default().curve_chance;
+        let curved = if (options.curve_chance - default_chance).abs() < f64::EPSILON {
+            fastrand::u8(0..3) == 2
+        } else {
+            fastrand::f64() < options.curve_chance
+        };
+        if curved {
+            Self::curved_path(grid, c1, c2, width);
         } else if fastrand::bool() {
             // Otherwise 50-50 shot of connecting from upper left vs lower right mid point
-            Self::horizontal_path(grid, c1.x, c2.x, c1.y);
-            Self::vertical_path(grid, c1.y, c2.y, c2.x);
+            Self::horizontal_path(grid, c1.x, c2.x, c1.y, width);
+            Self::vertical_path(grid, c1.y, c2.y, c2.x, width);
         } else {
-            Self::vertical_path(grid, c1.y, c2.y, c2.x);
-            Self::horizontal_path(grid, c1.x, c2.x, c1.y);
+            Self::vertical_path(grid, c1.y, c2.y, c2.x, width);
+            Self::horizontal_path(grid, c1.x, c2.x, c1.y, width);
         }
     }
 
-    fn horizontal_path(grid: &mut MapGrid, first: usize, second: usize, y: usize) {
+    fn horizontal_path(grid: &mut MapGrid, first: usize, second: usize, y: usize, width: usize) {
         let start = first.min(second);
         let end = first.max(second);
         for col in start..=end {
-            grid.set_cell_state(col, y, true);
+            Self::carve_block(grid, col, y, width);
         }
     }
 
-    fn vertical_path(grid: &mut MapGrid, first: usize, second: usize, x: usize) {
+    fn vertical_path(grid: &mut MapGrid, first: usize, second: usize, x: usize, width: usize) {
         let start = first.min(second);
         let end = first.max(second);
         for row in start..=end {
-            grid.set_cell_state(x, row, true);
+            Self::carve_block(grid, x, row, width);
         }
     }
 
-    fn curved_path(grid: &mut MapGrid, first: GridPos, second: GridPos) {
+    fn curved_path(grid: &mut MapGrid, first: GridPos, second: GridPos, width: usize) {
         let path = get_curve_between(first, second);
         for pos in path {
-            grid.set_cell_state(pos.0, pos.1, true);
+            Self::carve_block(grid, pos.0, pos.1, width);
+        }
+    }
+
+    /// Sets a `width`x`width` block of cells (roughly centered on `x`, `y`) to on, skipping any cells
+    /// outside of the grid. A `width` of 1 sets only the cell at `x`, `y`.
+    fn carve_block(grid: &mut MapGrid, x: usize, y: usize, width: usize) {
+        let offset = (width - 1) / 2;
+        for by in y.saturating_sub(offset)..(y + width - offset).min(grid.rows()) {
+            for bx in x.saturating_sub(offset)..(x + width - offset).min(grid.cols()) {
+                grid.set_cell_state(bx, by, true);
+            }
         }
     }
 
@@ -1084,3 +1199,52 @@ impl From<SizeRange> for (Range<usize>, Range<usize>) {
         (val.0, val.1)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::util::testing::crate_before_test;
+
+    #[test]
+    fn tiered_room_graph() {
+        crate_before_test();
+
+        let (grid, rooms, graph) = RoomBased::tiered_connected((80, 60).into());
+        assert_eq!(grid.size(), (80, 60).into());
+        assert_eq!(graph.room_count(), rooms.len());
+        assert!(graph.is_connected());
+        assert!(graph.edge_count() + 1 >= rooms.len());
+        for room in 0..graph.room_count() {
+            for &other in graph.neighbors(room) {
+                assert!(graph.neighbors(other).contains(&room));
+            }
+        }
+    }
+
+    #[test]
+    fn tiered_corridor_width() {
+        crate_before_test();
+
+        let options = TieredOptions {
+            corridor_width: 2,
+            ..TieredOptions::default()
+        };
+        let grid = RoomBased::tiered_with_options((80, 60).into(), options);
+        for ((x, y), cell) in grid.iter_pos() {
+            if !cell.is_on() {
+                continue;
+            }
+
+            let in_full_block = [(0, 0), (1, 0), (0, 1), (1, 1)].iter().any(|&(ox, oy)| {
+                let (Some(bx), Some(by)) = (x.checked_sub(ox), y.checked_sub(oy)) else {
+                    return false;
+                };
+                [(0, 0), (1, 0), (0, 1), (1, 1)]
+                    .iter()
+                    .all(|&(dx, dy)| matches!(grid.cell((bx + dx, by + dy)), Some(c) if c.is_on()))
+            });
+            assert!(in_full_block, "({}, {}) is part of a 1-wide passage", x, y);
+        }
+    }
+}
@@ -147,4 +147,148 @@ impl Room {
 
         edges
     }
+
+    /// Splits this room into two rooms that exactly tile it. If `horizontal` is true the room is cut
+    /// along a horizontal line `at` rows from its top, giving the top and bottom rooms, otherwise it is
+    /// cut along a vertical line `at` columns from its left side, giving the left and right rooms.
+    /// Returns [`None`] if either room would be smaller than 3x3.
+    #[must_use]
+    pub fn split(&self, horizontal: bool, at: usize) -> Option<(Room, Room)> {
+        trace!("Room::split({}, {})", horizontal, at);
+        let (min_x, min_y) = self.0.min.into();
+        let (width, height) = self.0.size().into();
+        let (length, breadth) = if horizontal {
+            (height, width)
+        } else {
+            (width, height)
+        };
+        if breadth < 3 || at < 3 || length < at + 3 {
+            return None;
+        }
+
+        Some(if horizontal {
+            (
+                Room::new((min_x, min_y), width, at),
+                Room::new((min_x, min_y + at), width, height - at),
+            )
+        } else {
+            (
+                Room::new((min_x, min_y), at, height),
+                Room::new((min_x + at, min_y), width - at, height),
+            )
+        })
+    }
+}
+
+/// The connections made between rooms by the [`crate::gen::room_based::RoomBased`] generators. Each
+/// room is referred to by its index in the room list returned alongside the graph.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RoomGraph {
+    adjacency: Vec<Vec<usize>>,
+    edges: usize,
+}
+
+impl RoomGraph {
+    /// Creates a new [`RoomGraph`] for `room_count` rooms with no connections.
+    #[must_use]
+    pub fn new(room_count: usize) -> Self {
+        Self {
+            adjacency: vec![Vec::new(); room_count],
+            edges: 0,
+        }
+    }
+
+    /// Records a connection between the rooms at `first` and `second`. Connections from a room to itself,
+    /// connections that already exist, and out of range indices are ignored.
+    pub fn connect(&mut self, first: usize, second: usize) {
+        if first == second
+            || first >= self.adjacency.len()
+            || second >= self.adjacency.len()
+            || self.adjacency[first].contains(&second)
+        {
+            return;
+        }
+
+        self.adjacency[first].push(second);
+        self.adjacency[second].push(first);
+        self.edges += 1;
+    }
+
+    /// Gets the indices of the rooms connected to the room at `room_idx`.
+    #[must_use]
+    pub fn neighbors(&self, room_idx: usize) -> &[usize] {
+        self.adjacency.get(room_idx).map_or(&[], Vec::as_slice)
+    }
+
+    /// Gets the number of rooms in this graph.
+    #[must_use]
+    pub fn room_count(&self) -> usize {
+        self.adjacency.len()
+    }
+
+    /// Gets the number of unique connections in this graph.
+    #[must_use]
+    pub fn edge_count(&self) -> usize {
+        self.edges
+    }
+
+    /// Gets the indices of every room reachable from the room at `room_idx` (including itself).
+    #[must_use]
+    pub fn reachable_from(&self, room_idx: usize) -> Vec<usize> {
+        if room_idx >= self.adjacency.len() {
+            return Vec::new();
+        }
+
+        let mut visited = vec![false; self.adjacency.len()];
+        let mut stack = vec![room_idx];
+        visited[room_idx] = true;
+        while let Some(current) = stack.pop() {
+            for &next in &self.adjacency[current] {
+                if !visited[next] {
+                    visited[next] = true;
+                    stack.push(next);
+                }
+            }
+        }
+
+        (0..visited.len()).filter(|&i| visited[i]).collect()
+    }
+
+    /// Checks whether every room can be reached from every other room. An empty graph is connected.
+    #[must_use]
+    pub fn is_connected(&self) -> bool {
+        self.adjacency.is_empty() || self.reachable_from(0).len() == self.adjacency.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::util::testing::crate_before_test;
+
+    #[test]
+    fn room_split() {
+        crate_before_test();
+
+        let room = Room::new((2, 4), 10, 6);
+        let (top, bottom) = room.split(true, 3).expect("Room should split");
+        assert_eq!(top, Room::new((2, 4), 10, 3));
+        assert_eq!(bottom, Room::new((2, 7), 10, 3));
+        assert!(!top.intersects(&bottom));
+        assert_eq!(top.square().union(&bottom.square()), room.square());
+        assert_eq!(
+            top.square().area() + bottom.square().area(),
+            room.square().area()
+        );
+
+        let (left, right) = room.split(false, 4).expect("Room should split");
+        assert_eq!(left, Room::new((2, 4), 4, 6));
+        assert_eq!(right, Room::new((6, 4), 6, 6));
+
+        assert!(room.split(true, 2).is_none());
+        assert!(room.split(true, 4).is_none());
+        assert!(room.split(false, 8).is_none());
+        assert!(Room::new((0, 0), 2, 10).split(true, 5).is_none());
+    }
 }
@@ -0,0 +1,309 @@
+use std::collections::HashMap;
+
+use crate::{
+    data::{GridSize, MapGrid},
+    logging::{info, trace, warn},
+};
+
+/// The maximum number of times [`WaveFunctionCollapse::generate`] will restart after running into
+/// a contradiction before giving up.
+const MAX_RETRIES: usize = 10;
+
+/// The four cardinal offsets used when propagating constraints, (dx, dy).
+const DIRECTIONS: [(isize, isize); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+
+/// A single square pattern extracted from the example grid, stored in row-major order.
+type Pattern = Vec<bool>;
+
+/// Static struct holding the (overlapping model) Wave Function Collapse generator.
+pub struct WaveFunctionCollapse;
+
+impl WaveFunctionCollapse {
+    /// Generates a new [`MapGrid`] of `output_size` whose local `pattern_size`x`pattern_size`
+    /// patterns all appear somewhere in the `example` grid.
+    ///
+    /// The `seed` is used for a local RNG so the same inputs always produce the same output.
+    /// Contradictions cause the generation to restart, up to a fixed number of retries.
+    ///
+    /// ### Errors
+    /// - Function errors if `pattern_size` is 0, or larger than either the `example` or the `output_size`.
+    /// - Function errors if the `output_size` is less than 3x3.
+    /// - Function errors if every attempt runs into a contradiction.
+    pub fn generate<Size: Into<GridSize> + std::fmt::Debug>(
+        example: &MapGrid,
+        pattern_size: usize,
+        output_size: Size,
+        seed: u64,
+    ) -> Result<MapGrid, String> {
+        trace!(
+            "WaveFunctionCollapse::generate(Grid, {}, {:?}, {})",
+            pattern_size,
+            output_size,
+            seed
+        );
+        let (width, height) = output_size.into().into();
+
+        if pattern_size == 0 {
+            return Err("Pattern size must be at least 1".to_string());
+        }
+        if pattern_size > example.cols() || pattern_size > example.rows() {
+            return Err(format!(
+                "Pattern size {} is larger than the example grid ({}x{})",
+                pattern_size,
+                example.cols(),
+                example.rows()
+            ));
+        }
+        if width < 3 || height < 3 {
+            return Err("Output size must be at least 3x3".to_string());
+        }
+        if pattern_size > width || pattern_size > height {
+            return Err(format!(
+                "Pattern size {} is larger than the output size ({}x{})",
+                pattern_size, width, height
+            ));
+        }
+
+        let (patterns, weights) = Self::extract_patterns(example, pattern_size);
+        let compatible = Self::build_compatibility(&patterns, pattern_size);
+        info!(
+            "WaveFunctionCollapse::generate - extracted {} unique patterns",
+            patterns.len()
+        );
+
+        let mut rng = fastrand::Rng::with_seed(seed);
+        let wave_size = (width - pattern_size + 1, height - pattern_size + 1);
+        for attempt in 0..MAX_RETRIES {
+            if let Some(chosen) = Self::run(&weights, &compatible, wave_size, &mut rng) {
+                let mut grid = MapGrid::empty((width, height));
+                for y in 0..height {
+                    for x in 0..width {
+                        let (wx, wy) = (x.min(wave_size.0 - 1), y.min(wave_size.1 - 1));
+                        let pattern = &patterns[chosen[wy * wave_size.0 + wx]];
+                        let (px, py) = (x - wx, y - wy);
+                        grid.set_cell_state(x, y, pattern[py * pattern_size + px]);
+                    }
+                }
+
+                return Ok(grid);
+            }
+
+            warn!(
+                "WaveFunctionCollapse::generate - contradiction on attempt {}/{}, restarting",
+                attempt + 1,
+                MAX_RETRIES
+            );
+        }
+
+        Err(format!(
+            "Unable to generate a grid without contradictions after {} attempts",
+            MAX_RETRIES
+        ))
+    }
+
+    /// Extracts every unique `n`x`n` pattern from `example`, along with how many times it appears.
+    fn extract_patterns(example: &MapGrid, n: usize) -> (Vec<Pattern>, Vec<usize>) {
+        let mut patterns = Vec::new();
+        let mut weights = Vec::new();
+        let mut lookup: HashMap<Pattern, usize> = HashMap::new();
+
+        for y in 0..=(example.rows() - n) {
+            for x in 0..=(example.cols() - n) {
+                let mut pattern = Vec::with_capacity(n * n);
+                for py in 0..n {
+                    for px in 0..n {
+                        pattern.push(example.cell((x + px, y + py)).map_or(false, |c| c.is_on()));
+                    }
+                }
+
+                if let Some(&index) = lookup.get(&pattern) {
+                    weights[index] += 1;
+                } else {
+                    lookup.insert(pattern.clone(), patterns.len());
+                    patterns.push(pattern);
+                    weights.push(1);
+                }
+            }
+        }
+
+        (patterns, weights)
+    }
+
+    /// Builds, for each pattern and each direction in [`DIRECTIONS`], the list of patterns that may
+    /// be placed at that offset without disagreeing on any overlapping cell.
+    #[allow(clippy::cast_possible_wrap, clippy::cast_sign_loss)]
+    fn build_compatibility(patterns: &[Pattern], n: usize) -> Vec<[Vec<usize>; 4]> {
+        let agrees = |first: &Pattern, second: &Pattern, (dx, dy): (isize, isize)| {
+            let n = n as isize;
+            for y in dy.max(0)..(n + dy.min(0)) {
+                for x in dx.max(0)..(n + dx.min(0)) {
+                    let other = ((y - dy) * n + (x - dx)) as usize;
+                    if first[(y * n + x) as usize] != second[other] {
+                        return false;
+                    }
+                }
+            }
+
+            true
+        };
+
+        patterns
+            .iter()
+            .map(|first| {
+                let mut result: [Vec<usize>; 4] = Default::default();
+                for (d, &offset) in DIRECTIONS.iter().enumerate() {
+                    result[d] = patterns
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, second)| agrees(first, second, offset))
+                        .map(|(i, _)| i)
+                        .collect();
+                }
+
+                result
+            })
+            .collect()
+    }
+
+    /// Runs a single observe / propagate loop over a wave of `size` positions. Returns the chosen
+    /// pattern index for each position, or `None` if a contradiction was reached.
+    #[allow(clippy::cast_possible_wrap, clippy::cast_sign_loss)]
+    fn run(
+        weights: &[usize],
+        compatible: &[[Vec<usize>; 4]],
+        size: (usize, usize),
+        rng: &mut fastrand::Rng,
+    ) -> Option<Vec<usize>> {
+        let pattern_count = weights.len();
+        let mut wave = vec![vec![true; pattern_count]; size.0 * size.1];
+        let mut remaining = vec![pattern_count; size.0 * size.1];
+
+        loop {
+            // Observe: collapse the undecided position with the fewest options left.
+            let mut lowest: Option<(usize, usize)> = None;
+            let mut ties = 0usize;
+            for (index, &count) in remaining.iter().enumerate() {
+                if count == 0 {
+                    return None;
+                }
+                if count == 1 {
+                    continue;
+                }
+                match lowest {
+                    Some((_, best)) if count > best => {}
+                    Some((_, best)) if count == best => {
+                        ties += 1;
+                        if rng.usize(0..ties) == 0 {
+                            lowest = Some((index, count));
+                        }
+                    }
+                    _ => {
+                        ties = 1;
+                        lowest = Some((index, count));
+                    }
+                }
+            }
+
+            let index = match lowest {
+                Some((index, _)) => index,
+                None => {
+                    return Some(
+                        wave.iter()
+                            .map(|options| options.iter().position(|&o| o).unwrap_or(0))
+                            .collect(),
+                    );
+                }
+            };
+
+            let total: usize = (0..pattern_count)
+                .filter(|&p| wave[index][p])
+                .map(|p| weights[p])
+                .sum();
+            let mut roll = rng.usize(0..total);
+            let mut choice = 0;
+            for p in (0..pattern_count).filter(|&p| wave[index][p]) {
+                if roll < weights[p] {
+                    choice = p;
+                    break;
+                }
+                roll -= weights[p];
+            }
+            for (p, option) in wave[index].iter_mut().enumerate() {
+                *option = p == choice;
+            }
+            remaining[index] = 1;
+
+            // Propagate: remove any neighboring options that no longer have support.
+            let mut stack = vec![index];
+            while let Some(current) = stack.pop() {
+                let (x, y) = ((current % size.0) as isize, (current / size.0) as isize);
+                for (d, &(dx, dy)) in DIRECTIONS.iter().enumerate() {
+                    let (nx, ny) = (x + dx, y + dy);
+                    if nx < 0 || ny < 0 || nx >= size.0 as isize || ny >= size.1 as isize {
+                        continue;
+                    }
+
+                    let neighbor = ny as usize * size.0 + nx as usize;
+                    let mut changed = false;
+                    for other in 0..pattern_count {
+                        if !wave[neighbor][other] {
+                            continue;
+                        }
+
+                        let supported = (0..pattern_count)
+                            .any(|p| wave[current][p] && compatible[p][d].contains(&other));
+                        if !supported {
+                            wave[neighbor][other] = false;
+                            remaining[neighbor] -= 1;
+                            changed = true;
+                        }
+                    }
+
+                    if remaining[neighbor] == 0 {
+                        return None;
+                    }
+                    if changed {
+                        stack.push(neighbor);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::util::testing::crate_before_test;
+
+    #[test]
+    fn wfc_stripes() {
+        crate_before_test();
+
+        let example =
+            MapGrid::parse_string("#.#.#.\n#.#.#.\n#.#.#.\n#.#.#.\n#.#.#.\n#.#.#.", '#', '.')
+                .expect("Unable to parse striped example");
+        let output = WaveFunctionCollapse::generate(&example, 2, (12, 8), 42)
+            .expect("Striped example should never contradict");
+        assert_eq!(output.size(), (12, 8).into());
+
+        let rows = output.to_strings();
+        for row in &rows {
+            assert_eq!(row, &rows[0], "Every row of a vertical stripe should match");
+        }
+        for (a, b) in rows[0].chars().zip(rows[0].chars().skip(1)) {
+            assert_ne!(a, b, "Adjacent columns should alternate");
+        }
+    }
+
+    #[test]
+    fn wfc_invalid_arguments() {
+        crate_before_test();
+
+        let example = MapGrid::empty((3, 3));
+        assert!(WaveFunctionCollapse::generate(&example, 0, (5, 5), 0).is_err());
+        assert!(WaveFunctionCollapse::generate(&example, 4, (5, 5), 0).is_err());
+        assert!(WaveFunctionCollapse::generate(&example, 2, (2, 5), 0).is_err());
+    }
+}
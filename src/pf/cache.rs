@@ -0,0 +1,211 @@
+use std::collections::HashMap;
+
+use crate::{
+    data::{GridPos, MapGrid},
+    logging::trace,
+    pf::pathing::PathAlgorithm,
+};
+
+/// A precomputed table of the open (off) neighbors of every open cell in a [`MapGrid`].
+///
+/// Building the cache once lets several pathfinding runs over the same grid skip the repeated
+/// [`MapGrid::neighbors_with_state`] allocations. The cache is a snapshot, so it must be rebuilt
+/// if the grid is modified.
+#[derive(Debug, Clone)]
+pub struct NeighborCache {
+    width: usize,
+    height: usize,
+    /// `offsets[i]..offsets[i + 1]` is the range in `neighbors` belonging to the cell with index `i`.
+    offsets: Vec<usize>,
+    neighbors: Vec<GridPos>,
+}
+
+impl NeighborCache {
+    /// Builds a new [`NeighborCache`] from the current state of `grid`. If `diagonal` is true all eight
+    /// surrounding cells are considered (matching [`MapGrid::neighbors_with_state`]), otherwise only
+    /// the four orthogonal ones are (matching [`MapGrid::neighbors_with_state_ortho`]).
+    #[must_use]
+    pub fn build(grid: &MapGrid, diagonal: bool) -> Self {
+        trace!("NeighborCache::build(Grid, {})", diagonal);
+        let (width, height) = grid.size().into();
+        let mut offsets = Vec::with_capacity(width * height + 1);
+        let mut neighbors = Vec::new();
+        offsets.push(0);
+        for ((x, y), cell) in grid.iter_pos() {
+            if cell.is_off() {
+                let open = if diagonal {
+                    grid.neighbors_with_state((x, y), false, false)
+                } else {
+                    grid.neighbors_with_state_ortho((x, y), false, false)
+                };
+                neighbors.extend(open.into_iter().map(GridPos::from));
+            }
+            offsets.push(neighbors.len());
+        }
+
+        Self {
+            width,
+            height,
+            offsets,
+            neighbors,
+        }
+    }
+
+    /// Gets the cached open neighbors of the cell at `pos`. Cells that are not open, or that are out
+    /// of bounds, have no neighbors.
+    #[must_use]
+    pub fn neighbors<Pos: Into<GridPos>>(&self, pos: Pos) -> &[GridPos] {
+        let pos = pos.into();
+        if pos.x >= self.width || pos.y >= self.height {
+            return &[];
+        }
+
+        let index = pos.y * self.width + pos.x;
+        &self.neighbors[self.offsets[index]..self.offsets[index + 1]]
+    }
+}
+
+/// Memoizes the results of [`PathAlgorithm`] searches, keyed by [`MapGrid::checksum`] along with the
+/// start, goal and algorithm used.
+///
+/// Repeating a query on an unchanged grid returns the stored result instead of searching again, while
+/// modifying the grid changes its checksum so stale results are practically never returned (only a
+/// collision of the 64 bit checksum could do so). The cache is owned by the caller and grows until it
+/// is [cleared](`PathCache::clear`).
+#[derive(Debug, Clone, Default)]
+pub struct PathCache {
+    paths: HashMap<(u64, GridPos, GridPos, PathAlgorithm), Option<Vec<GridPos>>>,
+    hits: usize,
+    misses: usize,
+}
+
+impl PathCache {
+    /// Creates a new, empty [`PathCache`].
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Finds a path from `start` to `goal` on `grid` using `algorithm`, returning the cached result if
+    /// this exact query has been made before on a grid with the same layout.
+    pub fn find<P1: Into<GridPos>, P2: Into<GridPos>>(
+        &mut self,
+        grid: &MapGrid,
+        start: P1,
+        goal: P2,
+        algorithm: PathAlgorithm,
+    ) -> Option<Vec<GridPos>> {
+        let (start, goal) = (start.into(), goal.into());
+        trace!(
+            "PathCache::find(Grid, {:?}, {:?}, {:?})",
+            start,
+            goal,
+            algorithm
+        );
+        let key = (grid.checksum(), start, goal, algorithm);
+        if let Some(path) = self.paths.get(&key) {
+            self.hits += 1;
+            return path.clone();
+        }
+
+        self.misses += 1;
+        let path = algorithm.find(grid, start, goal);
+        self.paths.insert(key, path.clone());
+        path
+    }
+
+    /// Gets the number of queries that were answered from the cache.
+    #[must_use]
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+
+    /// Gets the number of queries that had to run a search.
+    #[must_use]
+    pub fn misses(&self) -> usize {
+        self.misses
+    }
+
+    /// Gets the number of results currently stored.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.paths.len()
+    }
+
+    /// Checks whether the cache is holding no results.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty()
+    }
+
+    /// Removes every stored result. The hit and miss counters are left untouched.
+    pub fn clear(&mut self) {
+        self.paths.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::util::testing::crate_before_test;
+
+    #[test]
+    fn cache_matches_live() {
+        crate_before_test();
+
+        let grid = MapGrid::random_fill_percent((12, 9), 0.35);
+        let diagonal = NeighborCache::build(&grid, true);
+        let orthogonal = NeighborCache::build(&grid, false);
+        for ((x, y), cell) in grid.iter_pos() {
+            let cached: Vec<(usize, usize)> = diagonal
+                .neighbors((x, y))
+                .iter()
+                .map(|&p| p.into())
+                .collect();
+            if cell.is_off() {
+                assert_eq!(cached, grid.neighbors_with_state((x, y), false, false));
+            } else {
+                assert!(cached.is_empty());
+            }
+
+            for p in orthogonal.neighbors((x, y)) {
+                assert!(p.x == x || p.y == y);
+                assert!(diagonal.neighbors((x, y)).contains(p));
+            }
+        }
+
+        assert!(diagonal.neighbors((12, 0)).is_empty());
+    }
+
+    #[test]
+    fn path_cache_reuses_results() {
+        crate_before_test();
+
+        let mut grid =
+            MapGrid::parse_string("#######\n#.....#\n#.###.#\n#.....#\n#######", '#', '.')
+                .expect("Unable to parse grid!");
+        let mut cache = PathCache::new();
+        let first = cache.find(&grid, (1, 1), (5, 3), PathAlgorithm::AStar);
+        assert!(first.is_some());
+        assert_eq!((cache.hits(), cache.misses()), (0, 1));
+
+        let second = cache.find(&grid, (1, 1), (5, 3), PathAlgorithm::AStar);
+        assert_eq!(first, second);
+        assert_eq!((cache.hits(), cache.misses()), (1, 1));
+
+        let _ = cache.find(&grid, (1, 1), (5, 3), PathAlgorithm::Bfs);
+        assert_eq!((cache.hits(), cache.misses()), (1, 2));
+
+        grid.set_cell_state(1, 2, true);
+        grid.set_cell_state(5, 2, true);
+        assert!(cache
+            .find(&grid, (1, 1), (5, 3), PathAlgorithm::AStar)
+            .is_none());
+        assert_eq!((cache.hits(), cache.misses()), (1, 3));
+        assert_eq!(cache.len(), 3);
+
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+}
@@ -1,2 +1,7 @@
+/// `Cache` Module
+///
+/// Contains the [`crate::pf::cache::NeighborCache`] used to speed up repeated pathfinding on one grid, and
+/// the [`crate::pf::cache::PathCache`] used to skip repeated searches entirely.
+pub mod cache;
 /// `Pathing` Module
 pub mod pathing;
@@ -1,13 +1,53 @@
-use pathfinding::prelude::{astar, bfs, dfs, dijkstra, fringe};
+use std::{
+    cmp::{Ordering, Reverse},
+    collections::{BinaryHeap, HashMap, HashSet},
+};
+
+use pathfinding::prelude::{astar, bfs, dfs, dijkstra, fringe, yen};
 
 use crate::{
     data::{GridPos, MapGrid},
+    pf::cache::NeighborCache,
     util::math::absdiff,
 };
 
 /// Static struct holding pathfinding functions that work with [`MapGrid`](`crate::data::MapGrid`).
 pub struct Pathfinding;
 
+/// The basic point-to-point searches offered by [`Pathfinding`], used to pick one at runtime.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PathAlgorithm {
+    /// [`Pathfinding::dijkstra`]
+    Dijkstra,
+    /// [`Pathfinding::a_star`]
+    AStar,
+    /// [`Pathfinding::bfs`]
+    Bfs,
+    /// [`Pathfinding::dfs`]
+    Dfs,
+    /// [`Pathfinding::fringe`]
+    Fringe,
+}
+
+impl PathAlgorithm {
+    /// Runs this algorithm on `grid` to find a path from `start` to `goal`.
+    #[must_use]
+    pub fn find<P1: Into<(usize, usize)>, P2: Into<(usize, usize)>>(
+        self,
+        grid: &MapGrid,
+        start: P1,
+        goal: P2,
+    ) -> Option<Vec<GridPos>> {
+        match self {
+            PathAlgorithm::Dijkstra => Pathfinding::dijkstra(grid, start, goal),
+            PathAlgorithm::AStar => Pathfinding::a_star(grid, start, goal),
+            PathAlgorithm::Bfs => Pathfinding::bfs(grid, start, goal),
+            PathAlgorithm::Dfs => Pathfinding::dfs(grid, start, goal),
+            PathAlgorithm::Fringe => Pathfinding::fringe(grid, start, goal),
+        }
+    }
+}
+
 impl Pathfinding {
     fn default_heuristic(first: (usize, usize), second: (usize, usize)) -> usize {
         absdiff(first.0, second.0) + absdiff(first.1, second.1)
@@ -66,6 +106,41 @@ impl Pathfinding {
         .map(|(path, _)| path.into_iter().map(std::convert::Into::into).collect())
     }
 
+    /// Same as [`Pathfinding::a_star`], but stepping onto any cell in `avoid` costs an extra `penalty`
+    /// instead of being forbidden. A high `penalty` makes the path detour around the avoided cells when
+    /// possible, while a low one lets it cut straight through.
+    #[must_use]
+    pub fn a_star_avoiding<P1: Into<(usize, usize)>, P2: Into<(usize, usize)>>(
+        grid: &MapGrid,
+        start: P1,
+        goal: P2,
+        avoid: &[GridPos],
+        penalty: usize,
+    ) -> Option<Vec<GridPos>> {
+        let startu = start.into();
+        let goalu = goal.into();
+        let avoided: HashSet<(usize, usize)> = avoid.iter().map(|&p| p.into()).collect();
+        astar(
+            &startu,
+            |&p| {
+                grid.neighbors_with_state(p, false, false)
+                    .into_iter()
+                    .map(|pi| {
+                        let cost = if avoided.contains(&pi) {
+                            1 + penalty
+                        } else {
+                            1
+                        };
+                        (pi, cost)
+                    })
+                    .collect::<Vec<((usize, usize), usize)>>()
+            },
+            |&xy| Self::default_heuristic(xy, goalu),
+            |&p| Self::default_success(p, goalu),
+        )
+        .map(|(path, _)| path.into_iter().map(std::convert::Into::into).collect())
+    }
+
     /// Attempts to find a path from `start` to `goal` using the ***BFS*** algorithm from the [`pathfinding`] library.
     /// If a path cannot be found, `None` is returned, otherwise a [`Vec<GridPos>`] is returned containing each point
     /// in the resulting path.
@@ -85,6 +160,43 @@ impl Pathfinding {
         .map(|path| path.into_iter().map(std::convert::Into::into).collect())
     }
 
+    /// Same as [`Pathfinding::a_star`], but reads neighbors from a prebuilt [`NeighborCache`] instead of
+    /// recomputing them from the grid on every expansion.
+    #[must_use]
+    pub fn a_star_cached<P1: Into<(usize, usize)>, P2: Into<(usize, usize)>>(
+        cache: &NeighborCache,
+        start: P1,
+        goal: P2,
+    ) -> Option<Vec<GridPos>> {
+        let startu = start.into();
+        let goalu = goal.into();
+        astar(
+            &startu,
+            |&p| cache.neighbors(p).iter().map(|&pi| (pi.into(), 1usize)),
+            |&xy| Self::default_heuristic(xy, goalu),
+            |&p| Self::default_success(p, goalu),
+        )
+        .map(|(path, _)| path.into_iter().map(std::convert::Into::into).collect())
+    }
+
+    /// Same as [`Pathfinding::bfs`], but reads neighbors from a prebuilt [`NeighborCache`] instead of
+    /// recomputing them from the grid on every expansion.
+    #[must_use]
+    pub fn bfs_cached<P1: Into<(usize, usize)>, P2: Into<(usize, usize)>>(
+        cache: &NeighborCache,
+        start: P1,
+        goal: P2,
+    ) -> Option<Vec<GridPos>> {
+        let startu = start.into();
+        let goalu = goal.into();
+        bfs(
+            &startu,
+            |&p| cache.neighbors(p).iter().map(|&pi| pi.into()),
+            |&p| Self::default_success(p, goalu),
+        )
+        .map(|path| path.into_iter().map(std::convert::Into::into).collect())
+    }
+
     /// Attempts to find a path from `start` to `goal` using the ***DFS*** algorithm from the [`pathfinding`] library.
     /// If a path cannot be found, `None` is returned, otherwise a [`Vec<GridPos>`] is returned containing each point
     /// in the resulting path.
@@ -128,4 +240,256 @@ impl Pathfinding {
         )
         .map(|(path, _)| path.into_iter().map(std::convert::Into::into).collect())
     }
+
+    /// Same as [`Pathfinding::a_star`], but every change of direction along the path costs an extra
+    /// `turn_cost`. A high `turn_cost` favors long straight runs over paths that zigzag, even if that
+    /// makes the path slightly longer, which looks more natural for corridors.
+    #[must_use]
+    pub fn a_star_turn_penalized<P1: Into<(usize, usize)>, P2: Into<(usize, usize)>>(
+        grid: &MapGrid,
+        start: P1,
+        goal: P2,
+        turn_cost: usize,
+    ) -> Option<Vec<GridPos>> {
+        let startu = start.into();
+        let goalu = goal.into();
+        // The search state is the current cell along with the direction it was entered from
+        astar(
+            &(startu, None),
+            |&(p, heading): &((usize, usize), Option<(Ordering, Ordering)>)| {
+                grid.neighbors_with_state(p, false, false)
+                    .into_iter()
+                    .map(|pi| {
+                        let step = (pi.0.cmp(&p.0), pi.1.cmp(&p.1));
+                        let cost = match heading {
+                            Some(previous) if previous != step => 1 + turn_cost,
+                            _ => 1,
+                        };
+                        ((pi, Some(step)), cost)
+                    })
+                    .collect::<Vec<_>>()
+            },
+            |&(xy, _)| Self::default_heuristic(xy, goalu),
+            |&(p, _)| Self::default_success(p, goalu),
+        )
+        .map(|(path, _)| path.into_iter().map(|(p, _)| p.into()).collect())
+    }
+
+    /// Finds up to `k` distinct, loopless paths from `start` to `goal` using ***Yen's*** algorithm from the
+    /// [`pathfinding`] library. Paths are ordered from shortest to longest, and the result is empty if no
+    /// path exists.
+    #[must_use]
+    pub fn k_shortest<P1: Into<(usize, usize)>, P2: Into<(usize, usize)>>(
+        grid: &MapGrid,
+        start: P1,
+        goal: P2,
+        k: usize,
+    ) -> Vec<Vec<GridPos>> {
+        let startu = start.into();
+        let goalu = goal.into();
+        yen(
+            &startu,
+            |&p| {
+                grid.neighbors_with_state(p, false, false)
+                    .into_iter()
+                    .map(|pi| (pi, 1usize))
+                    .collect::<Vec<((usize, usize), usize)>>()
+            },
+            |&p| Self::default_success(p, goalu),
+            k,
+        )
+        .into_iter()
+        .map(|(path, _)| path.into_iter().map(std::convert::Into::into).collect())
+        .collect()
+    }
+
+    /// Runs an ***A-Star*** search from `start` toward `goal` that never expands past a path cost of
+    /// `max_cost`. Returns the path along with whether the goal was actually reached. If the goal can
+    /// not be reached within the budget, the returned path leads to the explored cell closest to the
+    /// goal (by the default heuristic) instead. The returned path always begins with `start`.
+    #[must_use]
+    pub fn a_star_budgeted<P1: Into<(usize, usize)>, P2: Into<(usize, usize)>>(
+        grid: &MapGrid,
+        start: P1,
+        goal: P2,
+        max_cost: usize,
+    ) -> (Vec<GridPos>, bool) {
+        let startu = start.into();
+        let goalu = goal.into();
+        let mut parents: HashMap<(usize, usize), ((usize, usize), usize)> = HashMap::new();
+        parents.insert(startu, (startu, 0));
+        let mut open = BinaryHeap::new();
+        open.push(Reverse((
+            Self::default_heuristic(startu, goalu),
+            0usize,
+            startu,
+        )));
+        let mut closest = (Self::default_heuristic(startu, goalu), 0usize, startu);
+
+        while let Some(Reverse((_, cost, current))) = open.pop() {
+            if parents
+                .get(&current)
+                .map_or(false, |&(_, best)| cost > best)
+            {
+                continue;
+            }
+
+            let distance = Self::default_heuristic(current, goalu);
+            if (distance, cost) < (closest.0, closest.1) {
+                closest = (distance, cost, current);
+            }
+            if Self::default_success(current, goalu) {
+                break;
+            }
+
+            let next_cost = cost + 1;
+            if next_cost > max_cost {
+                continue;
+            }
+            for neighbor in grid.neighbors_with_state(current, false, false) {
+                if parents
+                    .get(&neighbor)
+                    .map_or(true, |&(_, best)| next_cost < best)
+                {
+                    parents.insert(neighbor, (current, next_cost));
+                    open.push(Reverse((
+                        next_cost + Self::default_heuristic(neighbor, goalu),
+                        next_cost,
+                        neighbor,
+                    )));
+                }
+            }
+        }
+
+        let end = closest.2;
+        let mut path = vec![end];
+        let mut current = end;
+        while current != startu {
+            current = parents[&current].0;
+            path.push(current);
+        }
+        path.reverse();
+
+        (
+            path.into_iter().map(std::convert::Into::into).collect(),
+            end == goalu,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::data::PremadeGrids;
+    use crate::util::testing::crate_before_test;
+
+    #[test]
+    fn budgeted_a_star() {
+        crate_before_test();
+
+        let grid = MapGrid::parse_string(
+            "##########\n#........#\n#........#\n#........#\n##########",
+            '#',
+            '.',
+        )
+        .expect("Unable to parse grid!");
+
+        let (path, reached) = Pathfinding::a_star_budgeted(&grid, (1, 2), (8, 2), 20);
+        assert!(reached);
+        assert_eq!(path.first(), Some(&(1, 2).into()));
+        assert_eq!(path.last(), Some(&(8, 2).into()));
+
+        let (path, reached) = Pathfinding::a_star_budgeted(&grid, (1, 2), (8, 2), 3);
+        assert!(!reached);
+        assert_eq!(path.len(), 4);
+        assert_eq!(path.first(), Some(&(1, 2).into()));
+        assert_eq!(path.last(), Some(&(4, 2).into()));
+    }
+
+    #[test]
+    fn avoiding_a_star() {
+        crate_before_test();
+
+        let grid = MapGrid::parse_string(
+            "#########\n#.......#\n#.#####.#\n#.......#\n#########",
+            '#',
+            '.',
+        )
+        .expect("Unable to parse grid!");
+        let guard: GridPos = (4, 3).into();
+
+        let detour = Pathfinding::a_star_avoiding(&grid, (1, 3), (7, 3), &[guard], 10)
+            .expect("Unable to find path!");
+        assert!(!detour.contains(&guard));
+        assert!(detour.iter().any(|p| p.y == 1));
+
+        let direct = Pathfinding::a_star_avoiding(&grid, (1, 3), (7, 3), &[guard], 1)
+            .expect("Unable to find path!");
+        assert!(direct.contains(&guard));
+        assert_eq!(direct.len(), 7);
+    }
+
+    #[test]
+    fn k_shortest_paths() {
+        crate_before_test();
+
+        let grid = MapGrid::parse_string("#####\n#...#\n#.#.#\n#...#\n#####", '#', '.')
+            .expect("Unable to parse grid!");
+        let paths = Pathfinding::k_shortest(&grid, (1, 1), (3, 3), 2);
+        assert_eq!(paths.len(), 2);
+        assert_ne!(paths[0], paths[1]);
+        for path in &paths {
+            assert_eq!(path.len(), 4);
+            assert_eq!(path.first(), Some(&GridPos::new(1, 1)));
+            assert_eq!(path.last(), Some(&GridPos::new(3, 3)));
+        }
+
+        let more = Pathfinding::k_shortest(&grid, (1, 1), (3, 3), 5);
+        assert_eq!(more.len(), 5);
+        assert!(more.windows(2).all(|w| w[0].len() <= w[1].len()));
+        assert!(Pathfinding::k_shortest(&grid, (1, 1), (2, 2), 2).is_empty());
+    }
+
+    #[test]
+    fn turn_penalized_a_star() {
+        crate_before_test();
+
+        let grid = PremadeGrids::maze5();
+        let (start, goal) = ((1, 1), (19, 18));
+        let plain = Pathfinding::a_star(&grid, start, goal).expect("Unable to find path!");
+        let straight = Pathfinding::a_star_turn_penalized(&grid, start, goal, 3)
+            .expect("Unable to find path!");
+        assert_eq!(straight.first(), Some(&GridPos::new(1, 1)));
+        assert_eq!(straight.last(), Some(&GridPos::new(19, 18)));
+        assert!(MapGrid::count_turns(&straight) < MapGrid::count_turns(&plain));
+        assert!(straight.len() >= plain.len());
+
+        let free = Pathfinding::a_star_turn_penalized(&grid, start, goal, 0)
+            .expect("Unable to find path!");
+        assert_eq!(free.len(), plain.len());
+    }
+
+    #[test]
+    fn cached_search_matches_live() {
+        crate_before_test();
+
+        let grid = PremadeGrids::maze5();
+        let cache = NeighborCache::build(&grid, true);
+        let (start, goal) = ((1, 1), (19, 18));
+
+        let live = Pathfinding::a_star(&grid, start, goal).expect("Unable to find path!");
+        let cached = Pathfinding::a_star_cached(&cache, start, goal).expect("Unable to find path!");
+        assert_eq!(cached.len(), live.len());
+        assert_eq!(cached.first(), Some(&GridPos::new(1, 1)));
+        assert_eq!(cached.last(), Some(&GridPos::new(19, 18)));
+
+        let live = Pathfinding::bfs(&grid, start, goal).expect("Unable to find path!");
+        let cached = Pathfinding::bfs_cached(&cache, start, goal).expect("Unable to find path!");
+        assert_eq!(cached.len(), live.len());
+        assert_eq!(cached.last(), Some(&GridPos::new(19, 18)));
+
+        assert!(Pathfinding::a_star_cached(&cache, start, (0, 0)).is_none());
+        assert!(Pathfinding::bfs_cached(&cache, start, (0, 0)).is_none());
+    }
 }
@@ -1,5 +1,6 @@
 use crate::{
-    data::MapGrid,
+    data::{GridSize, GridSquare, MapGrid},
+    gen::cell_auto::{Algorithm, CellularAutomata},
     pipe::{
         changes::{Changelist, GridChange},
         context::Context,
@@ -170,6 +171,91 @@ impl Step for SetEntireColumnStep {
     }
 }
 
+struct GenerateStep {
+    size: GridSize,
+    fill: f64,
+}
+
+impl GenerateStep {
+    pub fn new<Size: Into<GridSize>>(size: Size, fill: f64) -> Self {
+        Self {
+            size: size.into(),
+            fill,
+        }
+    }
+}
+
+impl Step for GenerateStep {
+    fn run<'parent>(
+        &mut self,
+        _ctx: &Context<'parent>,
+        _current: &MapGrid,
+    ) -> Result<StepOutput, Error> {
+        Ok(StepOutput {
+            output: MapGrid::random_fill_percent(self.size, self.fill),
+            changes: Changelist::new(),
+        })
+    }
+}
+
+struct CellularAutomataStep {
+    passes: usize,
+}
+
+impl Step for CellularAutomataStep {
+    fn run<'parent>(
+        &mut self,
+        _ctx: &Context<'parent>,
+        current: &MapGrid,
+    ) -> Result<StepOutput, Error> {
+        let output = CellularAutomata::execute_on(current, self.passes, Algorithm::default_first());
+        let changes = Changelist::from_changes(
+            current
+                .iter_pos()
+                .zip(output.iter())
+                .filter(|((_, before), after)| before.state() != after.state())
+                .map(|(((x, y), before), after)| GridChange {
+                    row: y,
+                    col: x,
+                    prev_value: before.state(),
+                    new_value: after.state(),
+                }),
+        );
+
+        Ok(StepOutput { output, changes })
+    }
+}
+
+struct CropStep {
+    section: GridSquare,
+}
+
+impl Step for CropStep {
+    fn run<'parent>(
+        &mut self,
+        _ctx: &Context<'parent>,
+        current: &MapGrid,
+    ) -> Result<StepOutput, Error> {
+        let (width, height) = current.size().into();
+        if self.section.width() < 3
+            || self.section.height() < 3
+            || self.section.max.x > width
+            || self.section.max.y > height
+        {
+            return Err(Error::Other(format!(
+                "Unable to crop {:?} from a grid of size {:?}",
+                self.section,
+                current.size()
+            )));
+        }
+
+        Ok(StepOutput {
+            output: current.create_subgrid(&self.section),
+            changes: Changelist::new(),
+        })
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -337,4 +423,42 @@ mod test {
             ".....\n.###.\n.###.\n.###.\n....."
         );
     }
+
+    #[test]
+    fn grid_transform_works() {
+        crate::util::testing::crate_before_test();
+
+        let result = MapGrid::empty((3, 3))
+            .transform(vec![
+                Box::new(GenerateStep::new((30, 20), 0.45)),
+                Box::new(CellularAutomataStep { passes: 3 }),
+                Box::new(CropStep {
+                    section: crate::data::square(&(5, 5), 20, 10),
+                }),
+            ])
+            .expect("Pipeline returned error!");
+        assert_eq!(result.original.size(), (3, 3).into());
+        assert_eq!(result.history.len(), 3);
+        assert_eq!(result.history[&2].before.size(), (30, 20).into());
+        assert_eq!(result.result.size(), (20, 10).into());
+        assert_eq!(result.result.invalid_cells_count(), 0);
+        assert_eq!(
+            result.result.to_strings(),
+            result.history[&2]
+                .after
+                .create_subgrid(&crate::data::square(&(5, 5), 20, 10))
+                .to_strings()
+        );
+
+        assert!(matches!(
+            MapGrid::empty((3, 3)).transform(Vec::new()),
+            Err(Error::NoSteps)
+        ));
+        assert!(matches!(
+            MapGrid::empty((3, 3)).transform(vec![Box::new(CropStep {
+                section: crate::data::square(&(2, 2), 5, 5),
+            })]),
+            Err(Error::Other(_))
+        ));
+    }
 }
@@ -85,6 +85,11 @@ impl<'pipeline> Pipeline<'pipeline> {
         self.steps.push(Box::new(step));
     }
 
+    /// Adds the given already boxed step to the pipeline.
+    pub fn add_boxed_step(&mut self, step: Box<dyn Step + 'pipeline>) {
+        self.steps.push(step);
+    }
+
     /// Returns `true` if this pipeline currently has no steps added to it.
     #[must_use]
     pub fn is_empty(&self) -> bool {
@@ -88,22 +88,203 @@ pub fn bresenham_line<P1: Into<(usize, usize)>, P2: Into<(usize, usize)>>(
     points
 }
 
-/// Calculates a curved line between two points.
+/// Thick variant of [`bresenham_line`].
 ///
-/// This uses a coin-flip to determine if the middle point is (first.x, second.y) or (second.x, first.y).
+/// Returns every cell within `thickness` (Chebyshev distance) of a cell on the line from `first_point` to
+/// `second_point`, sorted and without duplicates. A `thickness` of 0 is the same as [`bresenham_line`],
+/// while a `thickness` of 1 produces a band three cells wide. Cells that would fall at negative
+/// coordinates are discarded.
+#[must_use]
+pub fn bresenham_line_thick<P1: Into<(usize, usize)>, P2: Into<(usize, usize)>>(
+    first_point: P1,
+    second_point: P2,
+    thickness: usize,
+) -> Vec<(usize, usize)> {
+    let mut points = Vec::new();
+    for (x, y) in bresenham_line(first_point, second_point) {
+        for py in y.saturating_sub(thickness)..=(y + thickness) {
+            for px in x.saturating_sub(thickness)..=(x + thickness) {
+                points.push((px, py));
+            }
+        }
+    }
+    points.sort_unstable();
+    points.dedup();
+
+    points
+}
+
+/// Samples `samples` evenly-spaced points along the line segment from `first` to `second`.
 ///
-/// TODO: Currently this algorithm uses 1000 steps and then dedups the resulting point array, but it can probably be done better by calculating the distance between the two points and using a calculation from that value to determine the maximum steps, so that two points that are adjacent don't use the same number of steps as two points that are 1000 units apart.
+/// Unlike [`bresenham_line`], the sampling density is independent of the grid resolution. The
+/// resulting points can be rounded to cells by the caller. Both endpoints are included whenever
+/// `samples` is at least 2, a `samples` of 1 returns only `first` and 0 returns an empty [`Vec`].
+#[allow(clippy::cast_precision_loss)]
+#[must_use]
+pub fn line_samples(first: (f64, f64), second: (f64, f64), samples: usize) -> Vec<(f64, f64)> {
+    match samples {
+        0 => Vec::new(),
+        1 => vec![first],
+        _ => {
+            let steps = (samples - 1) as f64;
+            (0..samples)
+                .map(|i| {
+                    let t = i as f64 / steps;
+                    (
+                        first.0 + (second.0 - first.0) * t,
+                        first.1 + (second.1 - first.1) * t,
+                    )
+                })
+                .collect()
+        }
+    }
+}
+
+/// Finds where the segment from `a1` to `a2` crosses the segment from `b1` to `b2`, rounded to the
+/// nearest cell. Returns [`None`] if the segments do not touch, or if they are parallel (including
+/// collinear segments that overlap, which have no single crossing point).
 #[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_possible_wrap,
     clippy::cast_precision_loss,
+    clippy::cast_sign_loss
+)]
+#[must_use]
+pub fn segments_intersect(a1: GridPos, a2: GridPos, b1: GridPos, b2: GridPos) -> Option<GridPos> {
+    let cross = |(x1, y1): (i64, i64), (x2, y2): (i64, i64)| x1 * y2 - y1 * x2;
+    let signed = |p: GridPos| (p.x as i64, p.y as i64);
+    let (a1, a2, b1, b2) = (signed(a1), signed(a2), signed(b1), signed(b2));
+    let r = (a2.0 - a1.0, a2.1 - a1.1);
+    let s = (b2.0 - b1.0, b2.1 - b1.1);
+    let offset = (b1.0 - a1.0, b1.1 - a1.1);
+
+    let denom = cross(r, s);
+    if denom == 0 {
+        return None;
+    }
+
+    // Intersection is at `a1 + r * t` and `b1 + s * u`, with t = t_num / denom and u = u_num / denom
+    let (t_num, u_num, denom) = if denom < 0 {
+        (-cross(offset, s), -cross(offset, r), -denom)
+    } else {
+        (cross(offset, s), cross(offset, r), denom)
+    };
+    if !(0..=denom).contains(&t_num) || !(0..=denom).contains(&u_num) {
+        return None;
+    }
+
+    let t = t_num as f64 / denom as f64;
+    Some(GridPos::new(
+        (a1.0 as f64 + r.0 as f64 * t).round() as usize,
+        (a1.1 as f64 + r.1 as f64 * t).round() as usize,
+    ))
+}
+
+/// Midpoint (Bresenham's) circle algorithm.
+///
+/// Returns the cells making up the perimeter of a circle with the given `radius` around `center`,
+/// sorted and without duplicates. Any cells that would fall at negative coordinates are discarded,
+/// and a `radius` of 0 returns only the `center`.
+///
+/// #### See [`Midpoint circle algorithm`](`https://en.wikipedia.org/wiki/Midpoint_circle_algorithm`).
+#[allow(
     clippy::cast_possible_truncation,
+    clippy::cast_possible_wrap,
     clippy::cast_sign_loss
 )]
+#[must_use]
+pub fn bresenham_circle<P: Into<(usize, usize)>>(center: P, radius: usize) -> Vec<(usize, usize)> {
+    let center = center.into();
+    if radius == 0 {
+        return vec![center];
+    }
+
+    let (cx, cy) = (center.0 as i64, center.1 as i64);
+    let mut points = Vec::new();
+    let mut x = radius as i64;
+    let mut y = 0i64;
+    let mut err = 1 - x;
+    while x >= y {
+        for (dx, dy) in [
+            (x, y),
+            (y, x),
+            (-y, x),
+            (-x, y),
+            (-x, -y),
+            (-y, -x),
+            (y, -x),
+            (x, -y),
+        ] {
+            let (px, py) = (cx + dx, cy + dy);
+            if px >= 0 && py >= 0 {
+                points.push((px as usize, py as usize));
+            }
+        }
+
+        y += 1;
+        if err < 0 {
+            err += 2 * y + 1;
+        } else {
+            x -= 1;
+            err += 2 * (y - x) + 1;
+        }
+    }
+    points.sort_unstable();
+    points.dedup();
+
+    points
+}
+
+/// Calculates a curved line between two points.
+///
+/// This uses a coin-flip to determine if the middle point is (first.x, second.y) or (second.x, first.y).
+///
+/// TODO: Currently this algorithm uses 1000 steps and then dedups the resulting point array, but it can probably be done better by calculating the distance between the two points and using a calculation from that value to determine the maximum steps, so that two points that are adjacent don't use the same number of steps as two points that are 1000 units apart.
 pub fn get_curve_between<P1: Into<(usize, usize)>, P2: Into<(usize, usize)>>(
     first_point: P1,
     second_point: P2,
 ) -> Vec<(usize, usize)> {
-    let first = first_point.into();
-    let second = second_point.into();
+    let mut points = curve_points(first_point.into(), second_point.into());
+    points.sort_unstable();
+    points.dedup();
+
+    points
+}
+
+/// Calculates a continuous curved line passing through each of the given `points` in order, by chaining
+/// the curves (see [`get_curve_between`]) between each consecutive pair. Unlike [`get_curve_between`]
+/// the result is ordered from the first point to the last, and the cells where two curves join are
+/// only included once.
+#[must_use]
+pub fn get_curve_through(points: &[GridPos]) -> Vec<(usize, usize)> {
+    let mut path: Vec<(usize, usize)> = Vec::new();
+    if let Some(&first) = points.first() {
+        path.push(first.into());
+    }
+
+    for pair in points.windows(2) {
+        let end: (usize, usize) = pair[1].into();
+        for point in curve_points(pair[0].into(), end)
+            .into_iter()
+            .chain(std::iter::once(end))
+        {
+            if path.last() != Some(&point) {
+                path.push(point);
+            }
+        }
+    }
+
+    path
+}
+
+/// Samples the curve between `first` and `second` (see [`get_curve_between`]) in order, skipping
+/// consecutive duplicate cells.
+#[allow(
+    clippy::cast_precision_loss,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss
+)]
+fn curve_points(first: (usize, usize), second: (usize, usize)) -> Vec<(usize, usize)> {
     let mid = if fastrand::bool() {
         (first.0, second.1)
     } else {
@@ -127,7 +308,6 @@ pub fn get_curve_between<P1: Into<(usize, usize)>, P2: Into<(usize, usize)>>(
         let f2 = fp.axis(1);
         points.push((f1.round() as usize, f2.round() as usize));
     }
-    points.sort_unstable();
     points.dedup();
 
     points
@@ -180,6 +360,10 @@ where
 mod tests {
     use super::*;
 
+    use assert_float_eq::{
+        afe_abs, afe_absolute_error_msg, afe_is_absolute_eq, assert_float_absolute_eq,
+    };
+
     #[test]
     fn absdiff_test() {
         assert_eq!(absdiff(4u32, 17u32), 13u32);
@@ -188,4 +372,110 @@ mod tests {
         assert_eq!(absdiff(40usize, 17usize), 23usize);
         assert_eq!(absdiff(17usize, 40usize), 23usize);
     }
+
+    #[test]
+    fn line_samples_test() {
+        let samples = line_samples((0.0, 0.0), (10.0, 5.0), 11);
+        assert_eq!(samples.len(), 11);
+        assert_float_absolute_eq!(samples[0].0, 0.0);
+        assert_float_absolute_eq!(samples[0].1, 0.0);
+        assert_float_absolute_eq!(samples[10].0, 10.0);
+        assert_float_absolute_eq!(samples[10].1, 5.0);
+        assert_float_absolute_eq!(samples[2].0, 2.0);
+        assert_float_absolute_eq!(samples[2].1, 1.0);
+
+        assert_eq!(line_samples((1.0, 1.0), (2.0, 2.0), 1), vec![(1.0, 1.0)]);
+        assert!(line_samples((1.0, 1.0), (2.0, 2.0), 0).is_empty());
+    }
+
+    #[test]
+    fn segments_intersect_test() {
+        let (p, q) = (GridPos::new(2, 5), GridPos::new(10, 5));
+        let (r, s) = (GridPos::new(6, 1), GridPos::new(6, 9));
+        assert_eq!(segments_intersect(p, q, r, s), Some(GridPos::new(6, 5)));
+        assert_eq!(segments_intersect(r, s, q, p), Some(GridPos::new(6, 5)));
+
+        // Touching at an endpoint counts, falling short does not
+        assert_eq!(
+            segments_intersect(p, q, GridPos::new(10, 0), GridPos::new(10, 5)),
+            Some(q)
+        );
+        assert_eq!(
+            segments_intersect(p, q, GridPos::new(11, 0), GridPos::new(11, 9)),
+            None
+        );
+
+        // Diagonals crossing between cells are rounded to the nearest one
+        assert_eq!(
+            segments_intersect(
+                GridPos::new(0, 0),
+                GridPos::new(3, 3),
+                GridPos::new(0, 3),
+                GridPos::new(3, 0)
+            ),
+            Some(GridPos::new(2, 2))
+        );
+
+        assert_eq!(
+            segments_intersect(p, q, GridPos::new(2, 7), GridPos::new(10, 7)),
+            None
+        );
+        assert_eq!(
+            segments_intersect(p, q, GridPos::new(4, 5), GridPos::new(12, 5)),
+            None
+        );
+    }
+
+    #[test]
+    fn bresenham_circle_test() {
+        assert_eq!(bresenham_circle((4, 4), 0), vec![(4, 4)]);
+
+        let ring = bresenham_circle((5, 5), 3);
+        assert!(!ring.contains(&(5, 5)));
+        for &(x, y) in &ring {
+            assert!(ring.contains(&(10 - x, y)));
+            assert!(ring.contains(&(x, 10 - y)));
+            assert!(ring.contains(&(y, x)));
+        }
+        assert!(ring.contains(&(8, 5)));
+        assert!(ring.contains(&(5, 2)));
+
+        let clipped = bresenham_circle((1, 1), 3);
+        assert!(clipped.contains(&(4, 1)));
+        assert!(clipped.iter().all(|&(x, y)| x <= 4 && y <= 4));
+    }
+
+    #[test]
+    fn bresenham_line_thick_test() {
+        assert_eq!(
+            bresenham_line_thick((1, 1), (4, 1), 0),
+            bresenham_line((1, 1), (4, 1))
+        );
+
+        let band = bresenham_line_thick((2, 2), (5, 2), 1);
+        assert_eq!(band.len(), 6 * 3);
+        assert!(band.contains(&(1, 1)));
+        assert!(band.contains(&(6, 3)));
+
+        let clipped = bresenham_line_thick((0, 0), (0, 2), 1);
+        assert_eq!(clipped.len(), 2 * 4);
+    }
+
+    #[test]
+    fn curve_through_test() {
+        crate::util::random::init_rng_seeded(0);
+
+        let waypoints = [GridPos::new(2, 2), GridPos::new(10, 4), GridPos::new(5, 12)];
+        let path = get_curve_through(&waypoints);
+        assert_eq!(path.first(), Some(&(2, 2)));
+        assert_eq!(path.last(), Some(&(5, 12)));
+        assert!(path.contains(&(10, 4)));
+        for step in path.windows(2) {
+            assert_ne!(step[0], step[1]);
+            assert!(absdiff(step[0].0, step[1].0) <= 1 && absdiff(step[0].1, step[1].1) <= 1);
+        }
+
+        assert_eq!(get_curve_through(&waypoints[..1]), vec![(2, 2)]);
+        assert!(get_curve_through(&[]).is_empty());
+    }
 }
@@ -70,7 +70,7 @@ pub mod random;
 /// println!("Has a 3: {}", has_a_3); // prints "Has a 3: False"
 /// ```
 mod tri;
-pub use tri::TriState;
+pub use tri::{tri_state_str, TriState};
 
 #[cfg(test)]
 pub mod test_framework;
@@ -32,3 +32,82 @@ pub fn init_rng_seeded(seed: u64) {
     trace!("init_rng_seeded");
     fastrand::seed(seed);
 }
+
+/// Runs `f` with a local [`fastrand::Rng`] seeded with `seed`, returning its result. Unlike
+/// [`init_rng_seeded`] this does not touch the global RNG, so the outcome only depends on `seed` and
+/// not on whatever else has used the global RNG in the meantime.
+pub fn with_rng<R>(seed: u64, f: impl FnOnce(&mut fastrand::Rng) -> R) -> R {
+    trace!("with_rng({})", seed);
+    let mut rng = fastrand::Rng::with_seed(seed);
+    f(&mut rng)
+}
+
+/// Rolls a die with the given number of `sides`, returning a value in `1..=sides`.
+///
+/// ### Panics
+/// Function panics if `sides` is 0.
+#[must_use]
+pub fn dice(sides: u32) -> u32 {
+    trace!("dice({})", sides);
+    assert!(sides > 0, "A die must have at least one side");
+    fastrand::u32(1..=sides)
+}
+
+/// Returns true with the given probability `p`, where 0.0 is never and 1.0 is always. Values
+/// outside of that range are clamped.
+#[must_use]
+pub fn percent_chance(p: f64) -> bool {
+    trace!("percent_chance({})", p);
+    fastrand::f64() < p.clamp(0.0, 1.0)
+}
+
+/// Gets a random value in `lo..=hi`.
+///
+/// ### Panics
+/// Function panics if `lo` is greater than `hi`.
+#[must_use]
+pub fn range_inclusive(lo: usize, hi: usize) -> usize {
+    trace!("range_inclusive({}, {})", lo, hi);
+    assert!(
+        lo <= hi,
+        "Range lower bound must not exceed the upper bound"
+    );
+    fastrand::usize(lo..=hi)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn random_helpers() {
+        init_rng_seeded(1234);
+
+        for _ in 0..1000 {
+            assert!(!percent_chance(0.0));
+            assert!(percent_chance(1.0));
+            assert!((1..=6).contains(&dice(6)));
+            assert!((3..=5).contains(&range_inclusive(3, 5)));
+        }
+        assert_eq!(dice(1), 1);
+        assert_eq!(range_inclusive(7, 7), 7);
+    }
+
+    #[test]
+    fn scoped_rng() {
+        let roll = |rng: &mut fastrand::Rng| (0..32).map(|_| rng.u32(..)).collect::<Vec<_>>();
+        let first = with_rng(42, roll);
+        init_rng_seeded(7);
+        let _ = fastrand::u64(..);
+        let second = with_rng(42, roll);
+        assert_eq!(first, second);
+        assert_ne!(first, with_rng(43, roll));
+
+        // The global RNG is left alone
+        init_rng_seeded(99);
+        let expected = fastrand::u64(..);
+        init_rng_seeded(99);
+        let _ = with_rng(42, roll);
+        assert_eq!(fastrand::u64(..), expected);
+    }
+}
@@ -326,6 +326,47 @@ impl std::fmt::Display for TriState {
     }
 }
 
+impl std::str::FromStr for TriState {
+    type Err = String;
+
+    /// Parses a [`TriState`] from its [`std::fmt::Display`] form, ignoring case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "true" => Ok(TriState::True),
+            "false" => Ok(TriState::False),
+            "invalid" => Ok(TriState::Invalid),
+            _ => Err(format!("Unable to parse TriState from {:?}", s)),
+        }
+    }
+}
+
+/// Serde helpers for (de)serializing a [`TriState`] as its [`std::fmt::Display`] string (`"True"`,
+/// `"False"`, or `"Invalid"`), for use with `#[serde(with = "dungen::util::tri_state_str")]`.
+/// Deserializing ignores case, which is friendlier for hand-edited files.
+pub mod tri_state_str {
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    use super::TriState;
+
+    /// Serializes `state` as its display string.
+    ///
+    /// ### Errors
+    /// Function errors if the serializer fails to write the string.
+    pub fn serialize<S: Serializer>(state: &TriState, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(state)
+    }
+
+    /// Deserializes a [`TriState`] from its display string.
+    ///
+    /// ### Errors
+    /// Function errors if the value is not a string, or is not one of `True`, `False` or `Invalid`.
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<TriState, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(D::Error::custom)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -558,4 +599,25 @@ mod tests {
         assert_eq!(TriState::False.to_string(), "False");
         assert_eq!(TriState::Invalid.to_string(), "Invalid");
     }
+
+    #[test]
+    fn string_serde_round_trip() {
+        #[derive(Debug, PartialEq, Deserialize, Serialize)]
+        struct Wrapper {
+            #[serde(with = "tri_state_str")]
+            state: TriState,
+        }
+
+        for state in [TriState::True, TriState::False, TriState::Invalid] {
+            let json = serde_json::to_string(&Wrapper { state }).unwrap();
+            assert_eq!(json, format!(r#"{{"state":"{}"}}"#, state));
+            let back: Wrapper = serde_json::from_str(&json).unwrap();
+            assert_eq!(back.state, state);
+        }
+
+        let lower: Wrapper = serde_json::from_str(r#"{"state":"invalid"}"#).unwrap();
+        assert_eq!(lower.state, TriState::Invalid);
+        assert!(serde_json::from_str::<Wrapper>(r#"{"state":"maybe"}"#).is_err());
+        assert!(serde_json::from_str::<Wrapper>(r#"{"state":1}"#).is_err());
+    }
 }